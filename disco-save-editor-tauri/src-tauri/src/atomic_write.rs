@@ -0,0 +1,322 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::error::{AppError, AppResult, FileRole};
+
+/// Lists, inside a backup folder, the files it is for.
+const MANIFEST: &str = "manifest";
+/// First line of a manifest, so only backups this editor wrote are restored.
+const MANIFEST_HEADER: &str = "disco-save-editor backup 1";
+
+/// One file to replace: where it goes, what it contains, and which save file it is.
+pub struct StagedFile<'a> {
+    pub path: PathBuf,
+    pub content: &'a [u8],
+    pub role: FileRole,
+}
+
+/// Replace every file in `files` (all in one folder, all belonging to the
+/// save named `save`) or none of them.
+///
+/// All new contents are written and fsynced to temp files next to their
+/// targets first. A single file is then renamed over its target, which is
+/// atomic on its own. For several, every original is hardlinked (or copied)
+/// into a `.<save>.disco-backup` folder whose manifest lists the targets,
+/// and each temp is renamed straight over its target, so the target path
+/// always holds a whole file. If any step fails, the originals are put back.
+/// A crash midway leaves the backup folder behind, and `recover` puts it
+/// back before the save is read again.
+pub fn replace_files(save: &str, files: &[StagedFile]) -> AppResult<()> {
+    replace_and_remove(save, files, &[])
+}
+
+/// `replace_files`, also deleting the files at `removed` (in the same
+/// folder) as part of the same all-or-nothing change.
+pub fn replace_and_remove(save: &str, files: &[StagedFile], removed: &[PathBuf]) -> AppResult<()> {
+    replace_with(save, files, removed, |from, to| std::fs::rename(from, to))
+}
+
+fn replace_with(
+    save: &str,
+    files: &[StagedFile],
+    removed: &[PathBuf],
+    rename: impl Fn(&Path, &Path) -> std::io::Result<()>,
+) -> AppResult<()> {
+    let Some(first) = files.first().map(|f| f.path.as_path()).or(removed.first().map(PathBuf::as_path)) else { return Ok(()) };
+    let dir = first.parent().unwrap_or(Path::new("."));
+    recover(dir, save)?;
+
+    let staged = stage(files)?;
+    if let ([file], [temp], []) = (files, staged.as_slice(), removed) {
+        if let Err(e) = rename(temp, &file.path) {
+            remove_all(&staged);
+            return Err(AppError::io(file.role, &file.path, e));
+        }
+        sync_dir(dir);
+        return Ok(());
+    }
+
+    let backup = backup_dir(dir, save);
+    if let Err(e) = back_up(&backup, files, removed) {
+        let _ = std::fs::remove_dir_all(&backup);
+        remove_all(&staged);
+        return Err(e);
+    }
+    sync_dir(dir);
+
+    for (file, temp) in files.iter().zip(&staged) {
+        if let Err(e) = rename(temp, &file.path) {
+            if restore(&backup, dir).is_ok() {
+                discard(&backup);
+            }
+            remove_all(&staged);
+            return Err(AppError::io(file.role, &file.path, e));
+        }
+    }
+    for path in removed {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                if restore(&backup, dir).is_ok() {
+                    discard(&backup);
+                }
+                return Err(AppError::io(FileRole::Archive, path, e));
+            }
+            _ => {}
+        }
+    }
+    sync_dir(dir);
+
+    discard(&backup);
+    Ok(())
+}
+
+/// Put back the originals of a replace of `save`'s files in `dir` that
+/// didn't finish. Only a backup whose manifest names files of this save is
+/// touched; anything else in `dir` is left alone. Returns the files that
+/// were restored.
+pub fn recover(dir: &Path, save: &str) -> AppResult<Vec<PathBuf>> {
+    let backup = backup_dir(dir, save);
+    let _ = std::fs::remove_dir_all(done_dir(&backup));
+    if !backup.is_dir() || read_manifest(&backup, save).is_none() {
+        return Ok(Vec::new());
+    }
+    let restored = restore(&backup, dir)?;
+    discard(&backup);
+    sync_dir(dir);
+    Ok(restored)
+}
+
+/// Write and fsync every new content to its temp file.
+fn stage(files: &[StagedFile]) -> AppResult<Vec<PathBuf>> {
+    let mut staged = Vec::with_capacity(files.len());
+    for file in files {
+        let name = file.path.file_name().unwrap_or_default().to_string_lossy();
+        let temp = file.path.with_file_name(format!(".{}.tmp", name));
+        let written = File::create(&temp)
+            .and_then(|mut f| f.write_all(file.content).and_then(|_| f.sync_all()));
+        staged.push(temp);
+        if let Err(e) = written {
+            remove_all(&staged);
+            return Err(AppError::io(file.role, &file.path, e));
+        }
+    }
+    Ok(staged)
+}
+
+/// Keep every original in `backup`, and write a manifest listing each
+/// target as `kept` or, when it doesn't exist yet, `created`, so a rollback
+/// can remove it. Files to remove are kept like any other original.
+fn back_up(backup: &Path, files: &[StagedFile], removed: &[PathBuf]) -> AppResult<()> {
+    let role = files.first().map_or(FileRole::Archive, |f| f.role);
+    std::fs::create_dir(backup).map_err(|e| AppError::io(role, backup, e))?;
+    let mut manifest = format!("{}\n", MANIFEST_HEADER);
+    let targets = files.iter().map(|f| (f.path.as_path(), f.role))
+        .chain(removed.iter().map(|p| (p.as_path(), FileRole::Archive)));
+    for (path, role) in targets {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if !path.exists() {
+            manifest.push_str(&format!("created {}\n", name));
+            continue;
+        }
+        let kept = backup.join(name.as_ref());
+        std::fs::hard_link(path, &kept)
+            .or_else(|_| std::fs::copy(path, &kept).map(|_| ()))
+            .map_err(|e| AppError::io(role, path, e))?;
+        manifest.push_str(&format!("kept {}\n", name));
+    }
+    File::create(backup.join(MANIFEST))
+        .and_then(|mut f| f.write_all(manifest.as_bytes()).and_then(|_| f.sync_all()))
+        .map_err(|e| AppError::io(role, backup, e))?;
+    sync_dir(backup);
+    Ok(())
+}
+
+/// The `(kept, name)` entries of a backup's manifest, or None unless it is
+/// one of ours and every name is a plain file name of `save`.
+fn read_manifest(backup: &Path, save: &str) -> Option<Vec<(bool, String)>> {
+    let text = std::fs::read_to_string(backup.join(MANIFEST)).ok()?;
+    let mut lines = text.lines();
+    if lines.next()? != MANIFEST_HEADER {
+        return None;
+    }
+    let prefix = format!("{}.", save);
+    lines.filter(|l| !l.is_empty())
+        .map(|line| {
+            let (kind, name) = line.split_once(' ')?;
+            let plain = !name.contains(['/', '\\']) && name.starts_with(&prefix);
+            match kind {
+                "kept" if plain => Some((true, name.to_string())),
+                "created" if plain => Some((false, name.to_string())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Move the originals in `backup` back over their targets and remove the
+/// targets that didn't exist before.
+fn restore(backup: &Path, dir: &Path) -> AppResult<Vec<PathBuf>> {
+    let text = std::fs::read_to_string(backup.join(MANIFEST)).map_err(|e| AppError::io(FileRole::Archive, backup, e))?;
+    let mut restored = Vec::new();
+    for line in text.lines().skip(1) {
+        match line.split_once(' ') {
+            Some(("kept", name)) => {
+                let target = dir.join(name);
+                std::fs::rename(backup.join(name), &target).map_err(|e| AppError::io(FileRole::Archive, &target, e))?;
+                restored.push(target);
+            }
+            Some(("created", name)) => {
+                let _ = std::fs::remove_file(dir.join(name));
+            }
+            _ => {}
+        }
+    }
+    Ok(restored)
+}
+
+/// Drop a backup that is no longer needed. It is renamed first, so a crash
+/// while deleting it can't leave a partial backup that looks restorable.
+fn discard(backup: &Path) {
+    let done = done_dir(backup);
+    if std::fs::rename(backup, &done).is_ok() {
+        let _ = std::fs::remove_dir_all(&done);
+    }
+}
+
+fn remove_all(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn backup_dir(dir: &Path, save: &str) -> PathBuf {
+    dir.join(format!(".{}.disco-backup", save))
+}
+
+fn done_dir(backup: &Path) -> PathBuf {
+    backup.with_file_name(format!("{}.done", backup.file_name().unwrap_or_default().to_string_lossy()))
+}
+
+/// Persist the renames themselves. Directories can't be opened for syncing
+/// on Windows, where renames are durable once they return.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(d) = File::open(dir) {
+        let _ = d.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVE: &str = "SAVE";
+
+    fn staged<'a>(dir: &Path, name: &str, content: &'a [u8]) -> StagedFile<'a> {
+        StagedFile { path: dir.join(name), content, role: FileRole::First }
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn replaces_every_file_and_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("SAVE.1st"), "old 1").unwrap();
+        std::fs::write(dir.path().join("SAVE.2nd"), "old 2").unwrap();
+
+        let files = [staged(dir.path(), "SAVE.1st", b"new 1"), staged(dir.path(), "SAVE.2nd", b"new 2"), staged(dir.path(), "SAVE.FOW", b"{}")];
+        replace_files(SAVE, &files).unwrap();
+
+        assert_eq!(std::fs::read_to_string(dir.path().join("SAVE.1st")).unwrap(), "new 1");
+        assert_eq!(std::fs::read_to_string(dir.path().join("SAVE.2nd")).unwrap(), "new 2");
+        assert_eq!(std::fs::read_to_string(dir.path().join("SAVE.FOW")).unwrap(), "{}");
+        assert_eq!(names(dir.path()), ["SAVE.1st", "SAVE.2nd", "SAVE.FOW"]);
+    }
+
+    #[test]
+    fn failed_rename_rolls_back_and_removes_created_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("SAVE.1st"), "old 1").unwrap();
+        std::fs::write(dir.path().join("SAVE.2nd"), "old 2").unwrap();
+
+        let files = [staged(dir.path(), "SAVE.1st", b"new 1"), staged(dir.path(), "SAVE.FOW", b"{}"), staged(dir.path(), "SAVE.2nd", b"new 2")];
+        let result = replace_with(SAVE, &files, &[], |from, to| {
+            if to.ends_with("SAVE.2nd") {
+                return Err(std::io::Error::other("disk full"));
+            }
+            std::fs::rename(from, to)
+        });
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(dir.path().join("SAVE.1st")).unwrap(), "old 1");
+        assert_eq!(std::fs::read_to_string(dir.path().join("SAVE.2nd")).unwrap(), "old 2");
+        assert_eq!(names(dir.path()), ["SAVE.1st", "SAVE.2nd"]);
+    }
+
+    #[test]
+    fn recovers_an_interrupted_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let backup = dir.path().join(".SAVE.disco-backup");
+        std::fs::create_dir(&backup).unwrap();
+        std::fs::write(backup.join("SAVE.1st"), "old 1").unwrap();
+        std::fs::write(backup.join(MANIFEST), format!("{}\nkept SAVE.1st\ncreated SAVE.FOW\n", MANIFEST_HEADER)).unwrap();
+        std::fs::write(dir.path().join("SAVE.1st"), "new 1").unwrap();
+        std::fs::write(dir.path().join("SAVE.FOW"), "{}").unwrap();
+
+        let restored = recover(dir.path(), SAVE).unwrap();
+
+        assert_eq!(restored, [dir.path().join("SAVE.1st")]);
+        assert_eq!(std::fs::read_to_string(dir.path().join("SAVE.1st")).unwrap(), "old 1");
+        assert_eq!(names(dir.path()), ["SAVE.1st"]);
+    }
+
+    #[test]
+    fn leaves_unrelated_dot_entries_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        for other in [".bashrc.backup", ".bashrc.backup.done", ".OTHER.disco-backup"] {
+            std::fs::create_dir(dir.path().join(other)).unwrap();
+            std::fs::write(dir.path().join(other).join("file"), "keep").unwrap();
+        }
+        std::fs::write(dir.path().join(".OTHER.disco-backup").join(MANIFEST), format!("{}\nkept file\n", MANIFEST_HEADER)).unwrap();
+        std::fs::write(dir.path().join(".bashrc.orig"), "keep").unwrap();
+        std::fs::write(dir.path().join(".bashrc"), "mine").unwrap();
+        // A backup named after this save but not written by the editor
+        std::fs::create_dir(dir.path().join(".SAVE.disco-backup")).unwrap();
+        std::fs::write(dir.path().join(".SAVE.disco-backup").join("SAVE.1st"), "keep").unwrap();
+
+        let before = names(dir.path());
+        assert!(recover(dir.path(), SAVE).unwrap().is_empty());
+        assert_eq!(names(dir.path()), before);
+        assert_eq!(std::fs::read_to_string(dir.path().join(".bashrc")).unwrap(), "mine");
+        assert_eq!(std::fs::read_to_string(dir.path().join(".OTHER.disco-backup").join("file")).unwrap(), "keep");
+        assert!(dir.path().join(".SAVE.disco-backup").join("SAVE.1st").exists());
+    }
+}
@@ -0,0 +1,462 @@
+//! Headless command line front end for inspecting and editing saves.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use serde_json::Value;
+
+use app_lib::error::{AppError, AppResult};
+use app_lib::game_data::GameDataService;
+use app_lib::lua_database::{self, ParseMode};
+use app_lib::models::{FileChangeKind, LuaValue};
+use app_lib::save_diff;
+use app_lib::save_discovery;
+use app_lib::save_service::{self, LoadedSave};
+use app_lib::settings;
+use app_lib::snapshots::SnapshotStore;
+use app_lib::variable_report;
+
+#[derive(Parser)]
+#[command(name = "disco-save-cli", version, about = "Inspect and edit Disco Elysium saves without the GUI")]
+struct Cli {
+    /// Folder holding the bundled game data JSON files
+    #[arg(long, global = true, env = "DISCO_GAME_DATA")]
+    game_data: Option<PathBuf>,
+
+    /// Load the Lua database in salvage mode, skipping malformed bytes
+    #[arg(long, global = true)]
+    salvage: bool,
+
+    /// Allow writing a salvaged save, losing the Lua bytes salvage mode skipped
+    #[arg(long, global = true)]
+    discard_salvaged: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List saves found in the known save folders
+    ListSaves {
+        /// Extra folder to scan, on top of the configured ones (repeatable)
+        #[arg(long = "root")]
+        roots: Vec<PathBuf>,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a short summary of a save
+    Info {
+        save: String,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print Lua variables by dotted key
+    Get {
+        save: String,
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    /// Set existing Lua variables, keeping their type (`key=value` ...)
+    Set {
+        save: String,
+        #[arg(required = true, value_parser = parse_assignment)]
+        assignments: Vec<(String, String)>,
+    },
+    /// Dump a save as JSON
+    Dump {
+        save: String,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Apply a JSON patch file to one or more saves
+    ///
+    /// The patch is an object with optional `lua` (dotted key -> value),
+    /// `first` and `second` (JSON pointer -> value) sections.
+    Apply {
+        patch: PathBuf,
+        #[arg(required = true)]
+        saves: Vec<String>,
+    },
+    /// Show every value that differs between two saves
+    Diff {
+        a: String,
+        b: String,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the Lua variables the playthrough changed from their defaults
+    Report {
+        save: String,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage a save's snapshots
+    Snapshot(SnapshotArgs),
+}
+
+#[derive(Args)]
+struct SnapshotArgs {
+    save: String,
+    #[command(subcommand)]
+    command: SnapshotCommand,
+}
+
+#[derive(Subcommand)]
+enum SnapshotCommand {
+    /// List snapshots, newest first
+    List {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Snapshot the save as it is now
+    Create {
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// Show which files changed since a snapshot
+    Diff {
+        from: String,
+        /// Compare with this snapshot instead of the save on disk
+        to: Option<String>,
+    },
+    /// Replace the save with a snapshot
+    Restore { id: String },
+    /// Delete snapshots the retention policy doesn't keep
+    Prune,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum CliError {
+    #[error(transparent)]
+    App(#[from] AppError),
+
+    #[error("Failed to read {path}: {source}")]
+    Io { path: String, source: std::io::Error },
+
+    #[error("Invalid patch {path}: {message}")]
+    Patch { path: String, message: String },
+
+    #[error("{0}")]
+    Usage(String),
+}
+
+type CliResult<T> = Result<T, CliError>;
+
+#[derive(Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Patch {
+    #[serde(default)]
+    lua: BTreeMap<String, Value>,
+    #[serde(default)]
+    first: BTreeMap<String, Value>,
+    #[serde(default)]
+    second: BTreeMap<String, Value>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    let mode = if cli.salvage { ParseMode::Salvage } else { ParseMode::Strict };
+
+    match cli.command {
+        Command::ListSaves { mut roots, json } => {
+            roots.extend(settings::load_settings()?.extra_save_roots.iter().map(PathBuf::from));
+            let saves = save_discovery::discover_saves(&roots)?;
+            if json {
+                print_json(&saves)?;
+            } else {
+                for save in saves {
+                    let details = save.details.as_ref()
+                        .map(|d| format!("day {} {:02}:{:02} {}", d.day, d.hours, d.minutes, d.area_id))
+                        .unwrap_or_else(|| "-".to_string());
+                    println!("{}\t{}\t{}\t{}", save.last_modified, save.name, details, save.path);
+                }
+            }
+        }
+        Command::Info { save, json } => {
+            let gd = load_game_data(cli.game_data.as_deref())?;
+            let loaded = load(&save, &gd, mode)?;
+            let info = save_info(&save, &loaded);
+            if json {
+                let map: serde_json::Map<String, Value> = info.into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect();
+                print_json(&map)?;
+            } else {
+                for (key, value) in info {
+                    println!("{:<16}{}", key, value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()));
+                }
+            }
+        }
+        Command::Get { save, keys } => {
+            let gd = load_game_data(cli.game_data.as_deref())?;
+            let loaded = load(&save, &gd, mode)?;
+            let flat = lua_database::flatten_lua(&loaded.lua_db);
+            for key in keys {
+                let value = flat.get(&key).ok_or_else(|| AppError::UnknownVariable(key.clone()))?;
+                println!("{}\t{}\t{}", key, value.type_name(), value.to_display_string());
+            }
+        }
+        Command::Set { save, assignments } => {
+            let gd = load_game_data(cli.game_data.as_deref())?;
+            let mut loaded = load(&save, &gd, mode)?;
+            let edits = assignments.iter().map(|(key, text)| (key.as_str(), text.as_str()));
+            save_service::apply_lua_edits(&mut loaded.lua_db, edits, &gd)?;
+            write(&save, &loaded, cli.discard_salvaged)?;
+            println!("{}: set {} variable(s)", save, assignments.len());
+        }
+        Command::Dump { save, output } => {
+            let gd = load_game_data(cli.game_data.as_deref())?;
+            let loaded = load(&save, &gd, mode)?;
+            let lua: BTreeMap<String, LuaValue> = lua_database::flatten_lua(&loaded.lua_db).into_iter().collect();
+            let dump = serde_json::json!({
+                "first": loaded.first_raw,
+                "second": loaded.second_raw,
+                "lua": lua,
+                "states": loaded.states,
+                "lua_salvage": loaded.lua_salvage,
+            });
+            match output {
+                Some(path) => {
+                    let text = serde_json::to_string_pretty(&dump).map_err(|e| CliError::Usage(e.to_string()))?;
+                    std::fs::write(&path, text).map_err(|e| CliError::Io { path: path.display().to_string(), source: e })?;
+                }
+                None => print_json(&dump)?,
+            }
+        }
+        Command::Apply { patch, saves } => {
+            let patch_path = patch.display().to_string();
+            let text = std::fs::read_to_string(&patch)
+                .map_err(|e| CliError::Io { path: patch_path.clone(), source: e })?;
+            let patch: Patch = serde_json::from_str(&text)
+                .map_err(|e| CliError::Patch { path: patch_path.clone(), message: e.to_string() })?;
+
+            let gd = load_game_data(cli.game_data.as_deref())?;
+            for save in &saves {
+                let mut loaded = load(save, &gd, mode)?;
+                apply_patch(&patch, &patch_path, &mut loaded, &gd)?;
+                write(save, &loaded, cli.discard_salvaged)?;
+                println!("{}: applied {}", save, patch_path);
+            }
+        }
+        Command::Diff { a, b, json } => {
+            let gd = load_game_data(cli.game_data.as_deref())?;
+            let diff = save_diff::diff_saves(&a, &b, &gd)?;
+            if json {
+                print_json(&diff)?;
+            } else {
+                let sections = [("first", &diff.first), ("second", &diff.second), ("lua", &diff.lua), ("states", &diff.states)];
+                for (section, changes) in sections {
+                    for change in changes {
+                        let show = |v: &Option<Value>| v.as_ref().map_or("-".to_string(), Value::to_string);
+                        println!("{}\t{}\t{} -> {}\t{}", section, change.key, show(&change.before), show(&change.after),
+                            change.label.as_deref().unwrap_or_default());
+                    }
+                }
+            }
+        }
+        Command::Report { save, json } => {
+            let gd = load_game_data(cli.game_data.as_deref())?;
+            let loaded = load(&save, &gd, mode)?;
+            let report = variable_report::changed_from_default(&loaded.lua_db, &gd);
+            if json {
+                print_json(&report)?;
+            } else {
+                for group in &report.groups {
+                    println!("[{}] {} changed", group.prefix, group.variables.len());
+                    for var in &group.variables {
+                        println!("  {}	{} -> {}	{}", var.key, var.initial_value.as_deref().unwrap_or("-"), var.value, var.description);
+                    }
+                }
+                println!("[not in game data] {}", report.unknown.len());
+                for var in &report.unknown {
+                    println!("  {}	{}", var.key, var.value);
+                }
+            }
+        }
+        Command::Snapshot(args) => run_snapshot(args)?,
+    }
+    Ok(())
+}
+
+fn run_snapshot(args: SnapshotArgs) -> CliResult<()> {
+    let store = SnapshotStore::open()?;
+    let save = &args.save;
+    match args.command {
+        SnapshotCommand::List { json } => {
+            let snapshots = store.list(save)?;
+            if json {
+                print_json(&snapshots)?;
+            } else {
+                for s in snapshots {
+                    println!("{}\t{}\t{}\t{}", s.id, s.created_at, &s.hash[..12], s.label.unwrap_or_default());
+                }
+            }
+        }
+        SnapshotCommand::Create { label } => {
+            let snapshot = store.take(save, label)?;
+            println!("{}: snapshot {}", save, snapshot.id);
+        }
+        SnapshotCommand::Diff { from, to } => {
+            let diff = store.diff(save, &from, to.as_deref())?;
+            for file in &diff.files {
+                let marker = match file.change {
+                    FileChangeKind::Added => "+",
+                    FileChangeKind::Removed => "-",
+                    FileChangeKind::Modified => "M",
+                };
+                println!("{} {}", marker, file.name);
+            }
+        }
+        SnapshotCommand::Restore { id } => {
+            store.restore(save, &id)?;
+            println!("{}: restored {}", save, id);
+        }
+        SnapshotCommand::Prune => {
+            for snapshot in store.prune(save)? {
+                println!("{}: removed {}", save, snapshot.id);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_assignment(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected key=value, got {:?}", arg))
+}
+
+/// Find the game data folder: the flag or env var, then `game_data` next to
+/// the executable, then the copy in the source tree.
+fn load_game_data(explicit: Option<&Path>) -> CliResult<GameDataService> {
+    let folder = match explicit {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let beside_exe = std::env::current_exe().ok()
+                .and_then(|exe| exe.parent().map(|dir| dir.join("game_data")));
+            beside_exe
+                .filter(|dir| dir.is_dir())
+                .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("game_data"))
+        }
+    };
+
+    let mut gd = GameDataService::default();
+    gd.load(&folder.to_string_lossy())?;
+    Ok(gd)
+}
+
+fn load(save: &str, gd: &GameDataService, mode: ParseMode) -> AppResult<LoadedSave> {
+    let loaded = save_service::load_save(save, gd, mode)?;
+    if let Some(report) = &loaded.lua_salvage {
+        for range in &report.skipped {
+            eprintln!("warning: {}: skipped lua bytes {}..{}", save, range.start, range.end);
+        }
+    }
+    Ok(loaded)
+}
+
+fn write(save: &str, loaded: &LoadedSave, discard_salvaged: bool) -> AppResult<()> {
+    save_service::check_salvage(loaded.lua_salvage.as_ref(), discard_salvaged)?;
+    let file_name = Path::new(save).file_name()
+        .ok_or_else(|| AppError::InvalidPath(save.to_string()))?
+        .to_string_lossy();
+    save_service::write_save_files(
+        save,
+        save_service::save_base_name(&file_name),
+        &loaded.first_raw,
+        &loaded.second_raw,
+        &loaded.lua_db,
+        &loaded.states,
+        loaded.fow.as_ref(),
+    )
+}
+
+fn save_info(save: &str, loaded: &LoadedSave) -> Vec<(&'static str, Value)> {
+    let pc = &loaded.second.player_character;
+    let time = &loaded.second.sunshine_clock_time_holder.time;
+    vec![
+        ("path", save.into()),
+        ("area", loaded.first.area_id.clone().into()),
+        ("day", time.day_counter.into()),
+        ("time", format!("{:02}:{:02}", time.hours(), time.minutes()).into()),
+        ("level", pc.level.into()),
+        ("xp", pc.xp_amount.into()),
+        ("skill_points", pc.skill_points.into()),
+        ("money", pc.money.into()),
+        ("lua_variables", lua_database::flatten_lua(&loaded.lua_db).len().into()),
+        ("lua_salvaged", loaded.lua_salvage.is_some().into()),
+    ]
+}
+
+fn apply_patch(patch: &Patch, patch_path: &str, loaded: &mut LoadedSave, gd: &GameDataService) -> CliResult<()> {
+    let mut edits = Vec::new();
+    for (key, json) in &patch.lua {
+        let text = match json {
+            Value::String(s) => s.clone(),
+            Value::Number(_) | Value::Bool(_) => json.to_string(),
+            _ => {
+                return Err(AppError::InvalidValue {
+                    key: key.clone(),
+                    message: "expected a string, number or boolean".to_string(),
+                }.into());
+            }
+        };
+        edits.push((key.as_str(), text));
+    }
+    save_service::apply_lua_edits(&mut loaded.lua_db, edits.iter().map(|(key, text)| (*key, text.as_str())), gd)?;
+
+    for (pointer, value) in &patch.first {
+        set_pointer(&mut loaded.first_raw, pointer, value.clone())
+            .map_err(|message| CliError::Patch { path: patch_path.to_string(), message: format!("first{}: {}", pointer, message) })?;
+    }
+    for (pointer, value) in &patch.second {
+        set_pointer(&mut loaded.second_raw, pointer, value.clone())
+            .map_err(|message| CliError::Patch { path: patch_path.to_string(), message: format!("second{}: {}", pointer, message) })?;
+    }
+    Ok(())
+}
+
+/// Set the value at a JSON pointer. The parent must already exist; a missing
+/// last segment is added to an object parent.
+fn set_pointer(root: &mut Value, pointer: &str, value: Value) -> Result<(), String> {
+    if let Some(target) = root.pointer_mut(pointer) {
+        *target = value;
+        return Ok(());
+    }
+
+    let (parent, last) = pointer.rsplit_once('/')
+        .ok_or_else(|| "not a JSON pointer".to_string())?;
+    match root.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(last.replace("~1", "/").replace("~0", "~"), value);
+            Ok(())
+        }
+        Some(_) => Err("parent is not an object".to_string()),
+        None => Err("path does not exist".to_string()),
+    }
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> CliResult<()> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| CliError::Usage(e.to_string()))?;
+    println!("{}", text);
+    Ok(())
+}
@@ -1,10 +1,25 @@
-use std::collections::HashMap;
 use std::sync::Mutex;
 use tauri::{State, Manager};
+use crate::error::{AppError, AppResult};
 use crate::models::*;
 use crate::game_data::GameDataService;
 use crate::save_service;
-use crate::lua_database;
+use crate::save_diff;
+use crate::containers;
+use crate::doors;
+use crate::fog;
+use crate::inventory;
+use crate::journal;
+use crate::skill_modifiers;
+use crate::thought_cabinet;
+use crate::time;
+use crate::white_checks;
+use crate::save_discovery;
+use crate::settings;
+use crate::snapshots::SnapshotStore;
+use crate::lua_database::{self, ParseMode};
+use crate::lua_index::LuaIndex;
+use crate::variable_report;
 
 /// Application state shared across commands
 pub struct AppState {
@@ -14,8 +29,10 @@ pub struct AppState {
     pub character_sheet: Mutex<Option<CharacterSheet>>,
     pub first_file: Mutex<Option<FirstFile>>,
     pub second_file: Mutex<Option<SecondFile>>,
-    pub lua_database: Mutex<HashMap<String, LuaValue>>,
+    pub lua_database: Mutex<LuaDatabase>,
+    pub lua_index: Mutex<LuaIndex>,
     pub states: Mutex<Option<StatesData>>,
+    pub lua_salvage: Mutex<Option<LuaSalvageReport>>,
     pub current_folder: Mutex<Option<String>>,
     pub current_base_name: Mutex<Option<String>>,
 }
@@ -29,8 +46,10 @@ impl Default for AppState {
             character_sheet: Mutex::new(None),
             first_file: Mutex::new(None),
             second_file: Mutex::new(None),
-            lua_database: Mutex::new(HashMap::new()),
+            lua_database: Mutex::new(LuaDatabase::default()),
+            lua_index: Mutex::new(LuaIndex::default()),
             states: Mutex::new(None),
+            lua_salvage: Mutex::new(None),
             current_folder: Mutex::new(None),
             current_base_name: Mutex::new(None),
         }
@@ -38,71 +57,26 @@ impl Default for AppState {
 }
 
 #[tauri::command]
-pub fn discover_saves() -> Result<Vec<SaveSummary>, String> {
-    let user_dir = dirs::home_dir().ok_or("Cannot find home directory")?;
-    let possible_paths = vec![
-        user_dir.join("AppData/LocalLow/ZAUM Studio/Disco Elysium/SaveGames"),
-        user_dir.join("AppData/LocalLow/ZA-UM/Disco Elysium/SaveGames"),
-        user_dir.join("AppData/LocalLow/ZA-UM/Disco Elysium - The Final Cut/SaveGames"),
-        user_dir.join("AppData/LocalLow/ZAUM Studio/Disco Elysium - The Final Cut/SaveGames"),
-    ];
-
-    let mut saves = Vec::new();
-
-    for path in possible_paths {
-        if path.exists() {
-            if let Ok(entries) = std::fs::read_dir(&path) {
-                for entry in entries.flatten() {
-                    let entry_path = entry.path();
-                    let name = entry_path.file_name()
-                        .unwrap_or_default().to_string_lossy().to_string();
-                    
-                    let is_save = if entry_path.is_dir() {
-                        name.ends_with(".ntwtf")
-                    } else {
-                        name.ends_with(".ntwtf.zip")
-                    };
-
-                    if is_save {
-                        let metadata = std::fs::metadata(&entry_path).ok();
-                        let last_modified = metadata
-                            .and_then(|m| m.modified().ok())
-                            .map(|t| format_system_time(t))
-                            .unwrap_or_default();
-
-                        let display_name = name
-                            .replace(".ntwtf.zip", "")
-                            .replace(".ntwtf", "");
-
-                        saves.push(SaveSummary {
-                            name: display_name,
-                            path: entry_path.to_string_lossy().to_string(),
-                            last_modified,
-                        });
-                    }
-                }
-            }
-        }
-    }
+pub fn discover_saves() -> AppResult<Vec<SaveSummary>> {
+    let extra_roots: Vec<std::path::PathBuf> = settings::load_settings()?
+        .extra_save_roots.iter().map(std::path::PathBuf::from).collect();
+    save_discovery::discover_saves(&extra_roots)
+}
 
-    // Sort by last modified descending
-    saves.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
-    Ok(saves)
+#[tauri::command]
+pub fn get_extra_save_roots() -> AppResult<Vec<String>> {
+    Ok(settings::load_settings()?.extra_save_roots)
 }
 
-fn format_system_time(time: std::time::SystemTime) -> String {
-    let duration = time.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
-    let secs = duration.as_secs();
-    // Simple ISO-ish format
-    let days = secs / 86400;
-    let remaining = secs % 86400;
-    let hours = remaining / 3600;
-    let mins = (remaining % 3600) / 60;
-    format!("{}-{:02}:{:02}", days, hours, mins)
+#[tauri::command]
+pub fn set_extra_save_roots(roots: Vec<String>) -> AppResult<()> {
+    let mut current = settings::load_settings()?;
+    current.extra_save_roots = roots;
+    settings::save_settings(&current)
 }
 
 #[tauri::command]
-pub async fn pick_save_file(app: tauri::AppHandle) -> Result<Option<String>, String> {
+pub async fn pick_save_file(app: tauri::AppHandle) -> AppResult<Option<String>> {
     use tauri_plugin_dialog::DialogExt;
     
     let (tx, rx) = tokio::sync::oneshot::channel();
@@ -112,86 +86,92 @@ pub async fn pick_save_file(app: tauri::AppHandle) -> Result<Option<String>, Str
             let _ = tx.send(file_path.map(|p| p.to_string()));
         });
     
-    rx.await.map_err(|e| e.to_string())
+    rx.await.map_err(|e| AppError::Dialog(e.to_string()))
 }
 
 #[tauri::command]
-pub fn load_save(folder_path: String, state: State<AppState>, app: tauri::AppHandle) -> Result<FullSaveState, String> {
-    // Load game data if not loaded
-    {
-        let mut gd = state.game_data.lock().map_err(|e| e.to_string())?;
-        if !gd.is_loaded {
-            let resource_path = app.path()
-                .resource_dir()
-                .map_err(|e| format!("Failed to get resource dir: {}", e))?;
-            let game_data_path = resource_path.join("game_data");
-            gd.load(game_data_path.to_str().unwrap())?;
-        }
+pub async fn pick_save_folder(app: tauri::AppHandle) -> AppResult<Option<String>> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog().file()
+        .pick_folder(move |folder_path| {
+            let _ = tx.send(folder_path.map(|p| p.to_string()));
+        });
+
+    rx.await.map_err(|e| AppError::Dialog(e.to_string()))
+}
+
+/// The bundled game data, loaded from the app's resources on first use.
+fn game_data<'a>(state: &'a State<AppState>, app: &tauri::AppHandle) -> AppResult<std::sync::MutexGuard<'a, GameDataService>> {
+    let mut gd = state.game_data.lock()?;
+    if !gd.is_loaded {
+        let resource_path = app.path()
+            .resource_dir()
+            .map_err(|e| AppError::GameDataUnavailable(e.to_string()))?;
+        let game_data_path = resource_path.join("game_data");
+        gd.load(game_data_path.to_str().unwrap())?;
     }
+    Ok(gd)
+}
 
-    let gd = state.game_data.lock().map_err(|e| e.to_string())?;
+#[tauri::command]
+pub fn load_save(folder_path: String, salvage: Option<bool>, state: State<AppState>, app: tauri::AppHandle) -> AppResult<FullSaveState> {
+    let gd = game_data(&state, &app)?;
 
-    let (first_raw, second_raw, character_sheet, first, second, lua_db, states_data) =
-        save_service::load_save(&folder_path, &gd)?;
+    let lua_mode = if salvage.unwrap_or(false) { ParseMode::Salvage } else { ParseMode::Strict };
+    let loaded = save_service::load_save(&folder_path, &gd, lua_mode)?;
 
     let folder_name = std::path::Path::new(&folder_path)
         .file_name().unwrap_or_default().to_string_lossy();
-    let base_name = if folder_name.ends_with(".ntwtf.zip") {
-        folder_name[..folder_name.len() - 10].to_string()
-    } else if folder_name.ends_with(".zip") {
-        folder_name[..folder_name.len() - 4].to_string()
-    } else if folder_name.ends_with(".ntwtf") {
-        folder_name[..folder_name.len() - 6].to_string()
-    } else {
-        folder_name.to_string()
-    };
+    let base_name = save_service::save_base_name(&folder_name).to_string();
 
     // Build the full state to send to frontend
-    let full_state = build_full_state(
-        &folder_path, &base_name, &first, &second, &character_sheet, &lua_db, &states_data, &gd
+    let mut full_state = build_full_state(
+        &folder_path, &base_name, &loaded.first, &loaded.second, &loaded.character_sheet, &loaded.lua_db, &loaded.states, &gd
     );
+    full_state.fog.fow_file = loaded.fow;
+    full_state.lua_salvage = loaded.lua_salvage.clone();
 
     // Store in app state for later saving
-    *state.first_raw.lock().map_err(|e| e.to_string())? = Some(first_raw);
-    *state.second_raw.lock().map_err(|e| e.to_string())? = Some(second_raw);
-    *state.character_sheet.lock().map_err(|e| e.to_string())? = Some(character_sheet);
-    *state.first_file.lock().map_err(|e| e.to_string())? = Some(first);
-    *state.second_file.lock().map_err(|e| e.to_string())? = Some(second);
-    *state.lua_database.lock().map_err(|e| e.to_string())? = lua_db;
-    *state.states.lock().map_err(|e| e.to_string())? = Some(states_data);
-    *state.current_folder.lock().map_err(|e| e.to_string())? = Some(folder_path);
-    *state.current_base_name.lock().map_err(|e| e.to_string())? = Some(base_name);
+    *state.first_raw.lock()? = Some(loaded.first_raw);
+    *state.second_raw.lock()? = Some(loaded.second_raw);
+    *state.character_sheet.lock()? = Some(loaded.character_sheet);
+    *state.first_file.lock()? = Some(loaded.first);
+    *state.second_file.lock()? = Some(loaded.second);
+    *state.lua_index.lock()? = LuaIndex::build(&loaded.lua_db, &gd);
+    *state.lua_database.lock()? = loaded.lua_db;
+    *state.states.lock()? = Some(loaded.states);
+    *state.lua_salvage.lock()? = loaded.lua_salvage;
+    *state.current_folder.lock()? = Some(folder_path);
+    *state.current_base_name.lock()? = Some(base_name);
 
     Ok(full_state)
 }
 
 #[tauri::command]
-pub fn save_changes(payload: save_service::SaveUpdatePayload, state: State<AppState>) -> Result<(), String> {
-    let mut first_raw = state.first_raw.lock().map_err(|e| e.to_string())?;
-    let mut second_raw = state.second_raw.lock().map_err(|e| e.to_string())?;
-    let character_sheet = state.character_sheet.lock().map_err(|e| e.to_string())?;
-    let mut lua_db = state.lua_database.lock().map_err(|e| e.to_string())?;
+pub fn save_changes(payload: save_service::SaveUpdatePayload, state: State<AppState>, app: tauri::AppHandle) -> AppResult<()> {
+    save_service::check_salvage(state.lua_salvage.lock()?.as_ref(), payload.discard_salvaged)?;
+    thought_cabinet::validate(&payload.thought_cabinet, &payload.thoughts)?;
+    time::validate(&payload.clock)?;
+    let gd = game_data(&state, &app)?;
+    inventory::validate_equipment(&payload.owned_items, &gd)?;
+    let mut first_raw = state.first_raw.lock()?;
+    let mut second_raw = state.second_raw.lock()?;
+    let mut lua_state = state.lua_database.lock()?;
 
     let folder_path = payload.folder_path.clone();
     let base_name = payload.base_name.clone();
 
-    // Apply lua edits
-    for (key, value) in &payload.lua_edits {
-        // Try to preserve the original type
-        let flat = lua_database::flatten_lua(&lua_db, "");
-        if let Some(original) = flat.get(key) {
-            let typed_value = match original {
-                LuaValue::Number(_) => {
-                    LuaValue::Number(value.parse::<f64>().unwrap_or(0.0))
-                }
-                LuaValue::Boolean(_) => {
-                    LuaValue::Boolean(value.parse::<bool>().unwrap_or(false))
-                }
-                _ => LuaValue::String(value.clone()),
-            };
-            lua_database::set_lua_value(&mut lua_db, key, typed_value);
-        }
-    }
+    // Edit copies, so a failed write leaves the loaded save untouched
+    let mut lua_db = lua_state.clone();
+    let mut first = first_raw.clone().ok_or(AppError::NotLoaded("1st file"))?;
+    let mut second = second_raw.clone().ok_or(AppError::NotLoaded("2nd file"))?;
+
+    // Doors first, so edits made in the variable browser win
+    doors::sync_lua(&payload.doors, &mut lua_db, &gd);
+    let edits = payload.lua_edits.iter().map(|(key, value)| (key.as_str(), value.as_str()));
+    save_service::apply_lua_edits(&mut lua_db, edits, &gd)?;
 
     // Apply reputation to lua db
     lua_database::set_lua_value(&mut lua_db, "reputation.communist", LuaValue::Number(payload.reputation.communist));
@@ -199,74 +179,287 @@ pub fn save_changes(payload: save_service::SaveUpdatePayload, state: State<AppSt
     lua_database::set_lua_value(&mut lua_db, "reputation.moralist", LuaValue::Number(payload.reputation.moralist));
     lua_database::set_lua_value(&mut lua_db, "reputation.revacholian_nationhood", LuaValue::Number(payload.reputation.nationalist));
     lua_database::set_lua_value(&mut lua_db, "reputation.kim", LuaValue::Number(payload.reputation.kim));
+    journal::sync_lua(&second, &payload.journal, &mut lua_db, &gd);
 
     let states = StatesData {
         area_states: payload.area_states.clone(),
         shown_orbs: payload.shown_orbs.clone(),
     };
 
-    let cs = character_sheet.as_ref().ok_or("No character sheet loaded")?;
-
     save_service::save_to_disk(
         &folder_path,
         &base_name,
-        first_raw.as_mut().ok_or("No first file loaded")?,
-        second_raw.as_mut().ok_or("No second file loaded")?,
+        &mut first,
+        &mut second,
         &payload,
-        cs,
         &lua_db,
         &states,
+        &gd,
     )?;
 
+    // Written, so the edited copies become the loaded save
+    *state.lua_index.lock()? = LuaIndex::build(&lua_db, &gd);
+    *lua_state = lua_db;
+    *first_raw = Some(first);
+    *second_raw = Some(second);
+    // The file on disk is clean now, nothing is left to lose
+    *state.lua_salvage.lock()? = None;
+
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_lua_variables(query: String, limit: usize, state: State<AppState>) -> Result<Vec<LuaVariableDisplay>, String> {
-    let lua_db = state.lua_database.lock().map_err(|e| e.to_string())?;
-    let gd = state.game_data.lock().map_err(|e| e.to_string())?;
+pub fn unlock_thought_slot(mut thought_cabinet: ThoughtCabinetDisplay) -> AppResult<ThoughtCabinetDisplay> {
+    thought_cabinet::unlock_slot(&mut thought_cabinet)?;
+    Ok(thought_cabinet)
+}
+
+#[tauri::command]
+pub fn lock_thought_slot(mut thought_cabinet: ThoughtCabinetDisplay) -> AppResult<ThoughtCabinetDisplay> {
+    thought_cabinet::lock_slot(&mut thought_cabinet)?;
+    Ok(thought_cabinet)
+}
+
+#[tauri::command]
+pub fn reopen_white_check(mut white_checks: WhiteChecksState, key: String) -> AppResult<WhiteChecksState> {
+    white_checks::reopen_check(&mut white_checks, &key)?;
+    Ok(white_checks)
+}
+
+#[tauri::command]
+pub fn fail_white_check(mut white_checks: WhiteChecksState, key: String) -> AppResult<WhiteChecksState> {
+    white_checks::fail_check(&mut white_checks, &key)?;
+    Ok(white_checks)
+}
+
+#[tauri::command]
+pub fn edit_white_check(
+    mut white_checks: WhiteChecksState,
+    key: String,
+    difficulty: i64,
+    last_skill_value: i64,
+    last_target_value: i64,
+) -> AppResult<WhiteChecksState> {
+    white_checks::edit_check(&mut white_checks, &key, difficulty, last_skill_value, last_target_value)?;
+    Ok(white_checks)
+}
+
+#[tauri::command]
+pub fn remove_white_check(mut white_checks: WhiteChecksState, key: String) -> AppResult<WhiteChecksState> {
+    white_checks::remove_check(&mut white_checks, &key)?;
+    Ok(white_checks)
+}
+
+#[tauri::command]
+pub fn add_inventory_item(mut owned_items: Vec<InventoryItemDisplay>, name: String, state: State<AppState>, app: tauri::AppHandle) -> AppResult<Vec<InventoryItemDisplay>> {
+    let gd = game_data(&state, &app)?;
+    inventory::add_item(&mut owned_items, &name, &gd)?;
+    Ok(owned_items)
+}
+
+#[tauri::command]
+pub fn remove_inventory_item(mut owned_items: Vec<InventoryItemDisplay>, name: String) -> AppResult<Vec<InventoryItemDisplay>> {
+    inventory::remove_item(&mut owned_items, &name)?;
+    Ok(owned_items)
+}
+
+#[tauri::command]
+pub fn edit_inventory_item(
+    mut owned_items: Vec<InventoryItemDisplay>,
+    name: String,
+    is_fresh: bool,
+    substance_uses: i64,
+    substance_time_left: i64,
+) -> AppResult<Vec<InventoryItemDisplay>> {
+    inventory::edit_item(&mut owned_items, &name, is_fresh, substance_uses, substance_time_left)?;
+    Ok(owned_items)
+}
+
+#[tauri::command]
+pub fn equip_inventory_item(mut owned_items: Vec<InventoryItemDisplay>, name: String, state: State<AppState>, app: tauri::AppHandle) -> AppResult<Vec<InventoryItemDisplay>> {
+    let gd = game_data(&state, &app)?;
+    inventory::equip_item(&mut owned_items, &name, &gd)?;
+    Ok(owned_items)
+}
+
+#[tauri::command]
+pub fn unequip_inventory_item(mut owned_items: Vec<InventoryItemDisplay>, name: String) -> AppResult<Vec<InventoryItemDisplay>> {
+    inventory::unequip_item(&mut owned_items, &name)?;
+    Ok(owned_items)
+}
+
+#[tauri::command]
+pub fn add_container_item(mut containers: Vec<ContainerDisplay>, container_id: String, item: ContainerItemDisplay, state: State<AppState>, app: tauri::AppHandle) -> AppResult<Vec<ContainerDisplay>> {
+    let gd = game_data(&state, &app)?;
+    containers::add_item(&mut containers, &container_id, item, &gd)?;
+    Ok(containers)
+}
+
+#[tauri::command]
+pub fn remove_container_item(mut containers: Vec<ContainerDisplay>, container_id: String, index: usize) -> AppResult<Vec<ContainerDisplay>> {
+    containers::remove_item(&mut containers, &container_id, index)?;
+    Ok(containers)
+}
+
+#[tauri::command]
+pub fn edit_container_item(mut containers: Vec<ContainerDisplay>, container_id: String, index: usize, item: ContainerItemDisplay, state: State<AppState>, app: tauri::AppHandle) -> AppResult<Vec<ContainerDisplay>> {
+    let gd = game_data(&state, &app)?;
+    containers::edit_item(&mut containers, &container_id, index, item, &gd)?;
+    Ok(containers)
+}
+
+#[tauri::command]
+pub fn reroll_containers(mut containers: Vec<ContainerDisplay>, target: String, seed: u64) -> AppResult<Vec<ContainerDisplay>> {
+    containers::reroll(&mut containers, &target, seed)?;
+    Ok(containers)
+}
+
+#[tauri::command]
+pub fn set_door_open(mut doors: Vec<DoorDisplay>, id: String, is_open: bool) -> AppResult<Vec<DoorDisplay>> {
+    doors::set_open(&mut doors, &id, is_open)?;
+    Ok(doors)
+}
+
+#[tauri::command]
+pub fn advance_time(
+    mut clock: GameClock,
+    mut thoughts: Vec<ThoughtDisplay>,
+    mut owned_items: Vec<InventoryItemDisplay>,
+    mut party_state: PartyState,
+    minutes: i64,
+) -> AppResult<TimeAdvance> {
+    time::advance(&mut clock, &mut thoughts, &mut owned_items, &mut party_state, minutes)?;
+    Ok(TimeAdvance { clock, thoughts, owned_items, party_state })
+}
+
+/// Clear the fog of an area; the result replaces the frontend's fog state.
+#[tauri::command]
+pub fn reveal_fog_area(mut fog: FogState, area: String) -> AppResult<FogState> {
+    fog::reveal_area(&mut fog, &area)?;
+    Ok(fog)
+}
+
+#[tauri::command]
+pub fn hide_fog_area(mut fog: FogState, area: String) -> AppResult<FogState> {
+    fog::hide_area(&mut fog, &area)?;
+    Ok(fog)
+}
+
+#[tauri::command]
+pub fn acquire_task(mut journal: JournalState, task_name: String, time: GameTimestamp, state: State<AppState>, app: tauri::AppHandle) -> AppResult<JournalState> {
+    let gd = game_data(&state, &app)?;
+    journal::acquire_task(&mut journal, &task_name, time, &gd)?;
+    Ok(journal)
+}
+
+#[tauri::command]
+pub fn remove_task(mut journal: JournalState, task_name: String) -> AppResult<JournalState> {
+    journal::remove_task(&mut journal, &task_name)?;
+    Ok(journal)
+}
+
+#[tauri::command]
+pub fn resolve_task(mut journal: JournalState, task_name: String, time: GameTimestamp) -> AppResult<JournalState> {
+    journal::resolve_task(&mut journal, &task_name, time)?;
+    Ok(journal)
+}
+
+#[tauri::command]
+pub fn unresolve_task(mut journal: JournalState, task_name: String) -> AppResult<JournalState> {
+    journal::unresolve_task(&mut journal, &task_name)?;
+    Ok(journal)
+}
 
-    let flat = lua_database::flatten_lua(&lua_db, "");
-    let query_lower = query.to_lowercase();
+#[tauri::command]
+pub fn add_subtask(mut journal: JournalState, task_name: String, subtask: String, time: GameTimestamp, state: State<AppState>, app: tauri::AppHandle) -> AppResult<JournalState> {
+    let gd = game_data(&state, &app)?;
+    journal::add_subtask(&mut journal, &task_name, &subtask, time, &gd)?;
+    Ok(journal)
+}
 
-    let mut vars: Vec<LuaVariableDisplay> = flat.iter()
-        .filter(|(k, _)| query.is_empty() || k.to_lowercase().contains(&query_lower))
-        .take(limit)
-        .map(|(k, v)| {
-            let desc = gd.all_variables.get(k)
-                .map(|var| var.description.clone())
-                .unwrap_or_default();
-            LuaVariableDisplay {
-                key: k.clone(),
-                value: v.to_display_string(),
-                var_type: v.type_name().to_string(),
-                description: desc,
-            }
-        })
-        .collect();
+#[tauri::command]
+pub fn remove_subtask(mut journal: JournalState, task_name: String, subtask: String) -> AppResult<JournalState> {
+    journal::remove_subtask(&mut journal, &task_name, &subtask)?;
+    Ok(journal)
+}
 
-    vars.sort_by(|a, b| a.key.cmp(&b.key));
-    Ok(vars)
+#[tauri::command]
+pub fn set_task_new(mut journal: JournalState, task_name: String, is_new: bool) -> AppResult<JournalState> {
+    journal::set_task_new(&mut journal, &task_name, is_new)?;
+    Ok(journal)
 }
 
 #[tauri::command]
-pub fn get_catalog_items(state: State<AppState>) -> Result<Vec<CatalogItem>, String> {
-    let gd = state.game_data.lock().map_err(|e| e.to_string())?;
+pub fn diff_saves(a: String, b: String, state: State<AppState>, app: tauri::AppHandle) -> AppResult<SaveDiff> {
+    let gd = game_data(&state, &app)?;
+    save_diff::diff_saves(&a, &b, &gd)
+}
+
+#[tauri::command]
+pub fn list_snapshots(save_path: String) -> AppResult<Vec<SnapshotInfo>> {
+    SnapshotStore::open()?.list(&save_path)
+}
+
+#[tauri::command]
+pub fn create_snapshot(save_path: String, label: Option<String>) -> AppResult<SnapshotInfo> {
+    SnapshotStore::open()?.take(&save_path, label)
+}
+
+#[tauri::command]
+pub fn diff_snapshots(save_path: String, from: String, to: Option<String>) -> AppResult<SnapshotDiff> {
+    SnapshotStore::open()?.diff(&save_path, &from, to.as_deref())
+}
+
+/// Restores the save on disk only; the frontend reloads it afterwards.
+#[tauri::command]
+pub fn restore_snapshot(save_path: String, id: String) -> AppResult<SnapshotInfo> {
+    SnapshotStore::open()?.restore(&save_path, &id)
+}
+
+#[tauri::command]
+pub fn prune_snapshots(save_path: String) -> AppResult<Vec<SnapshotInfo>> {
+    SnapshotStore::open()?.prune(&save_path)
+}
+
+#[tauri::command]
+pub fn get_lua_variables(search: LuaSearchQuery, state: State<AppState>) -> AppResult<LuaSearchPage> {
+    state.lua_index.lock()?.search(&search)
+}
+
+#[tauri::command]
+pub fn get_variable_report(state: State<AppState>, app: tauri::AppHandle) -> AppResult<VariableReport> {
+    let gd = game_data(&state, &app)?;
+    let lua_db = state.lua_database.lock()?;
+    Ok(variable_report::changed_from_default(&lua_db, &gd))
+}
+
+#[tauri::command]
+pub fn get_skill_breakdown(skill_type: String, state: State<AppState>, app: tauri::AppHandle) -> AppResult<SkillBreakdown> {
+    let gd = game_data(&state, &app)?;
+    let second_raw = state.second_raw.lock()?;
+    let second = second_raw.as_ref().ok_or(AppError::NotLoaded("2nd file"))?;
+    let sheet = save_service::character_sheet_from_raw(second, &gd).unwrap_or_default();
+    skill_modifiers::skill_breakdown(&sheet, &skill_type, &gd)
+}
+
+#[tauri::command]
+pub fn get_catalog_items(state: State<AppState>) -> AppResult<Vec<CatalogItem>> {
+    let gd = state.game_data.lock()?;
     Ok(gd.get_all_catalog_items())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_full_state(
     folder_path: &str,
     base_name: &str,
     first: &FirstFile,
     second: &SecondFile,
     cs: &CharacterSheet,
-    lua_db: &HashMap<String, LuaValue>,
+    lua_db: &LuaDatabase,
     states: &StatesData,
     gd: &GameDataService,
 ) -> FullSaveState {
     let pc = &second.player_character;
-    let time = &second.sunshine_clock_time_holder.time;
 
     // Build abilities
     let abilities: Vec<AbilityDisplay> = cs.abilities.iter().map(|(key, entry)| {
@@ -310,27 +503,17 @@ fn build_full_state(
 
     // Build owned items
     let owned_items: Vec<InventoryItemDisplay> = cs.gained_items.iter().map(|item_name| {
-        let game_def = gd.items.get(item_name);
         let item_state = second.inventory_state.item_list_state.iter()
             .find(|i| i.item_name == *item_name);
-        let is_equipped = cs.equipped_items.contains(item_name);
         let slot = second.inventory_state.inventory_view_state.equipment.iter()
             .find(|(_, v)| *v == item_name)
             .map(|(k, _)| k.clone())
             .unwrap_or_default();
 
         InventoryItemDisplay {
-            name: item_name.clone(),
-            display_name: game_def.map(|d| d.display_name.clone()).unwrap_or_else(|| item_name.clone()),
-            description: game_def.map(|d| d.description.clone()).unwrap_or_default(),
-            bonus: game_def.map(|d| d.medium_text_value.clone()).unwrap_or_default(),
-            is_owned: true,
-            is_equipped,
+            is_equipped: cs.equipped_items.contains(item_name),
             equip_slot: slot,
-            is_quest_item: game_def.map(|d| d.is_quest_item).unwrap_or(false),
-            is_cursed: game_def.map(|d| d.is_cursed()).unwrap_or(false),
-            is_substance: game_def.map(|d| d.is_substance_item()).unwrap_or(false),
-            substance_uses: item_state.map(|s| s.substance_uses).unwrap_or(0),
+            ..inventory::item_display(item_name, gd, item_state)
         }
     }).collect();
 
@@ -364,92 +547,14 @@ fn build_full_state(
             is_cursed: thought_def.is_cursed_thought(),
             state: current_state.to_string(),
             time_left: state.map(|s| s.time_left).unwrap_or(0.0),
+            is_fresh: state.map_or(true, |s| s.is_fresh),
         }
     }).collect();
 
-    // Build tasks
-    let tasks: Vec<TaskDisplay> = second.acquired_journal_tasks.task_acquisitions.iter().map(|(task_name, acquisition)| {
-        let description = gd.get_task_description(task_name);
-        let resolution = second.acquired_journal_tasks.task_resolutions.get(task_name);
-        let is_resolved = resolution.map(|r| {
-            r.is_object() && r.as_object().map(|o| !o.is_empty()).unwrap_or(false)
-        }).unwrap_or(false);
-        let is_new = second.acquired_journal_tasks.task_new_states
-            .get(task_name).copied().unwrap_or(true) == false;
-
-        let subtasks = second.acquired_journal_tasks.subtask_acquisitions
-            .get(task_name)
-            .map(|subs| subs.keys().map(|k| gd.get_task_description(k)).collect())
-            .unwrap_or_default();
-
-        TaskDisplay {
-            task_name: task_name.clone(),
-            description,
-            acquired_time: format!("Day {}, {:02}:{:02}",
-                acquisition.day_counter, acquisition.hours(), acquisition.minutes()),
-            is_resolved,
-            is_new,
-            subtasks,
-        }
-    }).collect();
-
-    // Build white checks
-    let failed_checks: Vec<WhiteCheckDisplay> = second.failed_white_checks_holder
-        .white_check_cache.iter().filter_map(|(key, element)| {
-            serde_json::from_value::<WhiteCheck>(element.clone()).ok().map(|check| {
-                WhiteCheckDisplay {
-                    key: key.clone(),
-                    flag_name: check.flag_name,
-                    skill_type: check.skill_type.clone(),
-                    skill_display_name: gd.get_skill_display_name_by_type(&check.skill_type),
-                    difficulty: check.difficulty,
-                    last_skill_value: check.last_skill_value,
-                    last_target_value: check.last_target_value,
-                    check_precondition: check.check_precondition,
-                    is_seen_only: false,
-                }
-            })
-        }).collect();
-
-    let seen_checks: Vec<WhiteCheckDisplay> = second.failed_white_checks_holder
-        .seen_white_check_cache.iter().map(|(key, check)| {
-            WhiteCheckDisplay {
-                key: key.clone(),
-                flag_name: check.flag_name.clone(),
-                skill_type: check.skill_type.clone(),
-                skill_display_name: gd.get_skill_display_name_by_type(&check.skill_type),
-                difficulty: check.difficulty,
-                last_skill_value: check.last_skill_value,
-                last_target_value: check.last_target_value,
-                check_precondition: check.check_precondition.clone(),
-                is_seen_only: check.is_only_seen,
-            }
-        }).collect();
-
-    // Build containers
-    let containers: Vec<ContainerDisplay> = second.container_source_state.item_registry.iter()
-        .map(|(container_id, items)| {
-            let container_items: Vec<ContainerItemDisplay> = items.iter().map(|item| {
-                ContainerItemDisplay {
-                    name: item.name.clone(),
-                    probability: item.probability,
-                    value: item.value,
-                    deviation: item.deviation,
-                    calculated_value: item.calculated_value,
-                    bonus_loot: item.bonus_loot,
-                }
-            }).collect();
-
-            ContainerDisplay {
-                container_id: container_id.clone(),
-                item_count: items.len(),
-                total_value: items.iter().map(|i| i.calculated_value).sum(),
-                items: container_items,
-            }
-        }).collect();
+    let containers = containers::build_containers(&second.container_source_state);
 
     // Reputation from lua db
-    let flat = lua_database::flatten_lua(lua_db, "");
+    let flat = lua_database::flatten_lua(lua_db);
     let get_rep = |key: &str| -> f64 {
         flat.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0)
     };
@@ -463,15 +568,14 @@ fn build_full_state(
         money: pc.money,
         health: pc.healing_pools.endurance,
         morale: pc.healing_pools.volition,
-        day: time.day_counter,
-        hours: time.hours(),
-        minutes: time.minutes(),
+        clock: time::clock(&second.sunshine_clock_time_holder),
         abilities,
         skills,
         owned_items,
         bullets: second.inventory_state.inventory_view_state.bullets,
         thoughts,
-        tasks,
+        thought_cabinet: thought_cabinet::build_cabinet(&second.thought_cabinet_state.thought_cabinet_view_state),
+        journal: journal::build_journal_state(&second.acquired_journal_tasks, gd),
         area_id: first.area_id.clone(),
         party_state: first.party_state.clone(),
         hud_state: HudStateDisplay {
@@ -504,11 +608,12 @@ fn build_full_state(
             kim: get_rep("reputation.kim"),
         },
         lua_variable_count: flat.len(),
-        failed_checks,
-        seen_checks,
+        white_checks: white_checks::build_white_checks_state(&second.failed_white_checks_holder, gd),
         containers,
-        door_states: second.various_items_holder.door_states.clone(),
+        doors: doors::build_doors(&second.various_items_holder.door_states, &flat, gd),
         area_states: states.area_states.clone(),
         shown_orbs: states.shown_orbs.clone(),
+        fog: fog::build_fog_state(first, None),
+        lua_salvage: None,
     }
 }
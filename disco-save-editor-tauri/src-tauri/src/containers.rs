@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use crate::error::{AppError, AppResult};
+use crate::game_data::GameDataService;
+use crate::models::*;
+
+/// Every container in `itemRegistry`, sorted by id. Entries that only refer
+/// to another container's item show that item.
+pub fn build_containers(state: &ContainerSourceState) -> Vec<ContainerDisplay> {
+    let by_ref_id: HashMap<&str, &ContainerItem> = state.item_registry.values()
+        .flatten()
+        .filter_map(|item| Some((item.ref_id.as_deref()?, item)))
+        .collect();
+
+    let mut containers: Vec<ContainerDisplay> = state.item_registry.iter()
+        .map(|(container_id, items)| {
+            let is_shared = items.iter().any(|i| i.ref_id.is_some() || i.ref_target.is_some());
+            let items = items.iter()
+                .map(|item| item.ref_target.as_deref().and_then(|r| by_ref_id.get(r).copied()).unwrap_or(item))
+                .map(|item| ContainerItemDisplay {
+                    name: item.name.clone(),
+                    probability: item.probability,
+                    value: item.value,
+                    deviation: item.deviation,
+                    calculated_value: item.calculated_value,
+                    bonus_loot: item.bonus_loot,
+                })
+                .collect();
+            let mut container = ContainerDisplay {
+                container_id: container_id.clone(),
+                area: area_of(container_id).to_string(),
+                item_count: 0,
+                total_value: 0,
+                is_shared,
+                items,
+            };
+            update_totals(&mut container);
+            container
+        })
+        .collect();
+    containers.sort_by(|a, b| a.container_id.cmp(&b.container_id));
+    containers
+}
+
+pub fn add_item(containers: &mut [ContainerDisplay], container_id: &str, item: ContainerItemDisplay, gd: &GameDataService) -> AppResult<()> {
+    validate_item(&item, gd)?;
+    let container = editable(containers, container_id)?;
+    container.items.push(ContainerItemDisplay { calculated_value: 0, ..item });
+    update_totals(container);
+    Ok(())
+}
+
+pub fn remove_item(containers: &mut [ContainerDisplay], container_id: &str, index: usize) -> AppResult<()> {
+    let container = editable(containers, container_id)?;
+    if index >= container.items.len() {
+        return Err(invalid(container_id, "no item at that position"));
+    }
+    container.items.remove(index);
+    update_totals(container);
+    Ok(())
+}
+
+/// Replace an item's definition. A value the game already rolled is kept
+/// if the new value and deviation still allow it, and moved to the nearest
+/// allowed amount otherwise.
+pub fn edit_item(
+    containers: &mut [ContainerDisplay],
+    container_id: &str,
+    index: usize,
+    item: ContainerItemDisplay,
+    gd: &GameDataService,
+) -> AppResult<()> {
+    validate_item(&item, gd)?;
+    let container = editable(containers, container_id)?;
+    let existing = container.items.get_mut(index)
+        .ok_or_else(|| invalid(container_id, "no item at that position"))?;
+    let calculated_value = match existing.calculated_value {
+        0 => 0,
+        rolled => rolled.clamp(min_value(&item), item.value.saturating_add(item.deviation)),
+    };
+    *existing = ContainerItemDisplay { calculated_value, ..item };
+    update_totals(container);
+    Ok(())
+}
+
+/// Roll the loot of one container, or of every container under a path
+/// (`Interactable whirl f2` covers `Interactable whirl f2/Shelves11` and
+/// everything below it), the way the game does on opening: each item drops
+/// with its probability and is worth its value give or take its deviation.
+/// The same seed always gives a container the same loot. Shared containers
+/// are skipped.
+pub fn reroll(containers: &mut [ContainerDisplay], target: &str, seed: u64) -> AppResult<()> {
+    let mut rerolled = 0;
+    for container in containers.iter_mut()
+        .filter(|c| is_under(&c.container_id, target))
+        .filter(|c| !c.is_shared)
+    {
+        let mut rng = LootRng::new(seed, &container.container_id);
+        for item in &mut container.items {
+            let drops = rng.next_f64() < item.probability;
+            item.calculated_value = if drops {
+                item.value.saturating_add(rng.next_in(-item.deviation, item.deviation)).max(min_value(item))
+            } else {
+                0
+            };
+        }
+        update_totals(container);
+        rerolled += 1;
+    }
+    if rerolled == 0 {
+        return Err(invalid(target, "no editable container at or under that path"));
+    }
+    Ok(())
+}
+
+/// Write edited containers back into `containerSourceState.itemRegistry`.
+/// Containers whose loot didn't change keep their raw entries untouched,
+/// and shared ones are never rewritten so their references stay intact.
+pub fn apply_to_second_raw(containers: &[ContainerDisplay], second_raw: &mut Value) {
+    let Some(Value::Object(registry)) = second_raw.get_mut("containerSourceState")
+        .and_then(|s| s.get_mut("itemRegistry")) else { return };
+
+    for container in containers.iter().filter(|c| !c.is_shared) {
+        let Some(Value::Array(raw_items)) = registry.get_mut(&container.container_id) else { continue };
+        let before: Vec<ContainerItem> = raw_items.iter()
+            .filter_map(|item| serde_json::from_value(item.clone()).ok())
+            .collect();
+        let unchanged = before.len() == container.items.len()
+            && before.iter().zip(&container.items).all(|(raw, item)| same_item(raw, item));
+        if unchanged {
+            continue;
+        }
+        *raw_items = container.items.iter().map(|item| json!({
+            "name": item.name,
+            "probability": item.probability,
+            "value": item.value,
+            "deviation": item.deviation,
+            "calculatedValue": item.calculated_value,
+            "bonusLoot": item.bonus_loot,
+        })).collect();
+    }
+}
+
+/// Whether `container_id` is `path` or lies below it, segment by segment.
+fn is_under(container_id: &str, path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    container_id.strip_prefix(path).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn area_of(container_id: &str) -> &str {
+    container_id.rsplit_once('/').map_or("", |(area, _)| area)
+}
+
+fn update_totals(container: &mut ContainerDisplay) {
+    container.item_count = container.items.len();
+    container.total_value = container.items.iter().map(|i| i.calculated_value).sum();
+}
+
+fn editable<'a>(containers: &'a mut [ContainerDisplay], container_id: &str) -> AppResult<&'a mut ContainerDisplay> {
+    let container = containers.iter_mut().find(|c| c.container_id == container_id)
+        .ok_or_else(|| invalid(container_id, "container is not in the save"))?;
+    if container.is_shared {
+        return Err(invalid(container_id, "loot is shared with another container"));
+    }
+    Ok(container)
+}
+
+fn validate_item(item: &ContainerItemDisplay, gd: &GameDataService) -> AppResult<()> {
+    if !gd.items.contains_key(&item.name) {
+        return Err(invalid(&item.name, "item is not in the game data"));
+    }
+    if !(0.0..=1.0).contains(&item.probability) {
+        return Err(invalid("probability", "must be between 0 and 1"));
+    }
+    if item.value < 0 || item.deviation < 0 {
+        return Err(invalid("value", "value and deviation must not be negative"));
+    }
+    Ok(())
+}
+
+fn min_value(item: &ContainerItemDisplay) -> i64 {
+    item.value.saturating_sub(item.deviation).max(0)
+}
+
+fn same_item(raw: &ContainerItem, item: &ContainerItemDisplay) -> bool {
+    raw.name == item.name
+        && raw.probability == item.probability
+        && raw.value == item.value
+        && raw.deviation == item.deviation
+        && raw.calculated_value == item.calculated_value
+        && raw.bonus_loot == item.bonus_loot
+}
+
+fn invalid(key: &str, message: &str) -> AppError {
+    AppError::InvalidValue { key: key.to_string(), message: message.to_string() }
+}
+
+/// SplitMix64 seeded from the user's seed and the container id, so each
+/// container's roll doesn't depend on which others are rerolled with it.
+struct LootRng(u64);
+
+impl LootRng {
+    fn new(seed: u64, container_id: &str) -> Self {
+        let digest = Sha256::new()
+            .chain_update(seed.to_le_bytes())
+            .chain_update(container_id.as_bytes())
+            .finalize();
+        let mut state = [0u8; 8];
+        state.copy_from_slice(&digest[..8]);
+        Self(u64::from_le_bytes(state))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `low..=high`, for any two bounds with `low <= high`.
+    fn next_in(&mut self, low: i64, high: i64) -> i64 {
+        let offset = match high.abs_diff(low).checked_add(1) {
+            Some(span) => self.next_u64() % span,
+            // The whole i64 range
+            None => self.next_u64(),
+        };
+        low.wrapping_add_unsigned(offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, probability: f64, value: i64, deviation: i64) -> ContainerItemDisplay {
+        ContainerItemDisplay { name: name.to_string(), probability, value, deviation, calculated_value: 0, bonus_loot: false }
+    }
+
+    fn container(id: &str, items: Vec<ContainerItemDisplay>) -> ContainerDisplay {
+        ContainerDisplay {
+            container_id: id.to_string(),
+            area: area_of(id).to_string(),
+            item_count: 0,
+            total_value: 0,
+            is_shared: false,
+            items,
+        }
+    }
+
+    fn rolled(containers: &[ContainerDisplay]) -> Vec<Vec<i64>> {
+        containers.iter().map(|c| c.items.iter().map(|i| i.calculated_value).collect()).collect()
+    }
+
+    fn loot() -> Vec<ContainerDisplay> {
+        let items = || (0..8).map(|n| item(&format!("item_{}", n), 0.5, 100, 40)).collect();
+        vec![container("Area/Shelves1", items()), container("Area/Shelves2", items())]
+    }
+
+    #[test]
+    fn same_seed_gives_same_loot() {
+        let mut first = loot();
+        let mut second = loot();
+        reroll(&mut first, "Area", 42).unwrap();
+        reroll(&mut second, "Area", 42).unwrap();
+        assert_eq!(rolled(&first), rolled(&second));
+
+        // One container's roll doesn't depend on the others rolled with it
+        let mut alone = loot();
+        reroll(&mut alone, "Area/Shelves2", 42).unwrap();
+        assert_eq!(rolled(&alone)[1], rolled(&first)[1]);
+
+        let mut other = loot();
+        reroll(&mut other, "Area", 43).unwrap();
+        assert_ne!(rolled(&other), rolled(&first));
+
+        for value in rolled(&first).concat() {
+            assert!(value == 0 || (60..=140).contains(&value));
+        }
+    }
+
+    #[test]
+    fn shared_containers_are_skipped() {
+        let state: ContainerSourceState = serde_json::from_value(json!({
+            "itemRegistry": {
+                "Area/Desk": [{ "$id": "1", "name": "pen", "probability": 1.0, "value": 10, "calculatedValue": 7 }],
+                "Area/Drawer": [{ "$ref": "1" }],
+                "Area/Shelf": [{ "name": "book", "probability": 1.0, "value": 20 }],
+            }
+        })).unwrap();
+        let mut containers = build_containers(&state);
+        assert_eq!(containers.iter().filter(|c| c.is_shared).count(), 2);
+
+        reroll(&mut containers, "Area", 7).unwrap();
+        assert_eq!(rolled(&containers), [vec![7], vec![7], vec![20]]);
+        assert!(reroll(&mut containers, "Area/Desk", 7).is_err());
+    }
+
+    #[test]
+    fn huge_deviations_do_not_overflow() {
+        let mut containers = vec![container("Area/Safe", vec![item("gold", 1.0, i64::MAX, i64::MAX)])];
+        reroll(&mut containers, "Area/Safe", 1).unwrap();
+
+        let mut rng = LootRng::new(1, "Area/Safe");
+        for _ in 0..100 {
+            let n = rng.next_in(-3, 3);
+            assert!((-3..=3).contains(&n));
+        }
+        rng.next_in(i64::MIN, i64::MAX);
+    }
+}
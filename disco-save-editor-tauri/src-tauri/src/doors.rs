@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use serde_json::{json, Value};
+use crate::error::{AppError, AppResult};
+use crate::game_data::GameDataService;
+use crate::lua_database;
+use crate::models::*;
+
+/// Last words of a variable name that say its door is open.
+const OPEN_WORDS: &[&str] = &["opens", "open", "opened", "unlocked"];
+
+/// Every door the save or the game data knows of, by area. Entries of
+/// `DoorStates` are paired with a door variable of their area (one whose
+/// name says the door opens or is unlocked) that shares a word with them,
+/// picking the one whose words are closest: "Apt Door Twelve" gets
+/// `apt.door_twelve_opens`, while "Whirling Door Tequila" shares no word
+/// with any Whirling door variable and stays unpaired. Doors only one of
+/// them knows of are listed on their own.
+pub fn build_doors(door_states: &HashMap<String, bool>, lua: &HashMap<String, LuaValue>, gd: &GameDataService) -> Vec<DoorDisplay> {
+    let mut doors: Vec<DoorDisplay> = gd.location_variables.values()
+        .filter(|v| is_door_variable(&v.name, gd))
+        .map(|v| DoorDisplay {
+            id: v.name.clone(),
+            name: door_name(&v.name, &v.description),
+            area: v.name.split_once('.').map_or("", |(area, _)| area).to_string(),
+            state_key: None,
+            is_open_state: None,
+            variable: Some(v.name.clone()),
+            is_open_variable: Some(variable_value(&v.name, lua, gd)),
+        })
+        .collect();
+
+    let mut keys: Vec<&String> = door_states.keys().collect();
+    keys.sort();
+    for key in keys {
+        let is_open = door_states[key];
+        let area = words(key).into_iter().next().unwrap_or_default();
+        let closest = doors.iter_mut()
+            .filter(|d| d.state_key.is_none() && d.variable.is_some() && d.area.eq_ignore_ascii_case(&area))
+            .filter_map(|d| Some((similarity(key, &d.id)?, d)))
+            .max_by(|(a, x), (b, y)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal).then_with(|| y.id.cmp(&x.id)));
+        match closest {
+            Some((_, door)) => {
+                door.state_key = Some(key.clone());
+                door.is_open_state = Some(is_open);
+            }
+            None => doors.push(DoorDisplay {
+                id: key.clone(),
+                name: key.clone(),
+                area,
+                state_key: Some(key.clone()),
+                is_open_state: Some(is_open),
+                variable: None,
+                is_open_variable: None,
+            }),
+        }
+    }
+
+    doors.sort_by(|a, b| a.area.cmp(&b.area).then_with(|| a.name.cmp(&b.name)));
+    doors
+}
+
+/// Open or lock a door everywhere it is known, so dialogue and the door in
+/// the world agree.
+pub fn set_open(doors: &mut [DoorDisplay], id: &str, is_open: bool) -> AppResult<()> {
+    let door = doors.iter_mut().find(|d| d.id == id)
+        .ok_or_else(|| invalid(id, "door is not in the save or the game data"))?;
+    if door.state_key.is_some() {
+        door.is_open_state = Some(is_open);
+    }
+    if door.variable.is_some() {
+        door.is_open_variable = Some(is_open);
+    }
+    Ok(())
+}
+
+/// Write door variables that differ from the Lua database into it.
+/// Variables left at their value are not written, so ones the game hasn't
+/// written yet stay unwritten.
+pub fn sync_lua(doors: &[DoorDisplay], lua_db: &mut LuaDatabase, gd: &GameDataService) {
+    let lua = lua_database::flatten_lua(lua_db);
+    let changed: Vec<(&str, bool)> = doors.iter()
+        .filter_map(|d| Some((d.variable.as_deref()?, d.is_open_variable?)))
+        .filter(|(name, is_open)| variable_value(name, &lua, gd) != *is_open)
+        .collect();
+    for (name, is_open) in changed {
+        lua_database::set_lua_value(lua_db, name, LuaValue::Boolean(is_open));
+    }
+}
+
+/// Write door states back into `variousItemsHolder.DoorStates`, in place.
+pub fn apply_to_second_raw(doors: &[DoorDisplay], second_raw: &mut Value) {
+    let Some(Value::Object(holder)) = second_raw.get_mut("variousItemsHolder") else { return };
+    let states = holder.entry("DoorStates".to_string()).or_insert_with(|| json!({}));
+    let Value::Object(states) = states else { return };
+    for door in doors {
+        if let (Some(key), Some(is_open)) = (&door.state_key, door.is_open_state) {
+            states.insert(key.clone(), json!(is_open));
+        }
+    }
+}
+
+/// A boolean location variable whose name says a door opens or is
+/// unlocked: `apt.door_twelve_opens`, `whirling.steel_door_unlocked`.
+fn is_door_variable(name: &str, gd: &GameDataService) -> bool {
+    let Some((_, local)) = name.split_once('.') else { return false };
+    let opens = local.rsplit('_').next().is_some_and(|w| OPEN_WORDS.contains(&w));
+    opens && local.contains("door") && matches!(gd.initial_lua_value(name), Some(LuaValue::Boolean(_)))
+}
+
+/// "the door to cuno's dad" reads as "Door to cuno's dad"; variables not
+/// described that way are named after their words up to the door
+/// ("Barred door" for `whirling.barreddoor_got_open`).
+fn door_name(variable: &str, description: &str) -> String {
+    let name = match description.trim().strip_prefix("the door to ") {
+        Some(rest) => format!("door to {}", rest),
+        None => {
+            let mut words = words(variable.split_once('.').map_or(variable, |(_, local)| local));
+            match words.iter().rposition(|w| w == "door") {
+                Some(i) if i > 0 => words.truncate(i + 1),
+                _ => words.retain(|w| !OPEN_WORDS.contains(&w.as_str())),
+            }
+            words.join(" ")
+        }
+    };
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => variable.to_string(),
+    }
+}
+
+/// Lowercase words of a door state key or variable, with `door` split out
+/// of compounds like `barreddoor`.
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| c == '.' || c == '_' || c == '-' || c.is_whitespace())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .flat_map(|w| match w.split_once("door") {
+            Some((before, after)) if !(before.is_empty() && after.is_empty()) => {
+                [before, "door", after].into_iter().filter(|w| !w.is_empty()).map(str::to_string).collect()
+            }
+            _ => vec![w],
+        })
+        .collect()
+}
+
+/// How alike a door state key and a variable of the same area are: the
+/// words they share, then how alike the rest reads, by letter pairs. None
+/// when they share no word besides the area and "door", unless neither has
+/// any other.
+fn similarity(key: &str, variable: &str) -> Option<f64> {
+    let significant = |name: &str| -> Vec<String> {
+        words(name).into_iter().skip(1)
+            .filter(|w| !w.starts_with("door") && !OPEN_WORDS.contains(&w.as_str()))
+            .collect()
+    };
+    let (a, b) = (significant(key), significant(variable));
+    let shared = a.iter().filter(|w| b.contains(w)).count();
+    if shared == 0 && !(a.is_empty() && b.is_empty()) {
+        return None;
+    }
+    Some(shared as f64 + letter_pairs_alike(&a.concat(), &b.concat()))
+}
+
+/// Dice coefficient of the letter pairs of `a` and `b`, from 0 to 1.
+fn letter_pairs_alike(a: &str, b: &str) -> f64 {
+    let pairs = |s: &str| -> Vec<(char, char)> { s.chars().zip(s.chars().skip(1)).collect() };
+    let (a, mut b) = (pairs(a), pairs(b));
+    let total = a.len() + b.len();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut shared = 0;
+    for pair in &a {
+        if let Some(i) = b.iter().position(|p| p == pair) {
+            b.swap_remove(i);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+fn variable_value(name: &str, lua: &HashMap<String, LuaValue>, gd: &GameDataService) -> bool {
+    lua.get(name).cloned()
+        .or_else(|| gd.initial_lua_value(name))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+fn invalid(key: &str, message: &str) -> AppError {
+    AppError::InvalidValue { key: key.to_string(), message: message.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::*;
+
+    fn game_data() -> GameDataService {
+        let mut gd = GameDataService::default();
+        gd.load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("game_data").to_string_lossy()).unwrap();
+        gd
+    }
+
+    fn door<'a>(doors: &'a [DoorDisplay], state_key: &str) -> &'a DoorDisplay {
+        doors.iter().find(|d| d.state_key.as_deref() == Some(state_key)).unwrap()
+    }
+
+    #[test]
+    fn pairs_states_with_the_closest_variable() {
+        let gd = game_data();
+        let states = HashMap::from([("Apt Door Twelve".to_string(), true)]);
+        let doors = build_doors(&states, &HashMap::new(), &gd);
+
+        let twelve = door(&doors, "Apt Door Twelve");
+        assert_eq!(twelve.variable.as_deref(), Some("apt.door_twelve_opens"));
+        assert_eq!(twelve.is_open_state, Some(true));
+        assert_eq!(doors.iter().filter(|d| d.variable.as_deref() == Some("apt.door_twelve_opens")).count(), 1);
+    }
+
+    #[test]
+    fn leaves_states_without_a_shared_word_unpaired() {
+        let gd = game_data();
+        let states = HashMap::from([("Whirling Door Tequila".to_string(), false)]);
+        let doors = build_doors(&states, &HashMap::new(), &gd);
+
+        let tequila = door(&doors, "Whirling Door Tequila");
+        assert_eq!(tequila.variable, None);
+        assert_eq!(tequila.area, "whirling");
+    }
+
+    #[test]
+    fn names_variables_after_their_door() {
+        assert_eq!(door_name("whirling.barreddoor_got_open", ""), "Barred door");
+        assert_eq!(door_name("apt.door_twelve_opens", ""), "Door twelve");
+        assert_eq!(door_name("x.y_opens", "the door to cuno's dad"), "Door to cuno's dad");
+    }
+
+    #[test]
+    fn similarity_needs_a_shared_word() {
+        assert!(similarity("Apt Door Twelve", "apt.door_twelve_opens").is_some());
+        assert!(similarity("Apt Door Twelve", "apt.door_twenty_opens").is_none());
+        assert!(similarity("Whirling Door", "whirling.door_open").is_some());
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use crate::models::ByteRange;
+
+/// Which file of a save (or of the bundled game data) an error relates to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileRole {
+    First,
+    Second,
+    LuaDatabase,
+    States,
+    Fow,
+    Archive,
+    Snapshot,
+    GameData,
+    Settings,
+}
+
+impl fmt::Display for FileRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FileRole::First => "1st file",
+            FileRole::Second => "2nd file",
+            FileRole::LuaDatabase => "lua database",
+            FileRole::States => "states file",
+            FileRole::Fow => "fog of war file",
+            FileRole::Archive => "save archive",
+            FileRole::Snapshot => "snapshot",
+            FileRole::GameData => "game data",
+            FileRole::Settings => "settings file",
+        })
+    }
+}
+
+/// Every failure the backend can report. Serialized to the frontend as an
+/// object tagged with `kind`, plus a `message` and the variant's context.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("Invalid save path: {0}")]
+    InvalidPath(String),
+
+    #[error("Missing {role}: {path}")]
+    MissingFile { role: FileRole, path: String },
+
+    #[error("Failed to access {role} {path}: {source}")]
+    Io {
+        role: FileRole,
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Corrupt zip archive {path}: {source}")]
+    Zip {
+        path: String,
+        #[source]
+        source: zip::result::ZipError,
+    },
+
+    #[error("Failed to parse {role} {path} at {json_path}: {source}")]
+    Json {
+        role: FileRole,
+        path: String,
+        json_path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Failed to parse lua database at offset {offset}: expected {expected}, found {}", describe_byte(*.found))]
+    LuaParse { offset: u64, expected: String, found: Option<u8> },
+
+    #[error("No snapshot {0}")]
+    UnknownSnapshot(String),
+
+    #[error("Unknown lua variable: {0}")]
+    UnknownVariable(String),
+
+    #[error("Saving drops the lua bytes skipped by salvage mode ({}); confirm to save anyway", describe_ranges(.0))]
+    SalvageUnconfirmed(Vec<ByteRange>),
+
+    #[error("Invalid value for {key}: {message}")]
+    InvalidValue { key: String, message: String },
+
+    #[error("Bundled game data is unavailable: {0}")]
+    GameDataUnavailable(String),
+
+    #[error("Editor state is unavailable because an earlier operation panicked")]
+    StatePoisoned,
+
+    #[error("No save loaded ({0} missing)")]
+    NotLoaded(&'static str),
+
+    #[error("File dialog failed: {0}")]
+    Dialog(String),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+fn describe_ranges(ranges: &[ByteRange]) -> String {
+    if ranges.is_empty() {
+        return "none".to_string();
+    }
+    ranges.iter().map(|r| format!("{}..{}", r.start, r.end)).collect::<Vec<_>>().join(", ")
+}
+
+fn describe_byte(byte: Option<u8>) -> String {
+    match byte {
+        Some(b) if b.is_ascii_graphic() => format!("0x{:02X} ('{}')", b, b as char),
+        Some(b) => format!("0x{:02X}", b),
+        None => "end of data".to_string(),
+    }
+}
+
+impl AppError {
+    pub fn io(role: FileRole, path: impl AsRef<std::path::Path>, source: std::io::Error) -> Self {
+        AppError::Io { role, path: path.as_ref().display().to_string(), source }
+    }
+
+    pub fn zip(path: impl AsRef<std::path::Path>, source: zip::result::ZipError) -> Self {
+        AppError::Zip { path: path.as_ref().display().to_string(), source }
+    }
+
+    pub fn json(role: FileRole, path: impl AsRef<std::path::Path>, source: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        AppError::Json {
+            role,
+            path: path.as_ref().display().to_string(),
+            json_path: source.path().to_string(),
+            source: source.into_inner(),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::InvalidPath(_) => "invalid_path",
+            AppError::MissingFile { .. } => "missing_file",
+            AppError::Io { .. } => "io",
+            AppError::Zip { .. } => "zip",
+            AppError::Json { .. } => "json",
+            AppError::LuaParse { .. } => "lua_parse",
+            AppError::UnknownSnapshot(_) => "unknown_snapshot",
+            AppError::UnknownVariable(_) => "unknown_variable",
+            AppError::SalvageUnconfirmed(_) => "salvage_unconfirmed",
+            AppError::InvalidValue { .. } => "invalid_value",
+            AppError::GameDataUnavailable(_) => "game_data_unavailable",
+            AppError::StatePoisoned => "state_poisoned",
+            AppError::NotLoaded(_) => "not_loaded",
+            AppError::Dialog(_) => "dialog",
+        }
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(_: std::sync::PoisonError<T>) -> Self {
+        AppError::StatePoisoned
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.to_string())?;
+        match self {
+            AppError::InvalidPath(path) => {
+                map.serialize_entry("path", path)?;
+            }
+            AppError::MissingFile { role, path } | AppError::Io { role, path, .. } => {
+                map.serialize_entry("role", role)?;
+                map.serialize_entry("path", path)?;
+            }
+            AppError::Zip { path, .. } => {
+                map.serialize_entry("role", &FileRole::Archive)?;
+                map.serialize_entry("path", path)?;
+            }
+            AppError::Json { role, path, json_path, source } => {
+                map.serialize_entry("role", role)?;
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("json_path", json_path)?;
+                map.serialize_entry("line", &source.line())?;
+                map.serialize_entry("column", &source.column())?;
+            }
+            AppError::LuaParse { offset, expected, found } => {
+                map.serialize_entry("role", &FileRole::LuaDatabase)?;
+                map.serialize_entry("offset", offset)?;
+                map.serialize_entry("expected", expected)?;
+                map.serialize_entry("found", found)?;
+            }
+            AppError::UnknownSnapshot(id) => {
+                map.serialize_entry("role", &FileRole::Snapshot)?;
+                map.serialize_entry("id", id)?;
+            }
+            AppError::SalvageUnconfirmed(skipped) => {
+                map.serialize_entry("role", &FileRole::LuaDatabase)?;
+                map.serialize_entry("skipped", skipped)?;
+            }
+            AppError::UnknownVariable(key) | AppError::InvalidValue { key, .. } => {
+                map.serialize_entry("key", key)?;
+            }
+            AppError::NotLoaded(what) => {
+                map.serialize_entry("missing", what)?;
+            }
+            AppError::GameDataUnavailable(_) => {
+                map.serialize_entry("role", &FileRole::GameData)?;
+            }
+            AppError::StatePoisoned | AppError::Dialog(_) => {}
+        }
+        map.end()
+    }
+}
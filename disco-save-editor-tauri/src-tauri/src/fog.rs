@@ -0,0 +1,74 @@
+use crate::error::{AppError, AppResult};
+use crate::models::*;
+
+/// Area an unrevealer belongs to: the first segment after `fog-`, so
+/// `fog-whirling-kitchen` is in `whirling` and `fog-main` in `main`.
+pub fn unrevealer_area(id: &str) -> &str {
+    let name = id.strip_prefix("fog-").unwrap_or(id);
+    name.split('-').next().unwrap_or(name)
+}
+
+pub fn build_fog_state(first: &FirstFile, fow_file: Option<&FowFile>) -> FogState {
+    FogState {
+        unrevealers: first.fow_unrevealers_status_cache.iter()
+            .filter_map(|(id, status)| FogStatus::parse(status).map(|s| (id.clone(), s)))
+            .collect(),
+        fow_file: fow_file.cloned(),
+    }
+}
+
+/// Mark every unrevealer of `area` as done, clearing its fog. Only the 1st
+/// file's `fowUnrevealersStatusCache` changes; `.FOW.json` is left as is.
+pub fn reveal_area(fog: &mut FogState, area: &str) -> AppResult<()> {
+    set_area_status(fog, area, FogStatus::Done)
+}
+
+/// Put the fog of `area` back: its unrevealers become unseen and any
+/// `.FOW.json` entry for the area is dropped. This is the only edit ever
+/// made to `.FOW.json`.
+pub fn hide_area(fog: &mut FogState, area: &str) -> AppResult<()> {
+    if let Some(file) = &fog.fow_file {
+        check_layout(file, fog)?;
+    }
+    set_area_status(fog, area, FogStatus::Unseen)?;
+    if let Some(file) = &mut fog.fow_file {
+        file.entries.remove(area);
+    }
+    Ok(())
+}
+
+/// Make sure `.FOW.json` is keyed by area the way `FowFile` assumes, so
+/// dropping an area's entry can't remove something else.
+fn check_layout(file: &FowFile, fog: &FogState) -> AppResult<()> {
+    let unknown = file.entries.iter()
+        .find(|(key, value)| !value.is_object() || !fog.unrevealers.keys().any(|id| unrevealer_area(id) == key.as_str()));
+    match unknown {
+        Some((key, _)) => Err(AppError::InvalidValue {
+            key: "fow_file".to_string(),
+            message: format!("entry {:?} isn't an area's fog; the .FOW.json layout isn't one the editor knows, so it is left alone", key),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn set_area_status(fog: &mut FogState, area: &str, status: FogStatus) -> AppResult<()> {
+    let mut found = false;
+    for (_, s) in fog.unrevealers.iter_mut().filter(|(id, _)| unrevealer_area(id) == area) {
+        *s = status;
+        found = true;
+    }
+    if found {
+        Ok(())
+    } else {
+        Err(AppError::InvalidValue { key: area.to_string(), message: "no fog unrevealers in this area".to_string() })
+    }
+}
+
+/// Write the unrevealer statuses back into the raw 1st file.
+pub fn apply_to_first_raw(fog: &FogState, first_raw: &mut serde_json::Value) {
+    if let Some(serde_json::Value::Object(cache)) = first_raw.get_mut("fowUnrevealersStatusCache") {
+        for (id, status) in &fog.unrevealers {
+            cache.insert(id.clone(), serde_json::json!(status.as_str()));
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::Path;
+use crate::error::{AppError, AppResult, FileRole};
 use crate::models::*;
 
 /// Loads static game definition data from bundled JSON files.
@@ -10,6 +11,8 @@ pub struct GameDataService {
     pub task_variables: HashMap<String, GameVariable>,
     pub reputation_variables: HashMap<String, GameVariable>,
     pub character_variables: HashMap<String, GameVariable>,
+    pub location_variables: HashMap<String, GameVariable>,
+    pub inventory_variables: HashMap<String, GameVariable>,
     pub all_variables: HashMap<String, GameVariable>,
     pub xp_variables: HashMap<String, GameVariableXp>,
     pub major_npcs: HashMap<String, Actor>,
@@ -26,6 +29,8 @@ impl Default for GameDataService {
             task_variables: HashMap::new(),
             reputation_variables: HashMap::new(),
             character_variables: HashMap::new(),
+            location_variables: HashMap::new(),
+            inventory_variables: HashMap::new(),
             all_variables: HashMap::new(),
             xp_variables: HashMap::new(),
             major_npcs: HashMap::new(),
@@ -44,7 +49,7 @@ impl Default for GameDataService {
 }
 
 impl GameDataService {
-    pub fn load(&mut self, game_data_folder: &str) -> Result<(), String> {
+    pub fn load(&mut self, game_data_folder: &str) -> AppResult<()> {
         let folder = Path::new(game_data_folder);
 
         self.skills = load_json_array::<GameSkill>(&folder.join("actors_skills.json"))?
@@ -65,6 +70,12 @@ impl GameDataService {
         self.character_variables = load_json_array::<GameVariable>(&folder.join("variables_character.json"))?
             .into_iter().map(|v| (v.name.clone(), v)).collect();
 
+        self.location_variables = load_json_array::<GameVariable>(&folder.join("variables_locations.json"))?
+            .into_iter().map(|v| (v.name.clone(), v)).collect();
+
+        self.inventory_variables = load_json_array::<GameVariable>(&folder.join("variables_inventory.json"))?
+            .into_iter().map(|v| (v.name.clone(), v)).collect();
+
         self.all_variables = load_json_array::<GameVariable>(&folder.join("variables_all.json"))?
             .into_iter().map(|v| (v.name.clone(), v)).collect();
 
@@ -76,9 +87,9 @@ impl GameDataService {
 
         let key_map_path = folder.join("skill_key_map.json");
         let key_map_json = std::fs::read_to_string(&key_map_path)
-            .map_err(|e| format!("Failed to read skill_key_map.json: {}", e))?;
-        self.skill_key_map = serde_json::from_str(&key_map_json)
-            .map_err(|e| format!("Failed to parse skill_key_map.json: {}", e))?;
+            .map_err(|e| AppError::io(FileRole::GameData, &key_map_path, e))?;
+        self.skill_key_map = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&key_map_json))
+            .map_err(|e| AppError::json(FileRole::GameData, &key_map_path, e))?;
 
         self.is_loaded = true;
         Ok(())
@@ -103,6 +114,40 @@ impl GameDataService {
             .unwrap_or_else(|| task_name.to_string())
     }
 
+    /// A Lua variable the way a fresh game starts it, typed from its
+    /// `initial_value` ("True"/"False" are booleans), or None if it isn't known.
+    pub fn initial_lua_value(&self, name: &str) -> Option<LuaValue> {
+        let variable = self.all_variables.get(name)?;
+        match &variable.initial_value {
+            serde_json::Value::Bool(b) => Some(LuaValue::Boolean(*b)),
+            serde_json::Value::Number(n) => n.as_f64().map(LuaValue::Number),
+            serde_json::Value::String(s) => Some(match s.as_str() {
+                "True" | "true" => LuaValue::Boolean(true),
+                "False" | "false" => LuaValue::Boolean(false),
+                _ => LuaValue::String(s.clone()),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn variable_category(&self, name: &str) -> Option<VariableCategory> {
+        if self.task_variables.contains_key(name) {
+            Some(VariableCategory::Tasks)
+        } else if self.xp_variables.contains_key(name) {
+            Some(VariableCategory::Xp)
+        } else if self.reputation_variables.contains_key(name) {
+            Some(VariableCategory::Reputation)
+        } else if self.character_variables.contains_key(name) {
+            Some(VariableCategory::Character)
+        } else if self.location_variables.contains_key(name) {
+            Some(VariableCategory::Locations)
+        } else if self.inventory_variables.contains_key(name) {
+            Some(VariableCategory::Inventory)
+        } else {
+            None
+        }
+    }
+
     pub fn get_all_catalog_items(&self) -> Vec<CatalogItem> {
         let mut items: Vec<CatalogItem> = self.items.values().map(|item| CatalogItem {
             name: item.name.clone(),
@@ -118,12 +163,12 @@ impl GameDataService {
     }
 }
 
-fn load_json_array<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>, String> {
+fn load_json_array<T: serde::de::DeserializeOwned>(path: &Path) -> AppResult<Vec<T>> {
     if !path.exists() {
         return Ok(vec![]);
     }
     let json = std::fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
-    serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+        .map_err(|e| AppError::io(FileRole::GameData, path, e))?;
+    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .map_err(|e| AppError::json(FileRole::GameData, path, e))
 }
@@ -0,0 +1,227 @@
+use serde_json::{json, Value};
+use crate::error::{AppError, AppResult};
+use crate::game_data::GameDataService;
+use crate::models::*;
+
+/// An owned item as the editor shows it, from its game definition and its
+/// entry in `itemListState`. Items without an entry look freshly picked up.
+pub fn item_display(name: &str, gd: &GameDataService, state: Option<&ItemState>) -> InventoryItemDisplay {
+    let game_def = gd.items.get(name);
+    let is_substance = game_def.map(|d| d.is_substance_item()).unwrap_or(false);
+    InventoryItemDisplay {
+        name: name.to_string(),
+        display_name: game_def.map(|d| d.display_name.clone()).unwrap_or_else(|| name.to_string()),
+        description: game_def.map(|d| d.description.clone()).unwrap_or_default(),
+        bonus: game_def.map(|d| d.medium_text_value.clone()).unwrap_or_default(),
+        is_owned: true,
+        is_equipped: false,
+        equip_slot: String::new(),
+        is_quest_item: game_def.map(|d| d.is_quest_item).unwrap_or(false),
+        is_cursed: game_def.map(|d| d.is_cursed()).unwrap_or(false),
+        is_substance,
+        substance_uses: state.map_or(new_substance_uses(is_substance), |s| s.substance_uses),
+        substance_time_left: state.map_or(0, |s| s.substance_time_left),
+        is_fresh: state.map_or(true, |s| s.is_fresh),
+        stack_name: game_def.and_then(|d| d.stack_name.clone()).unwrap_or_default(),
+    }
+}
+
+/// Pick up an item, along with the stack it goes in if that isn't owned yet.
+pub fn add_item(items: &mut Vec<InventoryItemDisplay>, name: &str, gd: &GameDataService) -> AppResult<()> {
+    if !gd.items.contains_key(name) {
+        return Err(invalid(name, "item is not in the game data"));
+    }
+    if items.iter().any(|i| i.name == name) {
+        return Err(invalid(name, "item is already owned"));
+    }
+    let item = item_display(name, gd, None);
+    if !item.stack_name.is_empty() && !items.iter().any(|i| i.name == item.stack_name) && gd.items.contains_key(&item.stack_name) {
+        items.push(item_display(&item.stack_name, gd, None));
+    }
+    items.push(item);
+    Ok(())
+}
+
+/// Drop an item. Dropping a stack drops everything stacked in it.
+pub fn remove_item(items: &mut Vec<InventoryItemDisplay>, name: &str) -> AppResult<()> {
+    if !items.iter().any(|i| i.name == name) {
+        return Err(invalid(name, "item is not owned"));
+    }
+    items.retain(|i| i.name != name && i.stack_name != name);
+    Ok(())
+}
+
+pub fn edit_item(
+    items: &mut [InventoryItemDisplay],
+    name: &str,
+    is_fresh: bool,
+    substance_uses: i64,
+    substance_time_left: i64,
+) -> AppResult<()> {
+    let item = items.iter_mut().find(|i| i.name == name)
+        .ok_or_else(|| invalid(name, "item is not owned"))?;
+    if !item.is_substance && (substance_uses != item.substance_uses || substance_time_left != item.substance_time_left) {
+        return Err(invalid(name, "only substances have uses and a timer"));
+    }
+    if substance_uses < -1 {
+        return Err(invalid("substance_uses", "must be -1 (unused) or more"));
+    }
+    if substance_time_left < 0 {
+        return Err(invalid("substance_time_left", "must not be negative"));
+    }
+    item.is_fresh = is_fresh;
+    item.substance_uses = substance_uses;
+    item.substance_time_left = substance_time_left;
+    Ok(())
+}
+
+/// The equipment slots an item can go in, from its `itemType`. The
+/// `equip_slot` column of `items_inventory.json` names the dialogue orb that
+/// reacts to the item rather than a slot.
+pub fn item_slots(item: &GameItem) -> &'static [&'static str] {
+    if item.is_substance_item() {
+        return &[];
+    }
+    match item.item_type as i64 {
+        2 => &["SHIRT"],
+        3 => &["JACKET"],
+        5 => &["PANTS"],
+        6 => &["NECK"],
+        7 => &["GLASSES"],
+        8 => &["GLOVES"],
+        9 => &["HAT"],
+        10 => &["SHOES"],
+        11 => &["HELDLEFT", "HELDRIGHT"],
+        _ => &[],
+    }
+}
+
+/// Put an item on, taking off whatever is in its slot. Held items go in the
+/// first free hand, or the left one if both are full. Owned items stacked in
+/// it (the left shoe of a pair) go on with it, without a slot of their own.
+pub fn equip_item(items: &mut [InventoryItemDisplay], name: &str, gd: &GameDataService) -> AppResult<()> {
+    let game_def = gd.items.get(name).ok_or_else(|| invalid(name, "item is not in the game data"))?;
+    let slots: Vec<&str> = item_slots(game_def).iter()
+        .copied()
+        .filter(|slot| gd.skill_key_map.equipment_slots.iter().any(|s| s == slot))
+        .collect();
+    let index = items.iter().position(|i| i.name == name && i.is_owned)
+        .ok_or_else(|| invalid(name, "item is not owned"))?;
+    if items[index].is_equipped {
+        return Err(invalid(name, "item is already equipped"));
+    }
+    let slot = slots.iter()
+        .find(|slot| !items.iter().any(|i| i.is_equipped && i.equip_slot == **slot))
+        .or(slots.first())
+        .ok_or_else(|| invalid(name, "item can't be equipped"))?
+        .to_string();
+
+    if let Some(occupant) = items.iter().position(|i| i.is_equipped && i.equip_slot == slot) {
+        let occupant = items[occupant].name.clone();
+        unequip_item(items, &occupant)?;
+    }
+    let item = &mut items[index];
+    item.is_equipped = true;
+    item.equip_slot = slot;
+    for stacked in items.iter_mut().filter(|i| i.stack_name == name && i.is_owned) {
+        stacked.is_equipped = true;
+        stacked.equip_slot.clear();
+    }
+    Ok(())
+}
+
+/// Take an item off, along with anything stacked in it.
+pub fn unequip_item(items: &mut [InventoryItemDisplay], name: &str) -> AppResult<()> {
+    if !items.iter().any(|i| i.name == name && i.is_equipped) {
+        return Err(invalid(name, "item is not equipped"));
+    }
+    for item in items.iter_mut().filter(|i| i.name == name || (i.stack_name == name && i.is_equipped)) {
+        item.is_equipped = false;
+        item.equip_slot.clear();
+    }
+    Ok(())
+}
+
+/// Check that every equipped item is in a slot it fits, one item per slot.
+/// Items stacked in an equipped item (the left shoe of a pair) need no slot.
+pub fn validate_equipment(items: &[InventoryItemDisplay], gd: &GameDataService) -> AppResult<()> {
+    let mut used: Vec<&str> = Vec::new();
+    for item in items.iter().filter(|i| i.is_equipped) {
+        if item.equip_slot.is_empty() {
+            if items.iter().any(|i| i.is_equipped && i.name == item.stack_name) {
+                continue;
+            }
+            return Err(invalid(&item.name, "equipped item has no slot"));
+        }
+        let fits = gd.items.get(&item.name).is_some_and(|d| item_slots(d).contains(&item.equip_slot.as_str()))
+            && gd.skill_key_map.equipment_slots.contains(&item.equip_slot);
+        if !fits {
+            return Err(invalid(&item.name, &format!("item doesn't go in {}", item.equip_slot)));
+        }
+        if used.contains(&item.equip_slot.as_str()) {
+            return Err(invalid(&item.equip_slot, "slot holds more than one item"));
+        }
+        used.push(&item.equip_slot);
+    }
+    Ok(())
+}
+
+/// Write item states back into the raw 2nd file's `inventoryState`. The
+/// game keeps an `itemListState` entry for every item it has seen, owned or
+/// not, so owned items are updated in place or added and the rest are left
+/// alone. Stacks that list their contents get the owned ones, and items no
+/// longer owned are taken out of the inventory view.
+pub fn apply_to_second_raw(items: &[InventoryItemDisplay], second_raw: &mut Value) {
+    let Some(Value::Object(inventory)) = second_raw.get_mut("inventoryState") else { return };
+
+    let list = inventory.entry("itemListState".to_string()).or_insert_with(|| json!([]));
+    if let Value::Array(list) = list {
+        for item in items.iter().filter(|i| i.is_owned) {
+            let existing = list.iter_mut()
+                .find(|entry| entry.get("itemName").and_then(Value::as_str) == Some(item.name.as_str()));
+            match existing {
+                Some(Value::Object(entry)) => {
+                    entry.insert("isFresh".to_string(), json!(item.is_fresh));
+                    entry.insert("substanceUses".to_string(), json!(item.substance_uses));
+                    entry.insert("substanceTimeLeft".to_string(), json!(item.substance_time_left));
+                    if let Some(Value::Array(stacked)) = entry.get_mut("StackItems") {
+                        *stacked = stack_contents(items, &item.name);
+                    }
+                }
+                _ => list.push(json!({
+                    "itemName": item.name,
+                    "isFresh": item.is_fresh,
+                    "substanceUses": item.substance_uses,
+                    "substanceTimeLeft": item.substance_time_left,
+                    "StackItems": null,
+                })),
+            }
+        }
+    }
+
+    if let Some(Value::Object(categories)) = inventory.get_mut("inventoryViewState").and_then(|v| v.get_mut("inventory")) {
+        let owned = |entry: &Value| {
+            let name = entry.get("Value").and_then(Value::as_str).unwrap_or_default();
+            items.iter().any(|i| i.is_owned && i.name == name)
+        };
+        for slots in categories.values_mut().filter_map(Value::as_array_mut) {
+            slots.retain(owned);
+        }
+    }
+}
+
+fn stack_contents(items: &[InventoryItemDisplay], stack: &str) -> Vec<Value> {
+    items.iter()
+        .filter(|i| i.is_owned && i.stack_name == stack)
+        .map(|i| json!(i.name))
+        .collect()
+}
+
+/// Substances the game hasn't seen used start at -1.
+fn new_substance_uses(is_substance: bool) -> i64 {
+    if is_substance { -1 } else { 0 }
+}
+
+fn invalid(key: &str, message: &str) -> AppError {
+    AppError::InvalidValue { key: key.to_string(), message: message.to_string() }
+}
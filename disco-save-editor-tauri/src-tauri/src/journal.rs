@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use serde_json::{json, Value};
+use crate::error::{AppError, AppResult};
+use crate::game_data::GameDataService;
+use crate::lua_database;
+use crate::models::*;
+
+/// Tasks in the order they were acquired.
+pub fn build_journal_state(journal: &AcquiredJournalTasks, gd: &GameDataService) -> JournalState {
+    let mut tasks: Vec<TaskDisplay> = journal.task_acquisitions.iter().map(|(task_name, acquired)| {
+        let mut subtasks: Vec<SubtaskDisplay> = journal.subtask_acquisitions.get(task_name)
+            .map(|subs| subs.iter().map(|(name, acquired)| SubtaskDisplay {
+                name: name.clone(),
+                description: gd.get_task_description(name),
+                acquired: acquired.clone(),
+            }).collect())
+            .unwrap_or_default();
+        subtasks.sort_by(|a, b| time_order(&a.acquired).cmp(&time_order(&b.acquired)).then_with(|| a.name.cmp(&b.name)));
+
+        let resolved = journal.task_resolutions.get(task_name).and_then(parse_resolution);
+        TaskDisplay {
+            task_name: task_name.clone(),
+            description: gd.get_task_description(task_name),
+            acquired_time: format_time(acquired),
+            acquired: acquired.clone(),
+            is_resolved: resolved.is_some(),
+            resolved,
+            is_new: journal.task_new_states.get(task_name).copied().unwrap_or(false),
+            subtasks,
+        }
+    }).collect();
+    tasks.sort_by(|a, b| time_order(&a.acquired).cmp(&time_order(&b.acquired)).then_with(|| a.task_name.cmp(&b.task_name)));
+
+    JournalState {
+        tasks,
+        last_active_task: journal.last_active_task.clone(),
+        last_done_task: journal.last_done_task.clone(),
+    }
+}
+
+/// Add a task from the game data to the journal as new and active.
+pub fn acquire_task(journal: &mut JournalState, task_name: &str, time: GameTimestamp, gd: &GameDataService) -> AppResult<()> {
+    require_task(task_name, gd)?;
+    if journal.tasks.iter().any(|t| t.task_name == task_name) {
+        return Err(invalid(task_name, "task is already in the journal"));
+    }
+    journal.tasks.push(TaskDisplay {
+        task_name: task_name.to_string(),
+        description: gd.get_task_description(task_name),
+        acquired_time: format_time(&time),
+        acquired: time,
+        resolved: None,
+        is_resolved: false,
+        is_new: true,
+        subtasks: Vec::new(),
+    });
+    journal.last_active_task = task_name.to_string();
+    Ok(())
+}
+
+/// Take a task out of the journal as if it had never been acquired.
+pub fn remove_task(journal: &mut JournalState, task_name: &str) -> AppResult<()> {
+    let index = journal.tasks.iter().position(|t| t.task_name == task_name)
+        .ok_or_else(|| invalid(task_name, "task is not in the journal"))?;
+    journal.tasks.remove(index);
+    if journal.last_active_task == task_name {
+        journal.last_active_task.clear();
+    }
+    if journal.last_done_task == task_name {
+        journal.last_done_task.clear();
+    }
+    Ok(())
+}
+
+/// Mark a task done. Like the game, this clears its new flag.
+pub fn resolve_task(journal: &mut JournalState, task_name: &str, time: GameTimestamp) -> AppResult<()> {
+    let task = task_mut(journal, task_name)?;
+    task.resolved = Some(time);
+    task.is_resolved = true;
+    task.is_new = false;
+    journal.last_done_task = task_name.to_string();
+    Ok(())
+}
+
+/// Reopen a task, setting its new flag again as an open task has.
+pub fn unresolve_task(journal: &mut JournalState, task_name: &str) -> AppResult<()> {
+    let task = task_mut(journal, task_name)?;
+    task.resolved = None;
+    task.is_resolved = false;
+    task.is_new = true;
+    if journal.last_done_task == task_name {
+        journal.last_done_task.clear();
+    }
+    Ok(())
+}
+
+pub fn add_subtask(journal: &mut JournalState, task_name: &str, subtask: &str, time: GameTimestamp, gd: &GameDataService) -> AppResult<()> {
+    require_task(subtask, gd)?;
+    let task = task_mut(journal, task_name)?;
+    if task.subtasks.iter().any(|s| s.name == subtask) {
+        return Err(invalid(subtask, "subtask is already in this task"));
+    }
+    task.subtasks.push(SubtaskDisplay {
+        name: subtask.to_string(),
+        description: gd.get_task_description(subtask),
+        acquired: time,
+    });
+    Ok(())
+}
+
+pub fn remove_subtask(journal: &mut JournalState, task_name: &str, subtask: &str) -> AppResult<()> {
+    let task = task_mut(journal, task_name)?;
+    let index = task.subtasks.iter().position(|s| s.name == subtask)
+        .ok_or_else(|| invalid(subtask, "subtask is not in this task"))?;
+    task.subtasks.remove(index);
+    Ok(())
+}
+
+pub fn set_task_new(journal: &mut JournalState, task_name: &str, is_new: bool) -> AppResult<()> {
+    task_mut(journal, task_name)?.is_new = is_new;
+    Ok(())
+}
+
+/// Set the `TASK.*` Lua flags for what changed between the journal in the
+/// raw 2nd file and `journal`: acquiring sets the task's flag, resolving its
+/// `_done` flag; undoing either clears them. Flags of tasks that didn't change
+/// are left alone, since the game also resolves tasks by cancelling them.
+pub fn sync_lua(second_raw: &Value, journal: &JournalState, lua_db: &mut LuaDatabase, gd: &GameDataService) {
+    let before: AcquiredJournalTasks = second_raw.get("aquiredJournalTasks")
+        .and_then(|j| serde_json::from_value(j.clone()).ok())
+        .unwrap_or_default();
+    let mut set = |key: String, value: bool| {
+        if gd.all_variables.contains_key(&key) {
+            lua_database::set_lua_value(lua_db, &key, LuaValue::Boolean(value));
+        }
+    };
+
+    for task in &journal.tasks {
+        let name = &task.task_name;
+        if !before.task_acquisitions.contains_key(name) {
+            set(name.clone(), true);
+        }
+        let was_resolved = before.task_resolutions.get(name).and_then(parse_resolution).is_some();
+        if task.resolved.is_some() && !was_resolved {
+            set(format!("{}_done", name), true);
+        } else if task.resolved.is_none() && was_resolved {
+            set(format!("{}_done", name), false);
+            set(format!("{}_cancelled", name), false);
+        }
+
+        let subtasks_before = before.subtask_acquisitions.get(name);
+        for subtask in &task.subtasks {
+            if !subtasks_before.is_some_and(|subs| subs.contains_key(&subtask.name)) {
+                set(subtask.name.clone(), true);
+            }
+        }
+        for subtask in subtasks_before.into_iter().flat_map(|subs| subs.keys()) {
+            if !task.subtasks.iter().any(|s| &s.name == subtask) {
+                set(subtask.clone(), false);
+            }
+        }
+    }
+
+    for name in before.task_acquisitions.keys().filter(|name| !journal.tasks.iter().any(|t| &t.task_name == *name)) {
+        set(name.clone(), false);
+        set(format!("{}_done", name), false);
+        set(format!("{}_cancelled", name), false);
+        for subtask in before.subtask_acquisitions.get(name).into_iter().flat_map(|subs| subs.keys()) {
+            set(subtask.clone(), false);
+        }
+    }
+}
+
+/// Write the journal back into the raw 2nd file.
+pub fn apply_to_second_raw(journal: &JournalState, second_raw: &mut Value) {
+    let Some(Value::Object(raw)) = second_raw.get_mut("aquiredJournalTasks") else { return };
+
+    let names: HashSet<&str> = journal.tasks.iter().map(|t| t.task_name.as_str()).collect();
+    let removed: Vec<String> = match raw.get("TaskAquisitions") {
+        Some(Value::Object(acquired)) => acquired.keys().filter(|k| !names.contains(k.as_str())).cloned().collect(),
+        _ => Vec::new(),
+    };
+
+    set_entries(raw, "TaskAquisitions", journal.tasks.iter()
+        .map(|t| (t.task_name.clone(), timestamp_json(&t.acquired)))
+        .collect());
+    set_entries(raw, "TaskResolutions", journal.tasks.iter()
+        .map(|t| (t.task_name.clone(), t.resolved.as_ref().map_or(Value::Null, timestamp_json)))
+        .collect());
+    set_entries(raw, "SubtaskAquisitions", journal.tasks.iter()
+        .filter(|t| !t.subtasks.is_empty())
+        .map(|t| (t.task_name.clone(), Value::Object(t.subtasks.iter()
+            .map(|s| (s.name.clone(), timestamp_json(&s.acquired)))
+            .collect())))
+        .collect());
+
+    // New states are also kept for tasks that left the journal some other
+    // way, so only the ones removed here are dropped.
+    if let Some(Value::Object(states)) = raw.get_mut("TaskNewStates") {
+        for name in &removed {
+            states.remove(name);
+        }
+        for task in &journal.tasks {
+            states.insert(task.task_name.clone(), json!(task.is_new));
+        }
+    }
+
+    raw.insert("LastActiveTask".to_string(), json!(journal.last_active_task));
+    raw.insert("LastDoneTask".to_string(), json!(journal.last_done_task));
+}
+
+/// Make the object at `field` hold exactly `entries`, updating existing keys in place.
+fn set_entries(raw: &mut serde_json::Map<String, Value>, field: &str, entries: Vec<(String, Value)>) {
+    let map = raw.entry(field.to_string()).or_insert_with(|| json!({}));
+    let Value::Object(map) = map else { return };
+    let keep: HashSet<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
+    map.retain(|k, _| keep.contains(k.as_str()));
+    for (key, value) in entries {
+        map.insert(key, value);
+    }
+}
+
+fn timestamp_json(time: &GameTimestamp) -> Value {
+    json!({
+        "dayCounter": time.day_counter,
+        "realDayCounter": time.real_day_counter,
+        "dayMinutes": time.day_minutes,
+        "seconds": time.seconds,
+    })
+}
+
+/// Unresolved tasks have a null resolution.
+fn parse_resolution(value: &Value) -> Option<GameTimestamp> {
+    match value {
+        Value::Object(o) if !o.is_empty() => serde_json::from_value(value.clone()).ok(),
+        _ => None,
+    }
+}
+
+fn format_time(time: &GameTimestamp) -> String {
+    format!("Day {}, {:02}:{:02}", time.day_counter, time.hours(), time.minutes())
+}
+
+fn time_order(time: &GameTimestamp) -> (i64, i64, i64) {
+    (time.real_day_counter, time.day_minutes, time.seconds)
+}
+
+fn require_task(name: &str, gd: &GameDataService) -> AppResult<()> {
+    if gd.task_variables.contains_key(name) {
+        Ok(())
+    } else {
+        Err(invalid(name, "not a task in the game data"))
+    }
+}
+
+fn task_mut<'a>(journal: &'a mut JournalState, task_name: &str) -> AppResult<&'a mut TaskDisplay> {
+    journal.tasks.iter_mut().find(|t| t.task_name == task_name)
+        .ok_or_else(|| invalid(task_name, "task is not in the journal"))
+}
+
+fn invalid(key: &str, message: &str) -> AppError {
+    AppError::InvalidValue { key: key.to_string(), message: message.to_string() }
+}
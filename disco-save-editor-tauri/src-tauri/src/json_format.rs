@@ -0,0 +1,373 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Serialize `value` as an edit of `original`: untouched values keep their
+/// exact text (number lexemes included), objects keep their key order and
+/// whitespace, and only what changed is re-rendered, in the indentation and
+/// line endings `original` uses. Keys that are new are appended to their object.
+///
+/// Returns None when `original` isn't valid JSON.
+pub fn rewrite(original: &str, value: &Value) -> Option<String> {
+    let mut parser = Parser { text: original, pos: 0 };
+    parser.skip_ws();
+    let root = parser.value()?;
+    parser.skip_ws();
+    if parser.pos != original.len() {
+        return None;
+    }
+
+    let style = Style::detect(original, &root);
+    let mut out = String::with_capacity(original.len());
+    out.push_str(&original[..root.start]);
+    style.emit(original, &root, value, "", &mut out);
+    out.push_str(&original[root.end..]);
+    Some(out)
+}
+
+/// A value's position in the original text.
+struct Node {
+    start: usize,
+    end: usize,
+    kind: Kind,
+}
+
+enum Kind {
+    Scalar,
+    Object(Vec<Member>),
+    Array(Vec<Node>),
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    key_end: usize,
+    value: Node,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> Option<()> {
+        self.skip_ws();
+        (self.peek() == Some(byte)).then(|| self.pos += 1)
+    }
+
+    fn value(&mut self) -> Option<Node> {
+        let start = self.pos;
+        let kind = match self.peek()? {
+            b'{' => Kind::Object(self.members()?),
+            b'[' => Kind::Array(self.items()?),
+            b'"' => {
+                self.string()?;
+                Kind::Scalar
+            }
+            _ => {
+                while matches!(self.peek(), Some(b) if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.')) {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return None;
+                }
+                Kind::Scalar
+            }
+        };
+        Some(Node { start, end: self.pos, kind })
+    }
+
+    fn string(&mut self) -> Option<()> {
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                b'\\' => self.pos += 2,
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn members(&mut self) -> Option<Vec<Member>> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        if self.eat(b'}').is_some() {
+            return Some(members);
+        }
+        loop {
+            self.skip_ws();
+            let key_start = self.pos;
+            if self.peek()? != b'"' {
+                return None;
+            }
+            self.string()?;
+            let key_end = self.pos;
+            let key: String = serde_json::from_str(&self.text[key_start..key_end]).ok()?;
+            self.eat(b':')?;
+            self.skip_ws();
+            let value = self.value()?;
+            members.push(Member { key, key_start, key_end, value });
+            if self.eat(b',').is_none() {
+                self.eat(b'}')?;
+                return Some(members);
+            }
+        }
+    }
+
+    fn items(&mut self) -> Option<Vec<Node>> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.eat(b']').is_some() {
+            return Some(items);
+        }
+        loop {
+            self.skip_ws();
+            items.push(self.value()?);
+            if self.eat(b',').is_none() {
+                self.eat(b']')?;
+                return Some(items);
+            }
+        }
+    }
+}
+
+/// How the original file lays out new content.
+struct Style {
+    newline: &'static str,
+    indent: String,
+}
+
+impl Style {
+    fn detect(text: &str, root: &Node) -> Style {
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let first_child = match &root.kind {
+            Kind::Object(members) => members.first().map(|m| m.key_start),
+            Kind::Array(items) => items.first().map(|i| i.start),
+            Kind::Scalar => None,
+        };
+        let indent = first_child
+            .map(|start| line_indent(&text[root.start + 1..start]))
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or("    ")
+            .to_string();
+        Style { newline, indent }
+    }
+
+    /// Write `new` in place of `node`, reusing the original text wherever possible.
+    /// `indent` is the indentation of the line `node` starts on.
+    fn emit(&self, text: &str, node: &Node, new: &Value, indent: &str, out: &mut String) {
+        match (&node.kind, new) {
+            (Kind::Object(members), Value::Object(map)) if !members.is_empty() => {
+                let unchanged_keys = members.len() == map.len() && members.iter().all(|m| map.contains_key(&m.key));
+                let child_indent = self.child_indent(text, node.start + 1, members[0].key_start, indent);
+                if unchanged_keys {
+                    let mut pos = node.start;
+                    for member in members {
+                        out.push_str(&text[pos..member.value.start]);
+                        self.emit(text, &member.value, &map[&member.key], &child_indent, out);
+                        pos = member.value.end;
+                    }
+                    out.push_str(&text[pos..node.end]);
+                } else {
+                    let colon = &text[members[0].key_end..members[0].value.start];
+                    let mut parts = Vec::new();
+                    for member in members.iter().filter(|m| map.contains_key(&m.key)) {
+                        let mut part = text[member.key_start..member.value.start].to_string();
+                        self.emit(text, &member.value, &map[&member.key], &child_indent, &mut part);
+                        parts.push(part);
+                    }
+                    let known: std::collections::HashSet<&str> = members.iter().map(|m| m.key.as_str()).collect();
+                    for (key, value) in map.iter().filter(|(key, _)| !known.contains(key.as_str())) {
+                        parts.push(format!("{}{}{}", json_string(key), colon, self.render(value, &child_indent)));
+                    }
+                    let spans: Vec<(usize, usize)> = members.iter().map(|m| (m.key_start, m.value.end)).collect();
+                    join(text, node, &spans, &parts, out);
+                }
+            }
+            (Kind::Array(items), Value::Array(values)) if !items.is_empty() => {
+                let child_indent = self.child_indent(text, node.start + 1, items[0].start, indent);
+                if items.len() == values.len() {
+                    let mut pos = node.start;
+                    for (item, value) in items.iter().zip(values) {
+                        out.push_str(&text[pos..item.start]);
+                        self.emit(text, item, value, &child_indent, out);
+                        pos = item.end;
+                    }
+                    out.push_str(&text[pos..node.end]);
+                } else {
+                    let parts: Vec<String> = values.iter().enumerate().map(|(i, value)| match items.get(i) {
+                        Some(item) => {
+                            let mut part = String::new();
+                            self.emit(text, item, value, &child_indent, &mut part);
+                            part
+                        }
+                        None => self.render(value, &child_indent),
+                    }).collect();
+                    let spans: Vec<(usize, usize)> = items.iter().map(|i| (i.start, i.end)).collect();
+                    join(text, node, &spans, &parts, out);
+                }
+            }
+            (Kind::Scalar, _) if scalar_eq(&text[node.start..node.end], new) => {
+                out.push_str(&text[node.start..node.end]);
+            }
+            (Kind::Object(members), Value::Object(map)) if members.is_empty() && map.is_empty() => {
+                out.push_str(&text[node.start..node.end]);
+            }
+            (Kind::Array(items), Value::Array(values)) if items.is_empty() && values.is_empty() => {
+                out.push_str(&text[node.start..node.end]);
+            }
+            _ => out.push_str(&self.render(new, indent)),
+        }
+    }
+
+    /// Indentation of a container's entries, from the whitespace before its
+    /// first entry; inline containers (no line break) keep the parent's.
+    fn child_indent(&self, text: &str, open: usize, first: usize, indent: &str) -> String {
+        let lead = &text[open..first];
+        if lead.contains('\n') {
+            line_indent(lead).to_string()
+        } else {
+            indent.to_string()
+        }
+    }
+
+    /// Pretty-print a new value, continuing lines at `indent`.
+    fn render(&self, value: &Value, indent: &str) -> String {
+        let mut buf = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buf, formatter);
+        // Writing a Value to memory can't fail
+        let _ = value.serialize(&mut serializer);
+        String::from_utf8_lossy(&buf).replace('\n', &format!("{}{}", self.newline, indent))
+    }
+}
+
+/// Write a container whose entries changed: `parts` laid out the way the
+/// original entries at `spans` were, separated like its first two.
+fn join(text: &str, node: &Node, spans: &[(usize, usize)], parts: &[String], out: &mut String) {
+    let (first, last) = (spans[0], spans[spans.len() - 1]);
+    let lead = &text[node.start + 1..first.0];
+    let separator = match spans.get(1) {
+        Some(second) => text[first.1..second.0].to_string(),
+        None => format!(",{}", lead),
+    };
+
+    out.push_str(&text[node.start..node.start + 1]);
+    if !parts.is_empty() {
+        out.push_str(lead);
+        out.push_str(&parts.join(&separator));
+        out.push_str(&text[last.1..node.end - 1]);
+    }
+    out.push_str(&text[node.end - 1..node.end]);
+}
+
+/// The whitespace after the last line break.
+fn line_indent(ws: &str) -> &str {
+    ws.rsplit('\n').next().unwrap_or("")
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// Whether a scalar's original text already says `new`. Numbers compare by
+/// value, so `924.0` stays `924.0` when the edit wrote back `924`.
+fn scalar_eq(lexeme: &str, new: &Value) -> bool {
+    let Ok(old) = serde_json::from_str::<Value>(lexeme) else { return false };
+    match (&old, new) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => a.as_f64() == b.as_f64(),
+        },
+        _ => &old == new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SAMPLE: &str = "{\n  \"hours\": 924.0,\n  \"big\": 1E3,\n  \"zero\": -0.0,\n  \"name\": \"caf\\u00e9\",\n  \"nested\": {\"b\": [1, 2.50, true], \"a\": null},\n  \"list\": [\n    {\"x\": 1}\n  ],\n  \"empty\": {}\n}\n";
+
+    fn parsed(text: &str) -> Value {
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn unedited_value_is_written_back_unchanged() {
+        assert_eq!(rewrite(SAMPLE, &parsed(SAMPLE)).unwrap(), SAMPLE);
+    }
+
+    #[test]
+    fn unedited_crlf_text_is_written_back_unchanged() {
+        let crlf = SAMPLE.replace('\n', "\r\n");
+        assert_eq!(rewrite(&crlf, &parsed(&crlf)).unwrap(), crlf);
+    }
+
+    #[test]
+    fn example_save_files_are_written_back_unchanged() {
+        let folder = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../example/MARTINAISE, DAY 1, 15-24(2_6_2026 4-25-35 PM).ntwtf");
+        for entry in std::fs::read_dir(folder).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|e| e == "json") {
+                let text = std::fs::read_to_string(&path).unwrap();
+                assert_eq!(rewrite(&text, &parsed(&text)).unwrap(), text, "{}", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn one_edit_changes_only_its_lexeme() {
+        let mut value = parsed(SAMPLE);
+        value["nested"]["b"][1] = json!(3);
+        assert_eq!(rewrite(SAMPLE, &value).unwrap(), SAMPLE.replace("2.50", "3"));
+
+        let mut value = parsed(SAMPLE);
+        value["name"] = json!("bar");
+        assert_eq!(rewrite(SAMPLE, &value).unwrap(), SAMPLE.replace("\"caf\\u00e9\"", "\"bar\""));
+    }
+
+    #[test]
+    fn number_lexemes_survive_when_their_value_does() {
+        let mut value = parsed(SAMPLE);
+        value["hours"] = json!(924);
+        value["big"] = json!(1000);
+        value["zero"] = json!(0.0);
+        assert_eq!(rewrite(SAMPLE, &value).unwrap(), SAMPLE);
+
+        value["hours"] = json!(925.5);
+        assert_eq!(rewrite(SAMPLE, &value).unwrap(), SAMPLE.replace("924.0", "925.5"));
+    }
+
+    #[test]
+    fn edits_to_crlf_text_keep_crlf() {
+        let crlf = SAMPLE.replace('\n', "\r\n");
+        let mut value = parsed(&crlf);
+        value["hours"] = json!(1.5);
+        value["added"] = json!({"k": [1]});
+        let out = rewrite(&crlf, &value).unwrap();
+        assert!(out.starts_with("{\r\n  \"hours\": 1.5,\r\n  \"big\": 1E3,"));
+        assert!(out.ends_with("  \"empty\": {},\r\n  \"added\": {\r\n    \"k\": [\r\n      1\r\n    ]\r\n  }\r\n}\r\n"), "{:?}", out);
+        assert!(!out.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn invalid_original_is_rejected() {
+        assert!(rewrite("{\"a\": 1,", &json!({"a": 1})).is_none());
+    }
+}
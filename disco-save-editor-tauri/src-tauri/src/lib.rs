@@ -1,14 +1,34 @@
-mod models;
-mod lua_database;
-mod states_lua;
-mod game_data;
-mod save_service;
+pub mod error;
+pub mod models;
+pub mod lua_database;
+pub mod lua_index;
+pub mod variable_report;
+pub mod states_lua;
+pub mod fog;
+pub mod journal;
+pub mod white_checks;
+pub mod thought_cabinet;
+pub mod skill_modifiers;
+pub mod inventory;
+pub mod containers;
+pub mod doors;
+pub mod time;
+pub mod game_data;
+pub mod save_service;
+pub mod save_diff;
+pub mod atomic_write;
+pub mod json_format;
+pub mod save_discovery;
+pub mod settings;
+pub mod snapshots;
+#[cfg(feature = "gui")]
 mod commands;
 
-use commands::AppState;
-
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    use commands::AppState;
+
     tauri::Builder::default()
         .manage(AppState::default())
         .plugin(tauri_plugin_dialog::init())
@@ -27,10 +47,47 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::discover_saves,
             commands::pick_save_file,
+            commands::pick_save_folder,
+            commands::get_extra_save_roots,
+            commands::set_extra_save_roots,
             commands::load_save,
             commands::save_changes,
+            commands::reveal_fog_area,
+            commands::hide_fog_area,
+            commands::acquire_task,
+            commands::remove_task,
+            commands::resolve_task,
+            commands::unresolve_task,
+            commands::add_subtask,
+            commands::remove_subtask,
+            commands::set_task_new,
+            commands::reopen_white_check,
+            commands::fail_white_check,
+            commands::edit_white_check,
+            commands::remove_white_check,
+            commands::unlock_thought_slot,
+            commands::lock_thought_slot,
+            commands::add_inventory_item,
+            commands::remove_inventory_item,
+            commands::edit_inventory_item,
+            commands::equip_inventory_item,
+            commands::unequip_inventory_item,
+            commands::add_container_item,
+            commands::remove_container_item,
+            commands::edit_container_item,
+            commands::reroll_containers,
+            commands::set_door_open,
+            commands::advance_time,
+            commands::diff_saves,
+            commands::list_snapshots,
+            commands::create_snapshot,
+            commands::diff_snapshots,
+            commands::restore_snapshot,
+            commands::prune_snapshots,
             commands::get_lua_variables,
+            commands::get_variable_report,
             commands::get_catalog_items,
+            commands::get_skill_breakdown,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,63 +1,134 @@
 use std::collections::HashMap;
 use std::io::{self, Read, Write, Cursor};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use crate::models::LuaValue;
+use crate::error::{AppError, AppResult, FileRole};
+use crate::models::{ByteRange, LuaChunk, LuaDatabase, LuaKey, LuaSalvageReport, LuaTable, LuaValue};
+
+const TYPE_BYTES: &str = "type byte S/N/B/T";
+
+/// How to react to bytes that do not form a valid value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Fail on the first malformed byte. Used for anything that may be saved.
+    #[default]
+    Strict,
+    /// Recovery path: skip malformed bytes one at a time and keep whatever
+    /// parses, reporting the skipped ranges.
+    Salvage,
+}
 
 /// Parses the binary TLV format used by .ntwtf.lua files.
 /// Format (matching the C#/Go reference):
 ///   S = string  (7-bit encoded length + UTF-8 bytes)
 ///   N = number  (little-endian float64)
 ///   B = boolean (single byte, 0 or 1)
-///   T = table   (4-byte padding + 4-byte LE int32 count, then count key/value pairs)
+///   T = table   (LE int32 array length + that many values,
+///                then LE int32 hash length + that many key/value pairs)
 #[allow(dead_code)]
-pub fn parse_lua_database(path: &str) -> io::Result<HashMap<String, LuaValue>> {
-    let data = std::fs::read(path)?;
+pub fn parse_lua_database(path: &str) -> AppResult<LuaDatabase> {
+    let data = std::fs::read(path).map_err(|e| AppError::io(FileRole::LuaDatabase, path, e))?;
     parse_lua_data(&data)
 }
 
-pub fn parse_lua_data(data: &Vec<u8>) -> io::Result<HashMap<String, LuaValue>> {
+/// Strictly parse a whole .ntwtf.lua file into its top-level chunks, in file
+/// order. NUL bytes between top-level values are the only tolerated filler;
+/// they are kept as raw chunks so `serialize_lua_database` reproduces the
+/// input exactly.
+pub fn parse_lua_data(data: &[u8]) -> AppResult<LuaDatabase> {
+    parse_with_mode(data, ParseMode::Strict).map(|(db, _)| db)
+}
+
+/// Parse a .ntwtf.lua file in the given mode. The report is only produced
+/// in salvage mode. Salvaged garbage is dropped rather than kept, so saving
+/// a salvaged database writes a clean file.
+pub fn parse_with_mode(data: &[u8], mode: ParseMode) -> AppResult<(LuaDatabase, Option<LuaSalvageReport>)> {
     let mut cursor = Cursor::new(data);
-    let mut all = HashMap::new();
+    let mut db = LuaDatabase::default();
+    let mut filler: Vec<u8> = Vec::new();
+    let mut report = LuaSalvageReport::default();
+    let mut first_error: Option<AppError> = None;
+    let mut parsed_table = false;
 
     while (cursor.position() as usize) < data.len() {
         let pos = cursor.position();
+        if data[pos as usize] == 0 {
+            filler.push(0);
+            cursor.set_position(pos + 1);
+            continue;
+        }
+
         match read_value(&mut cursor) {
-            Ok(LuaValue::Table(table)) => {
-                for (k, v) in table {
-                    all.insert(k, v);
+            Ok(value) => {
+                if !filler.is_empty() {
+                    db.chunks.push(LuaChunk::Raw(std::mem::take(&mut filler)));
                 }
+                parsed_table |= matches!(value, LuaValue::Table(_));
+                db.chunks.push(LuaChunk::Value(value));
             }
-            Ok(_) => {}
-            Err(_) => {
-                if (cursor.position() as usize) < data.len() {
-                    cursor.set_position(pos + 1);
-                    continue;
+            Err(e) => {
+                if mode == ParseMode::Strict {
+                    return Err(e);
+                }
+                let skip_to = if (cursor.position() as usize) < data.len() {
+                    pos + 1
+                } else {
+                    data.len() as u64
+                };
+                match report.skipped.last_mut() {
+                    Some(range) if range.end == pos => range.end = skip_to,
+                    _ => report.skipped.push(ByteRange { start: pos, end: skip_to }),
                 }
-                if !all.is_empty() {
-                    break;
+                if report.first_error.is_empty() {
+                    report.first_error = e.to_string();
                 }
-                return Err(io::Error::new(io::ErrorKind::InvalidData, "Failed to parse lua database"));
+                first_error.get_or_insert(e);
+                cursor.set_position(skip_to);
             }
         }
     }
 
-    Ok(all)
+    if !filler.is_empty() {
+        db.chunks.push(LuaChunk::Raw(filler));
+    }
+
+    match mode {
+        ParseMode::Strict => Ok((db, None)),
+        ParseMode::Salvage => match first_error {
+            Some(e) if !parsed_table => Err(e),
+            _ => Ok((db, Some(report))),
+        },
+    }
 }
 
 #[allow(dead_code)]
-pub fn write_lua_database(path: &str, data: &HashMap<String, LuaValue>) -> io::Result<()> {
+pub fn write_lua_database(path: &str, data: &LuaDatabase) -> io::Result<()> {
     let bytes = serialize_lua_database(data)?;
     std::fs::write(path, bytes)
 }
 
-pub fn serialize_lua_database(data: &HashMap<String, LuaValue>) -> io::Result<Vec<u8>> {
+pub fn serialize_lua_database(data: &LuaDatabase) -> io::Result<Vec<u8>> {
     let mut buffer = Vec::new();
-    write_value(&mut buffer, &LuaValue::Table(data.clone()))?;
+    for chunk in &data.chunks {
+        match chunk {
+            LuaChunk::Value(value) => write_value(&mut buffer, value)?,
+            LuaChunk::Raw(bytes) => buffer.write_all(bytes)?,
+        }
+    }
     Ok(buffer)
 }
 
-fn read_value(reader: &mut Cursor<&Vec<u8>>) -> io::Result<LuaValue> {
-    let type_byte = reader.read_u8()?;
+fn parse_error(offset: u64, expected: impl Into<String>, found: Option<u8>) -> AppError {
+    AppError::LuaParse { offset, expected: expected.into(), found }
+}
+
+/// Error for a read that ran past the end of the data.
+fn truncated(offset: u64, expected: impl Into<String>) -> AppError {
+    parse_error(offset, expected, None)
+}
+
+fn read_value(reader: &mut Cursor<&[u8]>) -> AppResult<LuaValue> {
+    let offset = reader.position();
+    let type_byte = reader.read_u8().map_err(|_| truncated(offset, TYPE_BYTES))?;
     match type_byte {
         0x53 => {
             // String
@@ -66,58 +137,90 @@ fn read_value(reader: &mut Cursor<&Vec<u8>>) -> io::Result<LuaValue> {
         }
         0x4E => {
             // Number (float64 LE)
-            let n = reader.read_f64::<LittleEndian>()?;
+            let pos = reader.position();
+            let n = reader.read_f64::<LittleEndian>().map_err(|_| truncated(pos, "8-byte number"))?;
             Ok(LuaValue::Number(n))
         }
         0x42 => {
             // Boolean
-            let b = reader.read_u8()?;
+            let pos = reader.position();
+            let b = reader.read_u8().map_err(|_| truncated(pos, "boolean byte"))?;
             Ok(LuaValue::Boolean(b != 0))
         }
         0x54 => {
             // Table
             read_table(reader)
         }
-        _ => Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("Unknown type byte 0x{:02X} at offset {}", type_byte, reader.position() - 1),
-        )),
+        _ => Err(parse_error(offset, TYPE_BYTES, Some(type_byte))),
     }
 }
 
-fn read_table(reader: &mut Cursor<&Vec<u8>>) -> io::Result<LuaValue> {
-    let mut padding = [0u8; 4];
-    reader.read_exact(&mut padding)?;
-    let count = reader.read_i32::<LittleEndian>()?;
-    let mut dict = HashMap::with_capacity(count as usize);
+fn read_table(reader: &mut Cursor<&[u8]>) -> AppResult<LuaValue> {
+    let array_len = read_length(reader, "array part length")?;
+    let mut array = Vec::with_capacity(array_len);
+    for _ in 0..array_len {
+        array.push(read_value(reader)?);
+    }
 
-    for _ in 0..count {
-        let key_val = read_value(reader)?;
-        let key = match key_val {
-            LuaValue::String(s) => s,
-            LuaValue::Number(n) => n.to_string(),
-            LuaValue::Boolean(b) => b.to_string(),
-            _ => String::from("unknown"),
+    let hash_len = read_length(reader, "hash part length")?;
+    let mut entries = Vec::with_capacity(hash_len);
+    for _ in 0..hash_len {
+        let key_offset = reader.position();
+        let key = match read_value(reader)? {
+            LuaValue::String(s) => LuaKey::String(s),
+            LuaValue::Number(n) => LuaKey::Number(n),
+            LuaValue::Boolean(b) => LuaKey::Boolean(b),
+            LuaValue::Table(_) => {
+                return Err(parse_error(key_offset, "table key of type S/N/B", Some(0x54)));
+            }
         };
         let value = read_value(reader)?;
-        dict.insert(key, value);
+        entries.push((key, value));
     }
 
-    Ok(LuaValue::Table(dict))
+    Ok(LuaValue::Table(LuaTable { array, entries }))
 }
 
-fn read_string(reader: &mut Cursor<&Vec<u8>>) -> io::Result<String> {
+/// Read a table part length. The result is safe to preallocate with: every
+/// element takes at least 2 bytes, so a length the remaining data can't hold
+/// is rejected here.
+fn read_length(reader: &mut Cursor<&[u8]>, what: &str) -> AppResult<usize> {
+    let pos = reader.position();
+    let len = reader.read_i32::<LittleEndian>().map_err(|_| truncated(pos, what))?;
+    let remaining = reader.get_ref().len().saturating_sub(reader.position() as usize);
+    if len < 0 || len as usize > remaining / 2 {
+        return Err(parse_error(pos, format!("{} of at most {}", what, remaining / 2), Some(reader.get_ref()[pos as usize])));
+    }
+    Ok(len as usize)
+}
+
+fn read_string(reader: &mut Cursor<&[u8]>) -> AppResult<String> {
+    let pos = reader.position();
     let length = read_7bit_encoded_int(reader)?;
-    let mut buf = vec![0u8; length as usize];
-    reader.read_exact(&mut buf)?;
-    String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    if length < 0 {
+        return Err(parse_error(pos, "non-negative string length", reader.get_ref().get(pos as usize).copied()));
+    }
+    let pos = reader.position();
+    let mut buf = vec![0u8; (length as usize).min(reader.get_ref().len())];
+    if buf.len() < length as usize {
+        return Err(truncated(pos, format!("{} bytes of string data", length)));
+    }
+    reader.read_exact(&mut buf).map_err(|_| truncated(pos, format!("{} bytes of string data", length)))?;
+    String::from_utf8(buf).map_err(|e| {
+        let bad = e.utf8_error().valid_up_to();
+        parse_error(pos + bad as u64, "UTF-8 string data", Some(e.as_bytes()[bad]))
+    })
 }
 
-fn read_7bit_encoded_int(reader: &mut Cursor<&Vec<u8>>) -> io::Result<i32> {
+fn read_7bit_encoded_int(reader: &mut Cursor<&[u8]>) -> AppResult<i32> {
     let mut result: i32 = 0;
     let mut shift = 0;
     loop {
-        let b = reader.read_u8()?;
+        let pos = reader.position();
+        let b = reader.read_u8().map_err(|_| truncated(pos, "7-bit encoded length"))?;
+        if shift > 28 {
+            return Err(parse_error(pos, "end of 7-bit encoded length", Some(b)));
+        }
         result |= ((b & 0x7F) as i32) << shift;
         shift += 7;
         if b & 0x80 == 0 {
@@ -149,13 +252,29 @@ fn write_value<W: Write>(writer: &mut W, value: &LuaValue) -> io::Result<()> {
     Ok(())
 }
 
-fn write_table<W: Write>(writer: &mut W, data: &HashMap<String, LuaValue>) -> io::Result<()> {
-    writer.write_all(&[0u8; 4])?; // 4-byte padding
-    writer.write_i32::<LittleEndian>(data.len() as i32)?;
+fn write_table<W: Write>(writer: &mut W, table: &LuaTable) -> io::Result<()> {
+    writer.write_i32::<LittleEndian>(table.array.len() as i32)?;
+    for value in &table.array {
+        write_value(writer, value)?;
+    }
+
+    writer.write_i32::<LittleEndian>(table.entries.len() as i32)?;
 
-    for (key, value) in data {
-        writer.write_u8(0x53)?;
-        write_string(writer, key)?;
+    for (key, value) in &table.entries {
+        match key {
+            LuaKey::String(s) => {
+                writer.write_u8(0x53)?;
+                write_string(writer, s)?;
+            }
+            LuaKey::Number(n) => {
+                writer.write_u8(0x4E)?;
+                writer.write_f64::<LittleEndian>(*n)?;
+            }
+            LuaKey::Boolean(b) => {
+                writer.write_u8(0x42)?;
+                writer.write_u8(if *b { 1 } else { 0 })?;
+            }
+        }
         write_value(writer, value)?;
     }
 
@@ -179,45 +298,206 @@ fn write_7bit_encoded_int<W: Write>(writer: &mut W, value: i32) -> io::Result<()
     Ok(())
 }
 
-/// Flatten a nested LuaValue table into dotted-key map
-pub fn flatten_lua(data: &HashMap<String, LuaValue>, prefix: &str) -> HashMap<String, LuaValue> {
+/// Flatten every top-level table into a dotted-key map.
+/// Later top-level tables win when the same key appears twice. Array parts
+/// (the conversation list) are records, not variables, and are left out.
+pub fn flatten_lua(data: &LuaDatabase) -> HashMap<String, LuaValue> {
     let mut result = HashMap::new();
-    for (key, value) in data {
+    for table in data.tables() {
+        flatten_table(table, "", &mut result);
+    }
+    result
+}
+
+fn flatten_table(table: &LuaTable, prefix: &str, result: &mut HashMap<String, LuaValue>) {
+    for (key, value) in &table.entries {
+        let key = key.to_display_string();
         let full_key = if prefix.is_empty() {
-            key.clone()
+            key
         } else {
             format!("{}.{}", prefix, key)
         };
 
         match value {
-            LuaValue::Table(nested) => {
-                for (nk, nv) in flatten_lua(nested, &full_key) {
-                    result.insert(nk, nv);
-                }
-            }
+            LuaValue::Table(nested) => flatten_table(nested, &full_key, result),
             _ => {
                 result.insert(full_key, value.clone());
             }
         }
     }
-    result
 }
 
-/// Set a dotted-key value in a nested LuaValue table
-pub fn set_lua_value(data: &mut HashMap<String, LuaValue>, dotted_key: &str, value: LuaValue) {
-    let parts: Vec<&str> = dotted_key.split('.').collect();
-    let mut current = data;
+/// Set a dotted-key value in the database.
+///
+/// Variable names themselves contain dots (`reputation.communist` is a single
+/// key in the game's variable table), so at each level the longest key that
+/// already exists wins. A key that exists nowhere is added, under its
+/// remaining dotted name, to the top-level table holding its siblings (keys
+/// with the same `area.` prefix), or else the largest one: the variable table.
+pub fn set_lua_value(data: &mut LuaDatabase, dotted_key: &str, value: LuaValue) {
+    let mut tables: Vec<&mut LuaTable> = data.tables_mut().collect();
+    for table in tables.iter_mut().rev() {
+        if let Some(path) = find_path(table, dotted_key) {
+            *value_at_path(table, &path) = value;
+            return;
+        }
+    }
+
+    let prefix = dotted_key.split_once('.').map(|(area, _)| format!("{}.", area));
+    let has_siblings = |table: &LuaTable| match &prefix {
+        Some(prefix) => table.entries.iter().any(|(k, _)| matches!(k, LuaKey::String(s) if s.starts_with(prefix.as_str()))),
+        None => false,
+    };
+    let home = match tables.iter().position(|t| has_siblings(t)) {
+        Some(i) => Some(tables.swap_remove(i)),
+        None => tables.into_iter().max_by_key(|t| t.entries.len()),
+    };
+
+    match home {
+        Some(table) => insert_into_deepest(table, dotted_key, value),
+        None => {
+            let mut table = LuaTable::default();
+            table.insert(dotted_key, value);
+            data.chunks.push(LuaChunk::Value(LuaValue::Table(table)));
+        }
+    }
+}
+
+/// Resolve `dotted_key` to the chain of keys leading to an existing value.
+fn find_path<'k>(table: &LuaTable, dotted_key: &'k str) -> Option<Vec<&'k str>> {
+    let splits = std::iter::once(dotted_key.len())
+        .chain(dotted_key.rmatch_indices('.').map(|(i, _)| i));
 
-    for i in 0..parts.len() - 1 {
-        let part = parts[i].to_string();
-        if !current.contains_key(&part) || !matches!(current.get(&part), Some(LuaValue::Table(_))) {
-            current.insert(part.clone(), LuaValue::Table(HashMap::new()));
+    for split in splits {
+        let (head, rest) = dotted_key.split_at(split);
+        let Some(value) = table.get(head) else { continue };
+        if rest.is_empty() {
+            return Some(vec![head]);
+        }
+        if let LuaValue::Table(nested) = value {
+            if let Some(mut path) = find_path(nested, &rest[1..]) {
+                path.insert(0, head);
+                return Some(path);
+            }
         }
-        current = match current.get_mut(&part) {
-            Some(LuaValue::Table(t)) => t,
+    }
+    None
+}
+
+fn value_at_path<'a>(table: &'a mut LuaTable, path: &[&str]) -> &'a mut LuaValue {
+    let (last, parents) = path.split_last().expect("lua path is never empty");
+    let mut current = table;
+    for key in parents {
+        current = match current.get_mut(key) {
+            Some(LuaValue::Table(nested)) => nested,
             _ => unreachable!(),
         };
     }
+    current.get_mut(last).expect("path was resolved by find_path")
+}
+
+/// Walk down through existing nested tables that prefix `dotted_key`, then
+/// insert whatever remains of the key as a single entry.
+fn insert_into_deepest(table: &mut LuaTable, dotted_key: &str, value: LuaValue) {
+    for (split, _) in dotted_key.rmatch_indices('.') {
+        let (head, rest) = dotted_key.split_at(split);
+        if let Some(LuaValue::Table(nested)) = table.get_mut(head) {
+            insert_into_deepest(nested, &rest[1..], value);
+            return;
+        }
+    }
+    table.insert(dotted_key, value);
+}
+
+/// Parse user-entered text as a value of the same type as `current`.
+/// Text that doesn't fit the type is rejected rather than coerced.
+pub fn parse_value_like(current: &LuaValue, key: &str, text: &str) -> AppResult<LuaValue> {
+    let invalid = |message: String| AppError::InvalidValue { key: key.to_string(), message };
+    match current {
+        LuaValue::String(_) => Ok(LuaValue::String(text.to_string())),
+        LuaValue::Number(_) => match text.trim().parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(LuaValue::Number(n)),
+            _ => Err(invalid(format!("expected a number, got {:?}", text))),
+        },
+        LuaValue::Boolean(_) => match text.trim().to_ascii_lowercase().as_str() {
+            "true" => Ok(LuaValue::Boolean(true)),
+            "false" => Ok(LuaValue::Boolean(false)),
+            _ => Err(invalid(format!("expected true or false, got {:?}", text))),
+        },
+        LuaValue::Table(_) => Err(invalid("tables can't be set directly".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// A hand-built database: array part, string, number and boolean keys
+    /// in no particular order, a -0.0 key, a string with a two-byte length,
+    /// NUL filler between top-level values and NUL padding at the end.
+    const FIXTURE: &[u8] = include_bytes!("../tests/fixtures/roundtrip.ntwtf.lua");
+
+    fn top_table(db: &LuaDatabase) -> &LuaTable {
+        match db.chunks.first() {
+            Some(LuaChunk::Value(LuaValue::Table(table))) => table,
+            other => panic!("expected a table first, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fixture_round_trips_byte_for_byte() {
+        let db = parse_lua_data(FIXTURE).unwrap();
+        assert_eq!(serialize_lua_database(&db).unwrap(), FIXTURE);
+    }
+
+    #[test]
+    fn fixture_keeps_key_order_and_types() {
+        let db = parse_lua_data(FIXTURE).unwrap();
+        let keys: Vec<String> = top_table(&db).entries.iter()
+            .map(|(key, _)| match key {
+                LuaKey::String(s) => format!("S:{}", s),
+                LuaKey::Number(n) => format!("N:{:016x}", n.to_bits()),
+                LuaKey::Boolean(b) => format!("B:{}", b),
+            })
+            .collect();
+        assert_eq!(keys, [
+            "S:Whirling.door_opens".to_string(),
+            "S:nested".to_string(),
+            "S:long".to_string(),
+            format!("N:{:016x}", 3.0f64.to_bits()),
+            format!("N:{:016x}", 0.5f64.to_bits()),
+            format!("N:{:016x}", (-0.0f64).to_bits()),
+            "B:true".to_string(),
+            "S:SimStatus".to_string(),
+        ]);
+        let nested = top_table(&db).entries.iter().find_map(|(key, value)| match (key, value) {
+            (LuaKey::String(s), LuaValue::Table(t)) if s == "nested" => Some(t),
+            _ => None,
+        }).unwrap();
+        let nested: Vec<String> = nested.entries.iter().map(|(key, _)| key.to_display_string()).collect();
+        assert_eq!(nested, ["zeta", "alpha"]);
+    }
 
-    current.insert(parts.last().unwrap().to_string(), value);
+    #[test]
+    fn fixture_keeps_nul_padding() {
+        let db = parse_lua_data(FIXTURE).unwrap();
+        assert_eq!(db.chunks.len(), 4);
+        assert!(matches!(&db.chunks[1], LuaChunk::Raw(bytes) if bytes == &[0; 2]));
+        assert!(matches!(&db.chunks[2], LuaChunk::Value(LuaValue::String(s)) if s == "tail"));
+        assert!(matches!(&db.chunks[3], LuaChunk::Raw(bytes) if bytes == &[0; 4]));
+    }
+
+    #[test]
+    fn example_save_round_trips_byte_for_byte() {
+        let zip_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../example/MARTINAISE, DAY 1, 15-24(2_6_2026 4-25-35 PM).ntwtf.zip");
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(zip_path).unwrap()).unwrap();
+        let name = archive.file_names().find(|n| n.ends_with(".ntwtf.lua")).unwrap().to_string();
+        let mut data = Vec::new();
+        archive.by_name(&name).unwrap().read_to_end(&mut data).unwrap();
+
+        let db = parse_lua_data(&data).unwrap();
+        assert_eq!(serialize_lua_database(&db).unwrap(), data);
+    }
 }
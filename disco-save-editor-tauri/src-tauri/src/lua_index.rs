@@ -0,0 +1,95 @@
+use regex::RegexBuilder;
+use crate::error::{AppError, AppResult};
+use crate::game_data::GameDataService;
+use crate::lua_database;
+use crate::models::*;
+
+/// Search index over a save's flattened Lua variables, built once per load
+/// and kept sorted by key so pages are stable.
+#[derive(Debug, Default)]
+pub struct LuaIndex {
+    entries: Vec<IndexEntry>,
+}
+
+#[derive(Debug)]
+struct IndexEntry {
+    display: LuaVariableDisplay,
+    key_lower: String,
+    description_lower: String,
+}
+
+impl LuaIndex {
+    pub fn build(lua_db: &LuaDatabase, game_data: &GameDataService) -> LuaIndex {
+        let mut entries: Vec<IndexEntry> = lua_database::flatten_lua(lua_db).into_iter()
+            .map(|(key, value)| {
+                let display = variable_display(key, &value, game_data);
+                IndexEntry {
+                    key_lower: display.key.to_lowercase(),
+                    description_lower: display.description.to_lowercase(),
+                    display,
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| a.display.key.cmp(&b.display.key));
+        LuaIndex { entries }
+    }
+
+    pub fn search(&self, search: &LuaSearchQuery) -> AppResult<LuaSearchPage> {
+        let query = search.query.to_lowercase();
+        let regex = match search.mode {
+            MatchMode::Regex if !query.is_empty() => Some(
+                RegexBuilder::new(&search.query)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| AppError::InvalidValue { key: "query".to_string(), message: e.to_string() })?,
+            ),
+            _ => None,
+        };
+
+        let text_matches = |text: &str| match search.mode {
+            _ if query.is_empty() => true,
+            MatchMode::Substring => text.contains(&query),
+            MatchMode::Prefix => text.starts_with(&query),
+            MatchMode::Regex => regex.as_ref().is_some_and(|r| r.is_match(text)),
+        };
+
+        let matching: Vec<&LuaVariableDisplay> = self.entries.iter()
+            .filter(|e| search.var_type.as_deref().map_or(true, |t| e.display.var_type.eq_ignore_ascii_case(t)))
+            .filter(|e| search.category.is_none() || e.display.category == search.category)
+            .filter(|e| !search.changed_only || e.display.differs_from_initial)
+            .filter(|e| text_matches(&e.key_lower) || text_matches(&e.description_lower))
+            .map(|e| &e.display)
+            .collect();
+
+        Ok(LuaSearchPage {
+            total: matching.len(),
+            offset: search.offset,
+            variables: matching.into_iter().skip(search.offset).take(search.limit).cloned().collect(),
+        })
+    }
+}
+
+/// A variable with its game data description, category and default.
+pub fn variable_display(key: String, value: &LuaValue, game_data: &GameDataService) -> LuaVariableDisplay {
+    let initial = game_data.initial_lua_value(&key);
+    LuaVariableDisplay {
+        value: value.to_display_string(),
+        var_type: value.type_name().to_string(),
+        description: game_data.all_variables.get(&key)
+            .map(|var| var.description.clone())
+            .unwrap_or_default(),
+        category: game_data.variable_category(&key),
+        differs_from_initial: initial.as_ref().is_some_and(|initial| !same_value(initial, value)),
+        initial_value: initial.map(|initial| initial.to_display_string()),
+        key,
+    }
+}
+
+fn same_value(a: &LuaValue, b: &LuaValue) -> bool {
+    match (a, b) {
+        (LuaValue::String(a), LuaValue::String(b)) => a == b,
+        (LuaValue::Number(a), LuaValue::Number(b)) => a == b,
+        (LuaValue::Boolean(a), LuaValue::Boolean(b)) => a == b,
+        _ => false,
+    }
+}
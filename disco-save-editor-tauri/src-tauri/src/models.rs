@@ -51,6 +51,9 @@ pub struct GameItem {
     pub is_consumable: String,
     #[serde(default, alias = "multipleAllowed")]
     pub multiple_allowed: String,
+    /// The item this one is kept in, like a key on the key ring.
+    #[serde(default, alias = "stackName")]
+    pub stack_name: Option<String>,
 }
 
 impl GameItem {
@@ -197,7 +200,7 @@ pub struct SaveData {
     pub base_name: String,
     pub first: FirstFile,
     pub second: SecondFile,
-    pub lua_database: HashMap<String, LuaValue>,
+    pub lua_database: LuaDatabase,
     pub states: StatesData,
 }
 
@@ -207,7 +210,7 @@ pub enum LuaValue {
     String(String),
     Number(f64),
     Boolean(bool),
-    Table(HashMap<String, LuaValue>),
+    Table(LuaTable),
 }
 
 impl Default for LuaValue {
@@ -238,7 +241,7 @@ impl LuaValue {
         }
     }
     #[allow(dead_code)]
-    pub fn as_table(&self) -> Option<&HashMap<String, LuaValue>> {
+    pub fn as_table(&self) -> Option<&LuaTable> {
         match self {
             LuaValue::Table(t) => Some(t),
             _ => None,
@@ -262,16 +265,224 @@ impl LuaValue {
     }
 }
 
+/// Key of a Lua table entry, kept with its original type tag so it is
+/// written back exactly as it was read.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LuaKey {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+impl LuaKey {
+    pub fn to_display_string(&self) -> String {
+        match self {
+            LuaKey::String(s) => s.clone(),
+            LuaKey::Number(n) => n.to_string(),
+            LuaKey::Boolean(b) => b.to_string(),
+        }
+    }
+
+    /// True if this key's display form equals `key`.
+    pub fn matches(&self, key: &str) -> bool {
+        match self {
+            LuaKey::String(s) => s == key,
+            _ => self.to_display_string() == key,
+        }
+    }
+}
+
+/// Ordered Lua table as stored in the file: the array part (implicit keys
+/// 1..=n) followed by the hash part, whose entries keep their file order and
+/// key types.
+#[derive(Debug, Clone, Default)]
+pub struct LuaTable {
+    pub array: Vec<LuaValue>,
+    pub entries: Vec<(LuaKey, LuaValue)>,
+}
+
+impl LuaTable {
+    /// Array part first (as number keys), then the hash part.
+    pub fn iter(&self) -> impl Iterator<Item = (LuaKey, &LuaValue)> {
+        self.array.iter().enumerate()
+            .map(|(i, v)| (LuaKey::Number((i + 1) as f64), v))
+            .chain(self.entries.iter().map(|(k, v)| (k.clone(), v)))
+    }
+    pub fn get(&self, key: &str) -> Option<&LuaValue> {
+        match self.entries.iter().find(|(k, _)| k.matches(key)) {
+            Some((_, v)) => Some(v),
+            None => self.array_index(key).map(|i| &self.array[i]),
+        }
+    }
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut LuaValue> {
+        if let Some(index) = self.entries.iter().position(|(k, _)| k.matches(key)) {
+            return Some(&mut self.entries[index].1);
+        }
+        self.array_index(key).map(|i| &mut self.array[i])
+    }
+    /// Replace the value of an existing key in place, or append a new string key.
+    pub fn insert(&mut self, key: &str, value: LuaValue) {
+        match self.get_mut(key) {
+            Some(existing) => *existing = value,
+            None => self.entries.push((LuaKey::String(key.to_string()), value)),
+        }
+    }
+    /// Index into the array part for a key like "3".
+    pub fn array_index(&self, key: &str) -> Option<usize> {
+        key.parse::<usize>().ok()
+            .filter(|n| (1..=self.array.len()).contains(n))
+            .map(|n| n - 1)
+    }
+}
+
+impl Serialize for LuaTable {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.array.len() + self.entries.len()))?;
+        for (k, v) in self.iter() {
+            map.serialize_entry(&k.to_display_string(), v)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for LuaTable {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TableVisitor;
+        impl<'de> serde::de::Visitor<'de> for TableVisitor {
+            type Value = LuaTable;
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a map of Lua values")
+            }
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, mut access: A) -> Result<LuaTable, A::Error> {
+                let mut table = LuaTable::default();
+                while let Some((k, v)) = access.next_entry::<String, LuaValue>()? {
+                    table.entries.push((LuaKey::String(k), v));
+                }
+                Ok(table)
+            }
+        }
+        deserializer.deserialize_map(TableVisitor)
+    }
+}
+
+/// One top-level piece of a .ntwtf.lua file. NUL filler between values is
+/// kept as `Raw` so the file is reproduced byte for byte.
+#[derive(Debug, Clone)]
+pub enum LuaChunk {
+    Value(LuaValue),
+    Raw(Vec<u8>),
+}
+
+/// A whole .ntwtf.lua file: its top-level chunks in file order.
+#[derive(Debug, Clone, Default)]
+pub struct LuaDatabase {
+    pub chunks: Vec<LuaChunk>,
+}
+
+impl LuaDatabase {
+    pub fn tables(&self) -> impl Iterator<Item = &LuaTable> {
+        self.chunks.iter().filter_map(|c| match c {
+            LuaChunk::Value(LuaValue::Table(t)) => Some(t),
+            _ => None,
+        })
+    }
+    pub fn tables_mut(&mut self) -> impl Iterator<Item = &mut LuaTable> {
+        self.chunks.iter_mut().filter_map(|c| match c {
+            LuaChunk::Value(LuaValue::Table(t)) => Some(t),
+            _ => None,
+        })
+    }
+}
+
+impl Serialize for LuaDatabase {
+    /// Serializes as a single map; later top-level tables win on key clashes,
+    /// matching how the game merges them.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut merged = LuaTable::default();
+        for table in self.tables() {
+            for (k, v) in table.iter() {
+                merged.insert(&k.to_display_string(), v.clone());
+            }
+        }
+        merged.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LuaDatabase {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = LuaTable::deserialize(deserializer)?;
+        Ok(LuaDatabase { chunks: vec![LuaChunk::Value(LuaValue::Table(table))] })
+    }
+}
+
+/// Half-open byte range `[start, end)` within a file.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// What a salvage parse of the Lua database had to throw away.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LuaSalvageReport {
+    pub skipped: Vec<ByteRange>,
+    pub first_error: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FirstFile {
     #[serde(default, alias = "areaId")]
     pub area_id: String,
     #[serde(default, alias = "partyState")]
     pub party_state: PartyState,
+    /// Unrevealer id (`fog-whirling-kitchen`) -> `UNSEEN` / `ACTIVE` / `DONE`
     #[serde(default, alias = "fowUnrevealersStatusCache")]
     pub fow_unrevealers_status_cache: HashMap<String, String>,
 }
 
+/// Status of one fog unrevealer in `fowUnrevealersStatusCache`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FogStatus {
+    Unseen,
+    Active,
+    Done,
+}
+
+impl FogStatus {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "UNSEEN" => Some(FogStatus::Unseen),
+            "ACTIVE" => Some(FogStatus::Active),
+            "DONE" => Some(FogStatus::Done),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FogStatus::Unseen => "UNSEEN",
+            FogStatus::Active => "ACTIVE",
+            FogStatus::Done => "DONE",
+        }
+    }
+}
+
+/// The `.FOW.json` file. The game writes `{}` until fog has been revealed,
+/// and its filled-in layout isn't known for certain. The editor assumes one
+/// entry per area, keyed by the area name the fog unrevealers use
+/// (`whirling` for `fog-whirling-kitchen`) and holding an object. Files
+/// that aren't an object fail to load; entries are kept exactly as read, and
+/// `fog::hide_area` refuses to edit a file that doesn't match the layout.
+/// The editor only ever drops entries from it: revealing fog changes the 1st
+/// file's unrevealer statuses, never this file.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(transparent)]
+pub struct FowFile {
+    pub entries: serde_json::Map<String, serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PartyState {
     #[serde(default, alias = "isKimInParty")]
@@ -400,7 +611,7 @@ pub struct ModifierEntry {
     #[serde(default, alias = "skillType")]
     pub skill_type: String,
     #[serde(default, alias = "modifierCause")]
-    pub modifier_cause: ModifierCause,
+    pub modifier_cause: Option<ModifierCause>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -429,7 +640,7 @@ pub struct SunshineClockTimeHolder {
     pub time_override: serde_json::Value,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct GameTimestamp {
     #[serde(default, alias = "dayCounter")]
     pub day_counter: i64,
@@ -560,6 +771,8 @@ pub struct WhiteCheck {
     pub is_only_seen: bool,
     #[serde(default, alias = "checkTargetArticyId")]
     pub check_target_articy_id: String,
+    #[serde(default, alias = "Actor")]
+    pub actor: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -671,6 +884,12 @@ pub struct ContainerItem {
     pub calculated_value: i64,
     #[serde(default, alias = "bonusLoot")]
     pub bonus_loot: bool,
+    /// Set when another container's entry refers to this one.
+    #[serde(default, rename = "$id")]
+    pub ref_id: Option<String>,
+    /// Set when this entry is only a reference to another container's item.
+    #[serde(default, rename = "$ref")]
+    pub ref_target: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -693,7 +912,108 @@ pub struct StatesData {
 pub struct SaveSummary {
     pub name: String,
     pub path: String,
+    /// RFC 3339, UTC
     pub last_modified: String,
+    pub is_zip: bool,
+    pub snapshot_count: usize,
+    /// None when the save's JSON files couldn't be read
+    pub details: Option<SaveDetails>,
+}
+
+/// In-game state shown on a save card, read without loading the whole save.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveDetails {
+    pub area_id: String,
+    pub day: i64,
+    pub hours: i64,
+    pub minutes: i64,
+    pub level: i64,
+    pub xp_amount: i64,
+    pub money: i64,
+}
+
+/// One stored copy of a save.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    /// RFC 3339, UTC
+    pub created_at: String,
+    /// SHA-256 of the save's files (zip entries uncompressed)
+    pub hash: String,
+    pub label: Option<String>,
+    pub save_path: String,
+    pub size: u64,
+    pub details: Option<SaveDetails>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub name: String,
+    pub change: FileChangeKind,
+}
+
+/// Files that differ between a snapshot and a later snapshot or the save on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub from: SnapshotInfo,
+    /// None when comparing against the save as it is now
+    pub to: Option<SnapshotInfo>,
+    /// Details of `to`, or of the save on disk
+    pub to_details: Option<SaveDetails>,
+    pub files: Vec<FileChange>,
+}
+
+/// One value that differs between two saves. `before`/`after` is None
+/// when the value only exists in the other save.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueChange {
+    /// JSON pointer for the JSON files, dotted key for Lua and states
+    pub key: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    /// Game data description of a Lua variable
+    pub label: Option<String>,
+}
+
+/// Everything that differs between save `a` and save `b`, by file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveDiff {
+    pub a: String,
+    pub b: String,
+    pub first: Vec<ValueChange>,
+    pub second: Vec<ValueChange>,
+    pub lua: Vec<ValueChange>,
+    pub states: Vec<ValueChange>,
+}
+
+/// The in-game clock. The day and the real day are kept separately, as
+/// `dayCounter` and `realDayCounter`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameClock {
+    pub day: i64,
+    pub real_day: i64,
+    pub hours: i64,
+    pub minutes: i64,
+    pub seconds: i64,
+    /// Time shown instead of the clock during scripted scenes
+    pub time_override: Option<GameTimestamp>,
+}
+
+/// Everything that changes when game time passes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeAdvance {
+    pub clock: GameClock,
+    pub thoughts: Vec<ThoughtDisplay>,
+    pub owned_items: Vec<InventoryItemDisplay>,
+    pub party_state: PartyState,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -707,9 +1027,7 @@ pub struct FullSaveState {
     pub money: i64,
     pub health: i64,
     pub morale: i64,
-    pub day: i64,
-    pub hours: i64,
-    pub minutes: i64,
+    pub clock: GameClock,
     pub abilities: Vec<AbilityDisplay>,
     pub skills: Vec<SkillDisplay>,
     // Inventory
@@ -717,8 +1035,9 @@ pub struct FullSaveState {
     pub bullets: i64,
     // Thoughts
     pub thoughts: Vec<ThoughtDisplay>,
+    pub thought_cabinet: ThoughtCabinetDisplay,
     // Journal
-    pub tasks: Vec<TaskDisplay>,
+    pub journal: JournalState,
     // Party
     pub area_id: String,
     pub party_state: PartyState,
@@ -730,14 +1049,16 @@ pub struct FullSaveState {
     pub reputation: ReputationDisplay,
     pub lua_variable_count: usize,
     // White Checks
-    pub failed_checks: Vec<WhiteCheckDisplay>,
-    pub seen_checks: Vec<WhiteCheckDisplay>,
+    pub white_checks: WhiteChecksState,
     // Containers
     pub containers: Vec<ContainerDisplay>,
     // States
-    pub door_states: HashMap<String, bool>,
+    pub doors: Vec<DoorDisplay>,
     pub area_states: HashMap<String, i64>,
     pub shown_orbs: HashMap<String, i64>,
+    pub fog: FogState,
+    // Set when the Lua database had to be salvaged
+    pub lua_salvage: Option<LuaSalvageReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -750,6 +1071,52 @@ pub struct AbilityDisplay {
     pub is_signature: bool,
 }
 
+/// An ability's or skill's derived numbers and the modifiers behind them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatBreakdown {
+    pub save_key: String,
+    pub type_code: String,
+    pub display_name: String,
+    /// Initial dice for an ability, the parent ability's value for a skill.
+    pub base: i64,
+    pub rank_value: i64,
+    pub damage_value: i64,
+    pub value: i64,
+    pub maximum_value: i64,
+    pub modifiers: Vec<ModifierEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CharacterStats {
+    pub abilities: Vec<StatBreakdown>,
+    pub skills: Vec<StatBreakdown>,
+}
+
+/// A modifier with its cause resolved to a display name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifierDisplay {
+    pub modifier_type: String,
+    pub amount: i64,
+    pub explanation: String,
+    pub cause_key: Option<String>,
+    pub cause_type: Option<String>,
+    /// The item, thought or ability name, or the key if it isn't known.
+    pub cause_name: Option<String>,
+}
+
+/// Why a skill shows the number it does, as the save has it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillBreakdown {
+    pub skill_type: String,
+    pub display_name: String,
+    pub calculated_ability: i64,
+    pub rank_value: i64,
+    pub modifiers: Vec<ModifierDisplay>,
+    pub modifier_sum: i64,
+    /// Ability plus rank plus modifiers.
+    pub total: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkillDisplay {
     pub save_key: String,
@@ -779,6 +1146,10 @@ pub struct InventoryItemDisplay {
     pub is_cursed: bool,
     pub is_substance: bool,
     pub substance_uses: i64,
+    pub substance_time_left: i64,
+    pub is_fresh: bool,
+    /// The owned item this one is stacked in, or empty.
+    pub stack_name: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -795,6 +1166,37 @@ pub struct ThoughtDisplay {
     pub is_cursed: bool,
     pub state: String,
     pub time_left: f64,
+    pub is_fresh: bool,
+}
+
+/// A thought cabinet slot as stored in `slotStates`: filled and empty slots
+/// are unlocked, buyable and locked ones are not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SlotStatus {
+    Filled,
+    Empty,
+    Buyable,
+    Locked,
+}
+
+impl SlotStatus {
+    pub fn is_unlocked(self) -> bool {
+        matches!(self, SlotStatus::Filled | SlotStatus::Empty)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CabinetSlot {
+    pub status: SlotStatus,
+    pub thought: Option<String>,
+}
+
+/// The cabinet's slots in save order, and the thought its view has open.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ThoughtCabinetDisplay {
+    pub slots: Vec<CabinetSlot>,
+    pub selected_project_name: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -802,9 +1204,29 @@ pub struct TaskDisplay {
     pub task_name: String,
     pub description: String,
     pub acquired_time: String,
+    pub acquired: GameTimestamp,
+    /// When the task was done or cancelled, None while it is active.
+    pub resolved: Option<GameTimestamp>,
     pub is_resolved: bool,
+    /// The task's `TaskNewStates` flag as stored. The game keeps it set while
+    /// the task is open and clears it on resolving.
     pub is_new: bool,
-    pub subtasks: Vec<String>,
+    pub subtasks: Vec<SubtaskDisplay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtaskDisplay {
+    pub name: String,
+    pub description: String,
+    pub acquired: GameTimestamp,
+}
+
+/// The journal of a save: its tasks and which were last active and done.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct JournalState {
+    pub tasks: Vec<TaskDisplay>,
+    pub last_active_task: String,
+    pub last_done_task: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -851,13 +1273,40 @@ pub struct WhiteCheckDisplay {
     pub last_target_value: i64,
     pub check_precondition: String,
     pub is_seen_only: bool,
+    pub actor_name: String,
+}
+
+/// Failed checks, failed checks reopened for another try, and checks only
+/// seen, each keyed by flag name.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WhiteChecksState {
+    pub failed: Vec<WhiteCheckDisplay>,
+    pub reopened: Vec<WhiteCheckDisplay>,
+    pub seen: Vec<WhiteCheckDisplay>,
+}
+
+/// A door from `DoorStates`, its open or unlocked variable, or both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoorDisplay {
+    /// The door's variable, or its `DoorStates` key if it has none
+    pub id: String,
+    pub name: String,
+    pub area: String,
+    pub state_key: Option<String>,
+    pub is_open_state: Option<bool>,
+    pub variable: Option<String>,
+    pub is_open_variable: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContainerDisplay {
     pub container_id: String,
+    /// The container's parent path, shared by the containers of one place.
+    pub area: String,
     pub item_count: usize,
     pub total_value: i64,
+    /// Loot that is also another container's; the editor leaves it alone.
+    pub is_shared: bool,
     pub items: Vec<ContainerItemDisplay>,
 }
 
@@ -877,6 +1326,85 @@ pub struct LuaVariableDisplay {
     pub value: String,
     pub var_type: String,
     pub description: String,
+    pub category: Option<VariableCategory>,
+    /// The value a new game starts with, None for variables not in the game data.
+    pub initial_value: Option<String>,
+    pub differs_from_initial: bool,
+}
+
+/// Which game data variable file a Lua variable comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableCategory {
+    Tasks,
+    Xp,
+    Reputation,
+    Character,
+    Locations,
+    Inventory,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    #[default]
+    Substring,
+    Prefix,
+    Regex,
+}
+
+/// A page request against the Lua variable index. The query matches keys and
+/// descriptions, case-insensitively.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LuaSearchQuery {
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub mode: MatchMode,
+    /// "String", "Number" or "Boolean"
+    #[serde(default)]
+    pub var_type: Option<String>,
+    #[serde(default)]
+    pub category: Option<VariableCategory>,
+    /// Only variables whose value differs from their `initial_value`.
+    #[serde(default)]
+    pub changed_only: bool,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// One page of search results, sorted by key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LuaSearchPage {
+    pub total: usize,
+    pub offset: usize,
+    pub variables: Vec<LuaVariableDisplay>,
+}
+
+/// Variables a playthrough has changed from their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableReport {
+    pub changed_count: usize,
+    pub groups: Vec<VariableGroup>,
+    /// Variables in the save that the game data doesn't define.
+    pub unknown: Vec<LuaVariableDisplay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableGroup {
+    pub prefix: String,
+    pub variables: Vec<LuaVariableDisplay>,
+}
+
+/// Fog of war of a save, from the 1st file and `.FOW.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FogState {
+    /// Unrevealer id -> status. Unrevealers with a status the editor
+    /// doesn't know are left out, and left alone on save.
+    pub unrevealers: std::collections::BTreeMap<String, FogStatus>,
+    /// None when the save has no `.FOW.json`
+    pub fow_file: Option<FowFile>,
 }
 
 // ─── Catalog item for adding items ───
@@ -0,0 +1,96 @@
+use std::collections::{BTreeMap, BTreeSet};
+use serde_json::Value;
+use crate::error::AppResult;
+use crate::game_data::GameDataService;
+use crate::lua_database::{self, ParseMode};
+use crate::models::*;
+use crate::save_service;
+
+/// Load two saves and report every value that differs between them.
+pub fn diff_saves(a: &str, b: &str, game_data: &GameDataService) -> AppResult<SaveDiff> {
+    let before = save_service::load_save(a, game_data, ParseMode::Strict)?;
+    let after = save_service::load_save(b, game_data, ParseMode::Strict)?;
+
+    let mut first = Vec::new();
+    diff_json("", &before.first_raw, &after.first_raw, &mut first);
+    let mut second = Vec::new();
+    diff_json("", &before.second_raw, &after.second_raw, &mut second);
+
+    let lua_before = lua_values(&before.lua_db);
+    let lua_after = lua_values(&after.lua_db);
+    let lua = diff_maps(&lua_before, &lua_after)
+        .into_iter()
+        .map(|mut change| {
+            change.label = variable_label(game_data, &change.key);
+            change
+        })
+        .collect();
+
+    let mut states = diff_maps(&states_values(&before.states.area_states), &states_values(&after.states.area_states));
+    states.iter_mut().for_each(|c| c.key = format!("area_states.{}", c.key));
+    let mut orbs = diff_maps(&states_values(&before.states.shown_orbs), &states_values(&after.states.shown_orbs));
+    orbs.iter_mut().for_each(|c| c.key = format!("shown_orbs.{}", c.key));
+    states.extend(orbs);
+
+    Ok(SaveDiff { a: a.to_string(), b: b.to_string(), first, second, lua, states })
+}
+
+/// Changes between two JSON values, keyed by JSON pointer.
+fn diff_json(pointer: &str, before: &Value, after: &Value, changes: &mut Vec<ValueChange>) {
+    match (before, after) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let child = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_json(&child, x, y, changes),
+                    (x, y) => changes.push(ValueChange::new(child, x.cloned(), y.cloned())),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child = format!("{}/{}", pointer, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(x), Some(y)) => diff_json(&child, x, y, changes),
+                    (x, y) => changes.push(ValueChange::new(child, x.cloned(), y.cloned())),
+                }
+            }
+        }
+        _ if before != after => changes.push(ValueChange::new(pointer.to_string(), Some(before.clone()), Some(after.clone()))),
+        _ => {}
+    }
+}
+
+fn diff_maps(before: &BTreeMap<String, Value>, after: &BTreeMap<String, Value>) -> Vec<ValueChange> {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .map(|key| ValueChange::new(key.clone(), before.get(key).cloned(), after.get(key).cloned()))
+        .collect()
+}
+
+fn lua_values(lua_db: &LuaDatabase) -> BTreeMap<String, Value> {
+    lua_database::flatten_lua(lua_db).into_iter()
+        .map(|(key, value)| (key, serde_json::to_value(value).unwrap_or(Value::Null)))
+        .collect()
+}
+
+fn states_values(states: &std::collections::HashMap<String, i64>) -> BTreeMap<String, Value> {
+    states.iter().map(|(key, value)| (key.clone(), Value::from(*value))).collect()
+}
+
+/// The game data description of a Lua variable, if it has one.
+fn variable_label(game_data: &GameDataService, key: &str) -> Option<String> {
+    [&game_data.all_variables, &game_data.task_variables].into_iter()
+        .filter_map(|vars| vars.get(key))
+        .map(|var| var.description.trim())
+        .find(|description| !description.is_empty())
+        .map(str::to_string)
+}
+
+impl ValueChange {
+    fn new(key: String, before: Option<Value>, after: Option<Value>) -> Self {
+        ValueChange { key, before, after, label: None }
+    }
+}
@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use crate::error::AppResult;
+use crate::models::SaveSummary;
+use crate::save_service;
+use crate::snapshots::SnapshotStore;
+
+/// Steam app id of Disco Elysium (and The Final Cut).
+const STEAM_APP_ID: &str = "632470";
+
+/// Company/product folders the game has shipped under, relative to Unity's
+/// per-user data folder (LocalLow on Windows, Application Support on macOS).
+const GAME_DIRS: [&str; 4] = [
+    "ZAUM Studio/Disco Elysium",
+    "ZA-UM/Disco Elysium",
+    "ZA-UM/Disco Elysium - The Final Cut",
+    "ZAUM Studio/Disco Elysium - The Final Cut",
+];
+
+/// Steam installs on Linux: native, Flatpak and Snap.
+const STEAM_ROOTS: [&str; 6] = [
+    ".steam/steam",
+    ".steam/root",
+    ".local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/data/Steam",
+    "snap/steam/common/.local/share/Steam",
+];
+
+/// Folders the game writes its saves to, followed by `extra_roots`.
+/// Duplicates (e.g. `~/.steam/steam` symlinked to `~/.local/share/Steam`) are dropped.
+pub fn save_roots(extra_roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut data_dirs = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        data_dirs.push(home.join("AppData/LocalLow"));
+        data_dirs.push(home.join("Library/Application Support"));
+        for library in steam_libraries(&home) {
+            data_dirs.push(
+                library.join("steamapps/compatdata").join(STEAM_APP_ID)
+                    .join("pfx/drive_c/users/steamuser/AppData/LocalLow"),
+            );
+        }
+    }
+
+    let mut roots: Vec<PathBuf> = data_dirs.iter()
+        .flat_map(|dir| GAME_DIRS.iter().map(move |game| dir.join(game).join("SaveGames")))
+        .collect();
+    roots.extend(extra_roots.iter().cloned());
+
+    let mut seen = Vec::new();
+    roots.retain(|root| {
+        let key = std::fs::canonicalize(root).unwrap_or_else(|_| root.clone());
+        if seen.contains(&key) {
+            false
+        } else {
+            seen.push(key);
+            true
+        }
+    });
+    roots
+}
+
+/// Every Steam library folder: each Steam install plus the libraries its
+/// `libraryfolders.vdf` lists.
+fn steam_libraries(home: &Path) -> Vec<PathBuf> {
+    let mut libraries = Vec::new();
+    for root in STEAM_ROOTS.iter().map(|r| home.join(r)).filter(|r| r.is_dir()) {
+        for vdf in ["steamapps/libraryfolders.vdf", "config/libraryfolders.vdf"] {
+            if let Ok(content) = std::fs::read_to_string(root.join(vdf)) {
+                libraries.extend(parse_library_folders(&content));
+            }
+        }
+        libraries.push(root);
+    }
+    libraries
+}
+
+/// Library paths from a `libraryfolders.vdf`, in both the current
+/// (`"path" "..."` inside numbered blocks) and the old (`"1" "..."`) format.
+fn parse_library_folders(vdf: &str) -> Vec<PathBuf> {
+    let re = Regex::new(r#"(?m)^\s*"(?:path|\d+)"\s+"((?:[^"\\]|\\.)*)"\s*$"#).unwrap();
+    re.captures_iter(vdf)
+        .map(|c| PathBuf::from(c[1].replace("\\\\", "\\")))
+        // Drops the `"appid" "size"` pairs of the "apps" blocks
+        .filter(|p| p.is_absolute())
+        .collect()
+}
+
+/// Find every save in the known save folders and `extra_roots`, newest first.
+pub fn discover_saves(extra_roots: &[PathBuf]) -> AppResult<Vec<SaveSummary>> {
+    let mut saves = Vec::new();
+    let snapshots = SnapshotStore::open().ok();
+
+    for path in save_roots(extra_roots) {
+        if let Ok(entries) = std::fs::read_dir(&path) {
+            for entry in entries.flatten() {
+                let entry_path = entry.path();
+                let name = entry_path.file_name()
+                    .unwrap_or_default().to_string_lossy().to_string();
+
+                let is_zip = !entry_path.is_dir();
+                let is_save = if is_zip {
+                    name.ends_with(".ntwtf.zip")
+                } else {
+                    name.ends_with(".ntwtf")
+                };
+
+                if is_save {
+                    let modified = entry.metadata().ok()
+                        .and_then(|m| m.modified().ok())
+                        .unwrap_or(std::time::UNIX_EPOCH);
+                    saves.push((modified, summarize(&entry_path, &name, is_zip, modified, snapshots.as_ref())));
+                }
+            }
+        }
+    }
+
+    // Sort by last modified descending
+    saves.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    Ok(saves.into_iter().map(|(_, summary)| summary).collect())
+}
+
+fn summarize(path: &Path, name: &str, is_zip: bool, modified: std::time::SystemTime, snapshots: Option<&SnapshotStore>) -> SaveSummary {
+    let path_str = path.to_string_lossy().to_string();
+    let snapshot_count = snapshots
+        .and_then(|store| store.list(&path_str).ok())
+        .map_or(0, |list| list.len());
+
+    SaveSummary {
+        name: save_service::save_base_name(name).to_string(),
+        details: save_service::read_save_details(&path_str).ok(),
+        path: path_str,
+        last_modified: chrono::DateTime::<chrono::Utc>::from(modified)
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        is_zip,
+        snapshot_count,
+    }
+}
@@ -1,91 +1,92 @@
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::io::{Read, Write};
+use crate::error::{AppError, AppResult, FileRole};
 use crate::models::*;
 use crate::game_data::GameDataService;
-use crate::lua_database;
+use crate::lua_database::{self, ParseMode};
 use crate::states_lua;
+use crate::containers;
+use crate::doors;
+use crate::fog;
+use crate::inventory;
+use crate::journal;
+use crate::skill_modifiers::{self, StatInputs};
+use crate::thought_cabinet;
+use crate::time;
+use crate::white_checks;
+use crate::json_format;
+use crate::atomic_write::{self, StagedFile};
+use crate::snapshots::SnapshotStore;
+
+/// Everything `load_save` reads from a save.
+pub struct LoadedSave {
+    pub first_raw: serde_json::Value,
+    pub second_raw: serde_json::Value,
+    pub character_sheet: CharacterSheet,
+    pub first: FirstFile,
+    pub second: SecondFile,
+    pub lua_db: LuaDatabase,
+    pub states: StatesData,
+    /// None when the save has no `.FOW.json`.
+    pub fow: Option<FowFile>,
+    /// Set when the Lua database was loaded in salvage mode.
+    pub lua_salvage: Option<LuaSalvageReport>,
+}
 
 /// Load a save from its .ntwtf folder or .zip file
-pub fn load_save(save_path: &str, game_data: &GameDataService) -> Result<(serde_json::Value, serde_json::Value, CharacterSheet, FirstFile, SecondFile, HashMap<String, LuaValue>, StatesData), String> {
+pub fn load_save(save_path: &str, game_data: &GameDataService, lua_mode: ParseMode) -> AppResult<LoadedSave> {
     let path = Path::new(save_path);
     let filename = path.file_name()
-        .ok_or("Invalid path")?
+        .ok_or_else(|| AppError::InvalidPath(save_path.to_string()))?
         .to_string_lossy();
 
-    let is_zip = filename.ends_with(".zip");
+    let base_name = save_base_name(&filename);
 
-    let base_name = if filename.ends_with(".ntwtf.zip") {
-        &filename[..filename.len() - 10]
-    } else if filename.ends_with(".ntwtf") {
-        &filename[..filename.len() - 6]
-    } else {
-        &filename
-    };
+    // Put back the originals of a folder save that was interrupted mid-write.
+    // Zips are replaced with a single rename and never need it.
+    if path.is_dir() {
+        atomic_write::recover(path, base_name)?;
+    }
 
-    let (first_content, second_content, lua_content, states_content) = if is_zip {
-        let file = std::fs::File::open(path).map_err(|e| format!("Failed to open zip: {}", e))?;
-        let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid zip archive: {}", e))?;
+    let first_name = format!("{}.1st.ntwtf.json", base_name);
+    let second_name = format!("{}.2nd.ntwtf.json", base_name);
+    let fow_name = format!("{}.FOW.json", base_name);
 
-        let read_entry = |name: String, archive: &mut zip::ZipArchive<std::fs::File>| -> Option<Vec<u8>> {
-            let mut file = archive.by_name(&name).ok()?;
-            let mut content = Vec::new();
-            file.read_to_end(&mut content).ok()?;
-            Some(content)
-        };
-
-        (
-            read_entry(format!("{}.1st.ntwtf.json", base_name), &mut archive),
-            read_entry(format!("{}.2nd.ntwtf.json", base_name), &mut archive),
-            read_entry(format!("{}.ntwtf.lua", base_name), &mut archive),
-            read_entry(format!("{}.states.lua", base_name), &mut archive),
-        )
-    } else {
-        let read_file = |p: std::path::PathBuf| -> Option<Vec<u8>> {
-            std::fs::read(p).ok()
-        };
-
-        (
-            read_file(path.join(format!("{}.1st.ntwtf.json", base_name))),
-            read_file(path.join(format!("{}.2nd.ntwtf.json", base_name))),
-            read_file(path.join(format!("{}.ntwtf.lua", base_name))),
-            read_file(path.join(format!("{}.states.lua", base_name))),
-        )
-    };
+    let mut contents = read_save_entries(path, &[
+        (first_name.as_str(), FileRole::First),
+        (second_name.as_str(), FileRole::Second),
+        (&format!("{}.ntwtf.lua", base_name), FileRole::LuaDatabase),
+        (&format!("{}.states.lua", base_name), FileRole::States),
+        (fow_name.as_str(), FileRole::Fow),
+    ])?.into_iter();
+    let (first_content, second_content, lua_content, states_content, fow_content) =
+        (contents.next().flatten(), contents.next().flatten(), contents.next().flatten(), contents.next().flatten(), contents.next().flatten());
 
     // Parse first file
-    let (first_raw, first): (serde_json::Value, FirstFile) = if let Some(content) = first_content {
-        let content_str = String::from_utf8_lossy(&content);
-        let raw: serde_json::Value = serde_json::from_str(&content_str)
-            .map_err(|e| format!("Failed to parse 1st file: {}", e))?;
-        let first: FirstFile = serde_json::from_str(&content_str)
-            .map_err(|e| format!("Failed to deserialize 1st file: {}", e))?;
-        (raw, first)
-    } else {
-        (serde_json::Value::Null, FirstFile::default())
-    };
+    let first_path = path.join(&first_name);
+    let first_content = first_content.ok_or_else(|| AppError::MissingFile {
+        role: FileRole::First,
+        path: first_path.display().to_string(),
+    })?;
+    let (first_raw, first): (serde_json::Value, FirstFile) = parse_json_file(&first_content, FileRole::First, &first_path)?;
 
     // Parse second file
-    let (second_raw, second): (serde_json::Value, SecondFile) = if let Some(content) = second_content {
-        let content_str = String::from_utf8_lossy(&content);
-        let raw: serde_json::Value = serde_json::from_str(&content_str)
-            .map_err(|e| format!("Failed to parse 2nd file: {}", e))?;
-        let second: SecondFile = serde_json::from_str(&content_str)
-            .map_err(|e| format!("Failed to deserialize 2nd file: {}", e))?;
-        (raw, second)
-    } else {
-        (serde_json::Value::Null, SecondFile::default())
-    };
+    let second_path = path.join(&second_name);
+    let second_content = second_content.ok_or_else(|| AppError::MissingFile {
+        role: FileRole::Second,
+        path: second_path.display().to_string(),
+    })?;
+    let (second_raw, second): (serde_json::Value, SecondFile) = parse_json_file(&second_content, FileRole::Second, &second_path)?;
 
     // Parse character sheet from raw JSON
     let character_sheet = parse_character_sheet(&second.character_sheet_raw, game_data);
 
     // Parse lua database
-    let lua_db = if let Some(content) = lua_content {
-        lua_database::parse_lua_data(&content)
-            .map_err(|e| format!("Failed to parse lua database: {}", e))?
+    let (lua_db, lua_salvage) = if let Some(content) = lua_content {
+        lua_database::parse_with_mode(&content, lua_mode)?
     } else {
-        HashMap::new()
+        (LuaDatabase::default(), None)
     };
 
     // Parse states
@@ -96,7 +97,125 @@ pub fn load_save(save_path: &str, game_data: &GameDataService) -> Result<(serde_
         StatesData::default()
     };
 
-    Ok((first_raw, second_raw, character_sheet, first, second, lua_db, states))
+    // Parse fog of war
+    let fow = match fow_content {
+        Some(content) => {
+            let fow_path = path.join(&fow_name);
+            Some(serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(&content))
+                .map_err(|e| AppError::json(FileRole::Fow, &fow_path, e))?)
+        }
+        None => None,
+    };
+
+    Ok(LoadedSave { first_raw, second_raw, character_sheet, first, second, lua_db, states, fow, lua_salvage })
+}
+
+/// Read the named files from a save folder or zip, `None` for any that are missing.
+fn read_save_entries(path: &Path, names: &[(&str, FileRole)]) -> AppResult<Vec<Option<Vec<u8>>>> {
+    if path.to_string_lossy().ends_with(".zip") {
+        let file = std::fs::File::open(path).map_err(|e| AppError::io(FileRole::Archive, path, e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| AppError::zip(path, e))?;
+
+        names.iter().map(|(name, _)| {
+            let mut file = match archive.by_name(name) {
+                Ok(file) => file,
+                Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+                Err(e) => return Err(AppError::zip(path, e)),
+            };
+            let mut content = Vec::new();
+            file.read_to_end(&mut content).map_err(|e| AppError::io(FileRole::Archive, path.join(name), e))?;
+            Ok(Some(content))
+        }).collect()
+    } else {
+        names.iter().map(|(name, role)| {
+            let p = path.join(name);
+            match std::fs::read(&p) {
+                Ok(content) => Ok(Some(content)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(AppError::io(*role, &p, e)),
+            }
+        }).collect()
+    }
+}
+
+/// The few 2nd file fields a save summary needs.
+#[derive(serde::Deserialize, Default)]
+struct SecondFileHeader {
+    #[serde(default, alias = "sunshineClockTimeHolder")]
+    sunshine_clock_time_holder: SunshineClockTimeHolder,
+    #[serde(default, alias = "playerCharacter", alias = "PlayerCharacter")]
+    player_character: PlayerCharacter,
+}
+
+/// Read the in-game details shown in the save picker. Only the 1st and 2nd
+/// JSON files are read; the Lua database and states are skipped.
+pub fn read_save_details(save_path: &str) -> AppResult<SaveDetails> {
+    let path = Path::new(save_path);
+    let filename = path.file_name()
+        .ok_or_else(|| AppError::InvalidPath(save_path.to_string()))?
+        .to_string_lossy();
+    let base_name = save_base_name(&filename);
+
+    let first_name = format!("{}.1st.ntwtf.json", base_name);
+    let second_name = format!("{}.2nd.ntwtf.json", base_name);
+    let mut contents = read_save_entries(path, &[
+        (first_name.as_str(), FileRole::First),
+        (second_name.as_str(), FileRole::Second),
+    ])?.into_iter();
+
+    let first_path = path.join(&first_name);
+    let first_content = contents.next().flatten().ok_or_else(|| AppError::MissingFile {
+        role: FileRole::First,
+        path: first_path.display().to_string(),
+    })?;
+    let second_path = path.join(&second_name);
+    let second_content = contents.next().flatten().ok_or_else(|| AppError::MissingFile {
+        role: FileRole::Second,
+        path: second_path.display().to_string(),
+    })?;
+
+    let first: FirstFile = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(&first_content))
+        .map_err(|e| AppError::json(FileRole::First, &first_path, e))?;
+    let second: SecondFileHeader = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(&second_content))
+        .map_err(|e| AppError::json(FileRole::Second, &second_path, e))?;
+
+    let time = &second.sunshine_clock_time_holder.time;
+    let pc = &second.player_character;
+    Ok(SaveDetails {
+        area_id: first.area_id,
+        day: time.day_counter,
+        hours: time.hours(),
+        minutes: time.minutes(),
+        level: pc.level,
+        xp_amount: pc.xp_amount,
+        money: pc.money,
+    })
+}
+
+/// Base name of a save's inner files, from the folder or archive name
+/// (`Foo.ntwtf`, `Foo.ntwtf.zip` or `Foo.zip` all give `Foo`).
+pub fn save_base_name(file_name: &str) -> &str {
+    file_name.strip_suffix(".ntwtf.zip")
+        .or_else(|| file_name.strip_suffix(".zip"))
+        .or_else(|| file_name.strip_suffix(".ntwtf"))
+        .unwrap_or(file_name)
+}
+
+/// Parse one save JSON file both as a raw value and as its typed model,
+/// reporting the JSON path of the first field that fails.
+fn parse_json_file<T: serde::de::DeserializeOwned>(content: &[u8], role: FileRole, path: &Path) -> AppResult<(serde_json::Value, T)> {
+    let content_str = String::from_utf8_lossy(content);
+    let raw: serde_json::Value = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&content_str))
+        .map_err(|e| AppError::json(role, path, e))?;
+    let typed: T = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&content_str))
+        .map_err(|e| AppError::json(role, path, e))?;
+    Ok((raw, typed))
+}
+
+/// The character sheet as currently held in the raw 2nd file.
+pub fn character_sheet_from_raw(second_raw: &serde_json::Value, game_data: &GameDataService) -> Option<CharacterSheet> {
+    let sheet = second_raw.get("characterSheet").or_else(|| second_raw.get("CharacterSheet"))?;
+    Some(parse_character_sheet(sheet, game_data))
 }
 
 fn parse_character_sheet(raw: &serde_json::Value, game_data: &GameDataService) -> CharacterSheet {
@@ -153,395 +272,393 @@ fn parse_character_sheet(raw: &serde_json::Value, game_data: &GameDataService) -
     sheet
 }
 
+/// Set Lua variables from user-entered text. Each value must parse as the
+/// variable's type: the one in the save, or for a variable the game hasn't
+/// written yet, the type of its `initial_value` in the game data. Nothing is
+/// changed unless every edit is valid.
+pub fn apply_lua_edits<'a>(
+    lua_db: &mut LuaDatabase,
+    edits: impl IntoIterator<Item = (&'a str, &'a str)>,
+    game_data: &GameDataService,
+) -> AppResult<()> {
+    let flat = lua_database::flatten_lua(lua_db);
+    let mut parsed = Vec::new();
+    for (key, text) in edits {
+        let current = flat.get(key).cloned()
+            .or_else(|| game_data.initial_lua_value(key))
+            .ok_or_else(|| AppError::UnknownVariable(key.to_string()))?;
+        parsed.push((key, lua_database::parse_value_like(&current, key, text)?));
+    }
+    for (key, value) in parsed {
+        lua_database::set_lua_value(lua_db, key, value);
+    }
+    Ok(())
+}
+
+/// Refuse to write a salvaged Lua database, which drops the bytes salvage
+/// mode skipped, unless the caller has confirmed losing them.
+pub fn check_salvage(report: Option<&LuaSalvageReport>, discard_salvaged: bool) -> AppResult<()> {
+    match report {
+        Some(report) if !discard_salvaged => Err(AppError::SalvageUnconfirmed(report.skipped.clone())),
+        _ => Ok(()),
+    }
+}
+
 /// Save modifications back to disk with round-trip fidelity.
+#[allow(clippy::too_many_arguments)]
 pub fn save_to_disk(
     folder_path: &str,
     base_name: &str,
     first_raw: &mut serde_json::Value,
     second_raw: &mut serde_json::Value,
     save_state: &SaveUpdatePayload,
-    _character_sheet: &CharacterSheet,
-    lua_db: &HashMap<String, LuaValue>,
+    lua_db: &LuaDatabase,
     states: &StatesData,
-) -> Result<(), String> {
-    // Create backup
-    create_backup(folder_path)?;
+    game_data: &GameDataService,
+) -> AppResult<()> {
+    let cabinet_slots = thought_cabinet::place_thoughts(&save_state.thought_cabinet, &save_state.thoughts)?;
 
     // Apply first file changes
     if let serde_json::Value::Object(obj) = first_raw {
         obj.insert("areaId".to_string(), serde_json::json!(save_state.area_id));
 
-        if let Some(ps) = obj.get_mut("partyState") {
-            if let serde_json::Value::Object(party) = ps {
-                party.insert("isKimInParty".to_string(), serde_json::json!(save_state.party_state.is_kim_in_party));
-                party.insert("isKimLeftOutside".to_string(), serde_json::json!(save_state.party_state.is_kim_left_outside));
-                party.insert("isKimAbandoned".to_string(), serde_json::json!(save_state.party_state.is_kim_abandoned));
-                party.insert("isKimAwayUpToMorning".to_string(), serde_json::json!(save_state.party_state.is_kim_away_up_to_morning));
-                party.insert("isKimSleepingInHisRoom".to_string(), serde_json::json!(save_state.party_state.is_kim_sleeping_in_his_room));
-                party.insert("isKimSayingGoodMorning".to_string(), serde_json::json!(save_state.party_state.is_kim_saying_good_morning));
-                party.insert("isCunoInParty".to_string(), serde_json::json!(save_state.party_state.is_cuno_in_party));
-                party.insert("isCunoLeftOutside".to_string(), serde_json::json!(save_state.party_state.is_cuno_left_outside));
-                party.insert("isCunoAbandoned".to_string(), serde_json::json!(save_state.party_state.is_cuno_abandoned));
-                party.insert("hasHangover".to_string(), serde_json::json!(save_state.party_state.has_hangover));
-                party.insert("sleepLocation".to_string(), serde_json::json!(save_state.party_state.sleep_location));
-                party.insert("waitLocation".to_string(), serde_json::json!(save_state.party_state.wait_location));
-                party.insert("cunoWaitLocation".to_string(), serde_json::json!(save_state.party_state.cuno_wait_location));
-                party.insert("timeSinceKimWentSleepingInHisRoom".to_string(), serde_json::json!(save_state.party_state.time_since_kim_went_sleeping));
-                party.insert("kimLastArrivalLocation".to_string(), serde_json::json!(save_state.party_state.kim_last_arrival_location));
-                party.insert("cunoLastArrivalLocation".to_string(), serde_json::json!(save_state.party_state.cuno_last_arrival_location));
-            }
+        if let Some(serde_json::Value::Object(party)) = obj.get_mut("partyState") {
+            party.insert("isKimInParty".to_string(), serde_json::json!(save_state.party_state.is_kim_in_party));
+            party.insert("isKimLeftOutside".to_string(), serde_json::json!(save_state.party_state.is_kim_left_outside));
+            party.insert("isKimAbandoned".to_string(), serde_json::json!(save_state.party_state.is_kim_abandoned));
+            party.insert("isKimAwayUpToMorning".to_string(), serde_json::json!(save_state.party_state.is_kim_away_up_to_morning));
+            party.insert("isKimSleepingInHisRoom".to_string(), serde_json::json!(save_state.party_state.is_kim_sleeping_in_his_room));
+            party.insert("isKimSayingGoodMorning".to_string(), serde_json::json!(save_state.party_state.is_kim_saying_good_morning));
+            party.insert("isCunoInParty".to_string(), serde_json::json!(save_state.party_state.is_cuno_in_party));
+            party.insert("isCunoLeftOutside".to_string(), serde_json::json!(save_state.party_state.is_cuno_left_outside));
+            party.insert("isCunoAbandoned".to_string(), serde_json::json!(save_state.party_state.is_cuno_abandoned));
+            party.insert("hasHangover".to_string(), serde_json::json!(save_state.party_state.has_hangover));
+            party.insert("sleepLocation".to_string(), serde_json::json!(save_state.party_state.sleep_location));
+            party.insert("waitLocation".to_string(), serde_json::json!(save_state.party_state.wait_location));
+            party.insert("cunoWaitLocation".to_string(), serde_json::json!(save_state.party_state.cuno_wait_location));
+            party.insert("timeSinceKimWentSleepingInHisRoom".to_string(), serde_json::json!(save_state.party_state.time_since_kim_went_sleeping));
+            party.insert("kimLastArrivalLocation".to_string(), serde_json::json!(save_state.party_state.kim_last_arrival_location));
+            party.insert("cunoLastArrivalLocation".to_string(), serde_json::json!(save_state.party_state.cuno_last_arrival_location));
         }
     }
+    fog::apply_to_first_raw(&save_state.fog, first_raw);
 
     // Apply second file changes
+    journal::apply_to_second_raw(&save_state.journal, second_raw);
+    white_checks::apply_to_second_raw(&save_state.white_checks, second_raw);
+    thought_cabinet::apply_to_second_raw(
+        &save_state.thoughts,
+        &cabinet_slots,
+        &save_state.thought_cabinet.selected_project_name,
+        second_raw,
+    );
+    inventory::apply_to_second_raw(&save_state.owned_items, second_raw);
+    containers::apply_to_second_raw(&save_state.containers, second_raw);
+    time::apply_to_second_raw(&save_state.clock, second_raw);
+    doors::apply_to_second_raw(&save_state.doors, second_raw);
     if let serde_json::Value::Object(obj) = second_raw {
         // Player character - check both cases
         let pc_key = if obj.contains_key("playerCharacter") { "playerCharacter" } else { "PlayerCharacter" };
-        if let Some(pc) = obj.get_mut(pc_key) {
-            if let serde_json::Value::Object(pc_obj) = pc {
-                let xp_key = if pc_obj.contains_key("XpAmount") { "XpAmount" } else { "xpAmount" };
-                let lvl_key = if pc_obj.contains_key("Level") { "Level" } else { "level" };
-                let sp_key = if pc_obj.contains_key("SkillPoints") { "SkillPoints" } else { "skillPoints" };
-                let money_key = if pc_obj.contains_key("Money") { "Money" } else { "money" };
-
-                pc_obj.insert(xp_key.to_string(), serde_json::json!(save_state.xp_amount));
-                pc_obj.insert(lvl_key.to_string(), serde_json::json!(save_state.level));
-                pc_obj.insert(sp_key.to_string(), serde_json::json!(save_state.skill_points));
-                pc_obj.insert(money_key.to_string(), serde_json::json!(save_state.money));
-                
-                if let Some(hp) = pc_obj.get_mut("healingPools") {
-                    if let serde_json::Value::Object(hp_obj) = hp {
-                        hp_obj.insert("ENDURANCE".to_string(), serde_json::json!(save_state.health));
-                        hp_obj.insert("VOLITION".to_string(), serde_json::json!(save_state.morale));
-                    }
-                }
-            }
-        }
-
-        // Time
-        let time_holder_key = if obj.contains_key("sunshineClockTimeHolder") { "sunshineClockTimeHolder" } else { "SunshineClockTimeHolder" };
-        if let Some(th) = obj.get_mut(time_holder_key) {
-            if let Some(time) = th.get_mut("time") {
-                if let serde_json::Value::Object(time_obj) = time {
-                    time_obj.insert("dayCounter".to_string(), serde_json::json!(save_state.day));
-                    time_obj.insert("realDayCounter".to_string(), serde_json::json!(save_state.day));
-                    time_obj.insert("dayMinutes".to_string(), serde_json::json!(save_state.hours * 60 + save_state.minutes));
-                }
+        if let Some(serde_json::Value::Object(pc_obj)) = obj.get_mut(pc_key) {
+            let xp_key = if pc_obj.contains_key("XpAmount") { "XpAmount" } else { "xpAmount" };
+            let lvl_key = if pc_obj.contains_key("Level") { "Level" } else { "level" };
+            let sp_key = if pc_obj.contains_key("SkillPoints") { "SkillPoints" } else { "skillPoints" };
+            let money_key = if pc_obj.contains_key("Money") { "Money" } else { "money" };
+
+            pc_obj.insert(xp_key.to_string(), serde_json::json!(save_state.xp_amount));
+            pc_obj.insert(lvl_key.to_string(), serde_json::json!(save_state.level));
+            pc_obj.insert(sp_key.to_string(), serde_json::json!(save_state.skill_points));
+            pc_obj.insert(money_key.to_string(), serde_json::json!(save_state.money));
+            
+            if let Some(serde_json::Value::Object(hp_obj)) = pc_obj.get_mut("healingPools") {
+                hp_obj.insert("ENDURANCE".to_string(), serde_json::json!(save_state.health));
+                hp_obj.insert("VOLITION".to_string(), serde_json::json!(save_state.morale));
             }
         }
 
         // Character sheet - abilities and skills
         let cs_key = if obj.contains_key("characterSheet") { "characterSheet" } else { "CharacterSheet" };
-        if let Some(cs) = obj.get_mut(cs_key) {
-            if let serde_json::Value::Object(cs_obj) = cs {
-                for ability in &save_state.abilities {
-                    if let Some(entry) = cs_obj.get_mut(&ability.save_key) {
-                        if let serde_json::Value::Object(e) = entry {
-                            e.insert("value".to_string(), serde_json::json!(ability.value));
-                            e.insert("maximumValue".to_string(), serde_json::json!(ability.maximum_value.max(ability.value)));
-                            e.insert("isSignature".to_string(), serde_json::json!(ability.is_signature));
-                        }
-                    }
-                }
-
-                for skill in &save_state.skills {
-                    if let Some(entry) = cs_obj.get_mut(&skill.save_key) {
-                        if let serde_json::Value::Object(e) = entry {
-                            e.insert("value".to_string(), serde_json::json!(skill.value));
-                            e.insert("maximumValue".to_string(), serde_json::json!(skill.maximum_value.max(skill.value)));
-                            e.insert("rankValue".to_string(), serde_json::json!(skill.rank_value));
-                            e.insert("hasAdvancement".to_string(), serde_json::json!(skill.has_advancement));
-                            e.insert("isSignature".to_string(), serde_json::json!(skill.is_signature));
-                        }
-                    }
+        if let Some(serde_json::Value::Object(cs_obj)) = obj.get_mut(cs_key) {
+            // Derived numbers and modifiers are recomputed against the sheet as it was
+            let previous = parse_character_sheet(&serde_json::Value::Object(cs_obj.clone()), game_data);
+            let stats = skill_modifiers::compute(&StatInputs {
+                abilities: &save_state.abilities,
+                skills: &save_state.skills,
+                items: &save_state.owned_items,
+                thoughts: &save_state.thoughts,
+                previous: &previous,
+            });
+            skill_modifiers::apply_to_character_sheet(&stats, cs_obj);
+
+            for ability in &save_state.abilities {
+                if let Some(serde_json::Value::Object(e)) = cs_obj.get_mut(&ability.save_key) {
+                    e.insert("isSignature".to_string(), serde_json::json!(ability.is_signature));
                 }
+            }
 
-                // Items
-                let gained: Vec<String> = save_state.owned_items.iter()
-                    .filter(|i| i.is_owned).map(|i| i.name.clone()).collect();
-                let equipped: Vec<String> = save_state.owned_items.iter()
-                    .filter(|i| i.is_equipped).map(|i| i.name.clone()).collect();
-                cs_obj.insert("gainedItems".to_string(), serde_json::json!(gained));
-                cs_obj.insert("equippedItems".to_string(), serde_json::json!(equipped));
-
-                // Thoughts
-                let mut gained_thoughts = vec![];
-                let mut cooking_thoughts = vec![];
-                let mut fixed_thoughts = vec![];
-                let mut forgotten_thoughts = vec![];
-
-                for thought in &save_state.thoughts {
-                    match thought.state.as_str() {
-                        "Gained" => gained_thoughts.push(thought.name.clone()),
-                        "Processing" => {
-                            gained_thoughts.push(thought.name.clone());
-                            cooking_thoughts.push(thought.name.clone());
-                        }
-                        "Internalized" => {
-                            gained_thoughts.push(thought.name.clone());
-                            fixed_thoughts.push(thought.name.clone());
-                        }
-                        "Forgotten" => {
-                            gained_thoughts.push(thought.name.clone());
-                            forgotten_thoughts.push(thought.name.clone());
-                        }
-                        _ => {}
-                    }
+            for skill in &save_state.skills {
+                if let Some(serde_json::Value::Object(e)) = cs_obj.get_mut(&skill.save_key) {
+                    e.insert("hasAdvancement".to_string(), serde_json::json!(skill.has_advancement));
+                    e.insert("isSignature".to_string(), serde_json::json!(skill.is_signature));
                 }
-                cs_obj.insert("gainedThoughts".to_string(), serde_json::json!(gained_thoughts));
-                cs_obj.insert("cookingThoughts".to_string(), serde_json::json!(cooking_thoughts));
-                cs_obj.insert("fixedThoughts".to_string(), serde_json::json!(fixed_thoughts));
-                cs_obj.insert("forgottenThoughts".to_string(), serde_json::json!(forgotten_thoughts));
             }
-        }
 
-        // Thought cabinet state
-        if let Some(tcs) = obj.get_mut("thoughtCabinetState") {
-            if let serde_json::Value::Object(tcs_obj) = tcs {
-                let mut list_state = vec![];
-                let mut slots = vec![];
-
-                for thought in &save_state.thoughts {
-                    if thought.state == "NotAcquired" { continue; }
-
-                    let game_state = match thought.state.as_str() {
-                        "Internalized" => "FIXED",
-                        "Processing" => "COOKING",
-                        "Gained" => "GAINED",
-                        "Forgotten" => "FORGOTTEN",
-                        _ => "GAINED",
-                    };
-
-                    list_state.push(serde_json::json!({
-                        "name": thought.name,
-                        "isFresh": false,
-                        "state": game_state,
-                        "timeLeft": thought.time_left
-                    }));
-
-                    if thought.state == "Internalized" || thought.state == "Processing" {
-                        slots.push(serde_json::json!({
-                            "Item1": "FILLED",
-                            "Item2": thought.name
-                        }));
+            // Items
+            let gained: Vec<String> = save_state.owned_items.iter()
+                .filter(|i| i.is_owned).map(|i| i.name.clone()).collect();
+            let equipped: Vec<String> = save_state.owned_items.iter()
+                .filter(|i| i.is_equipped).map(|i| i.name.clone()).collect();
+            cs_obj.insert("gainedItems".to_string(), serde_json::json!(gained));
+            cs_obj.insert("equippedItems".to_string(), serde_json::json!(equipped));
+
+            // Thoughts
+            let mut gained_thoughts = vec![];
+            let mut cooking_thoughts = vec![];
+            let mut fixed_thoughts = vec![];
+            let mut forgotten_thoughts = vec![];
+
+            for thought in &save_state.thoughts {
+                match thought.state.as_str() {
+                    "Gained" => gained_thoughts.push(thought.name.clone()),
+                    "Processing" => {
+                        gained_thoughts.push(thought.name.clone());
+                        cooking_thoughts.push(thought.name.clone());
                     }
-                }
-
-                // Fill remaining slots with EMPTY (up to 12 slots is standard in DE)
-                while slots.len() < 12 {
-                    slots.push(serde_json::json!({
-                        "Item1": "EMPTY",
-                        "Item2": ""
-                    }));
-                }
-
-                tcs_obj.insert("thoughtListState".to_string(), serde_json::Value::Array(list_state));
-                if let Some(view) = tcs_obj.get_mut("thoughtCabinetViewState") {
-                    if let serde_json::Value::Object(v) = view {
-                        v.insert("slotStates".to_string(), serde_json::Value::Array(slots));
+                    "Internalized" => {
+                        gained_thoughts.push(thought.name.clone());
+                        fixed_thoughts.push(thought.name.clone());
+                    }
+                    "Forgotten" => {
+                        gained_thoughts.push(thought.name.clone());
+                        forgotten_thoughts.push(thought.name.clone());
                     }
+                    _ => {}
                 }
             }
+            cs_obj.insert("gainedThoughts".to_string(), serde_json::json!(gained_thoughts));
+            cs_obj.insert("cookingThoughts".to_string(), serde_json::json!(cooking_thoughts));
+            cs_obj.insert("fixedThoughts".to_string(), serde_json::json!(fixed_thoughts));
+            cs_obj.insert("forgottenThoughts".to_string(), serde_json::json!(forgotten_thoughts));
         }
 
         // Game mode
-        if let Some(gm) = obj.get_mut("gameModeState") {
-            if let serde_json::Value::Object(gm_obj) = gm {
-                gm_obj.insert("gameMode".to_string(), serde_json::json!(save_state.game_mode));
-            }
+        if let Some(serde_json::Value::Object(gm_obj)) = obj.get_mut("gameModeState") {
+            gm_obj.insert("gameMode".to_string(), serde_json::json!(save_state.game_mode));
         }
 
         // HUD state
-        if let Some(hud) = obj.get_mut("hudState") {
-            if let serde_json::Value::Object(h) = hud {
-                h.insert("tequilaPortraitObscured".to_string(), serde_json::json!(save_state.hud_state.portrait_obscured));
-                h.insert("tequilaPortraitShaved".to_string(), serde_json::json!(save_state.hud_state.portrait_shaved));
-                h.insert("tequilaPortraitExpressionStopped".to_string(), serde_json::json!(save_state.hud_state.portrait_expression_stopped));
-                h.insert("tequilaPortraitFascist".to_string(), serde_json::json!(save_state.hud_state.portrait_fascist));
-                h.insert("charsheetNotification".to_string(), serde_json::json!(save_state.hud_state.charsheet_notification));
-                h.insert("inventoryNotification".to_string(), serde_json::json!(save_state.hud_state.inventory_notification));
-                h.insert("journalNotification".to_string(), serde_json::json!(save_state.hud_state.journal_notification));
-                h.insert("thcNotification".to_string(), serde_json::json!(save_state.hud_state.thc_notification));
-                h.insert("invClothesNotification".to_string(), serde_json::json!(save_state.hud_state.inv_clothes_notification));
-                h.insert("invPawnablesNotification".to_string(), serde_json::json!(save_state.hud_state.inv_pawnables_notification));
-                h.insert("invReadingNotification".to_string(), serde_json::json!(save_state.hud_state.inv_reading_notification));
-                h.insert("invToolsNotification".to_string(), serde_json::json!(save_state.hud_state.inv_tools_notification));
-            }
+        if let Some(serde_json::Value::Object(h)) = obj.get_mut("hudState") {
+            h.insert("tequilaPortraitObscured".to_string(), serde_json::json!(save_state.hud_state.portrait_obscured));
+            h.insert("tequilaPortraitShaved".to_string(), serde_json::json!(save_state.hud_state.portrait_shaved));
+            h.insert("tequilaPortraitExpressionStopped".to_string(), serde_json::json!(save_state.hud_state.portrait_expression_stopped));
+            h.insert("tequilaPortraitFascist".to_string(), serde_json::json!(save_state.hud_state.portrait_fascist));
+            h.insert("charsheetNotification".to_string(), serde_json::json!(save_state.hud_state.charsheet_notification));
+            h.insert("inventoryNotification".to_string(), serde_json::json!(save_state.hud_state.inventory_notification));
+            h.insert("journalNotification".to_string(), serde_json::json!(save_state.hud_state.journal_notification));
+            h.insert("thcNotification".to_string(), serde_json::json!(save_state.hud_state.thc_notification));
+            h.insert("invClothesNotification".to_string(), serde_json::json!(save_state.hud_state.inv_clothes_notification));
+            h.insert("invPawnablesNotification".to_string(), serde_json::json!(save_state.hud_state.inv_pawnables_notification));
+            h.insert("invReadingNotification".to_string(), serde_json::json!(save_state.hud_state.inv_reading_notification));
+            h.insert("invToolsNotification".to_string(), serde_json::json!(save_state.hud_state.inv_tools_notification));
         }
 
         // Weather
-        if let Some(ws) = obj.get_mut("weatherState") {
-            if let serde_json::Value::Object(w) = ws {
-                w.insert("weatherPreset".to_string(), serde_json::json!(save_state.weather_preset));
-            }
+        if let Some(serde_json::Value::Object(w)) = obj.get_mut("weatherState") {
+            w.insert("weatherPreset".to_string(), serde_json::json!(save_state.weather_preset));
         }
 
         // Inventory bullets and equipment
-        if let Some(inv) = obj.get_mut("inventoryState") {
-            if let serde_json::Value::Object(inv_obj) = inv {
-                if let Some(ivs) = inv_obj.get_mut("inventoryViewState") {
-                    if let serde_json::Value::Object(ivs_obj) = ivs {
-                        ivs_obj.insert("bullets".to_string(), serde_json::json!(save_state.bullets));
-
-                        let mut equipment = serde_json::Map::new();
-                        for item in save_state.owned_items.iter().filter(|i| i.is_equipped && !i.equip_slot.is_empty()) {
-                            equipment.insert(item.equip_slot.clone(), serde_json::json!(item.name));
-                        }
-                        ivs_obj.insert("equipment".to_string(), serde_json::Value::Object(equipment));
-                    }
-                }
-            }
-        }
+        if let Some(serde_json::Value::Object(inv_obj)) = obj.get_mut("inventoryState") {
+            if let Some(serde_json::Value::Object(ivs_obj)) = inv_obj.get_mut("inventoryViewState") {
+                ivs_obj.insert("bullets".to_string(), serde_json::json!(save_state.bullets));
 
-        // Door states
-        if let Some(vih) = obj.get_mut("variousItemsHolder") {
-            if let serde_json::Value::Object(v) = vih {
-                v.insert("DoorStates".to_string(), serde_json::json!(save_state.door_states));
+                let mut equipment = serde_json::Map::new();
+                for item in save_state.owned_items.iter().filter(|i| i.is_equipped && !i.equip_slot.is_empty()) {
+                    equipment.insert(item.equip_slot.clone(), serde_json::json!(item.name));
+                }
+                ivs_obj.insert("equipment".to_string(), serde_json::Value::Object(equipment));
             }
         }
 
         // Journal location flags
-        if let Some(jt) = obj.get_mut("aquiredJournalTasks") {
-            if let serde_json::Value::Object(j) = jt {
-                j.insert("wasChurchVisited".to_string(), serde_json::json!(save_state.location_flags.was_church_visited));
-                j.insert("wasFishingVillageVisited".to_string(), serde_json::json!(save_state.location_flags.was_fishing_village_visited));
-                j.insert("wasQuicktravelChurchDiscovered".to_string(), serde_json::json!(save_state.location_flags.was_quicktravel_church_discovered));
-                j.insert("wasQuicktravelFishingVillageDiscovered".to_string(), serde_json::json!(save_state.location_flags.was_quicktravel_fishing_village_discovered));
-            }
-        }
-
-        // White checks - remove ones marked for reset
-        if let Some(fwc) = obj.get_mut("failedWhiteChecksHolder") {
-            if let serde_json::Value::Object(f) = fwc {
-                if let Some(wcc) = f.get_mut("WhiteCheckCache") {
-                    if let serde_json::Value::Object(w) = wcc {
-                        for key in &save_state.reset_check_keys {
-                            w.remove(key);
-                        }
-                    }
-                }
-                if let Some(swcc) = f.get_mut("SeenWhiteCheckCache") {
-                    if let serde_json::Value::Object(s) = swcc {
-                        for key in &save_state.reset_seen_check_keys {
-                            s.remove(key);
-                        }
-                    }
-                }
-            }
+        if let Some(serde_json::Value::Object(j)) = obj.get_mut("aquiredJournalTasks") {
+            j.insert("wasChurchVisited".to_string(), serde_json::json!(save_state.location_flags.was_church_visited));
+            j.insert("wasFishingVillageVisited".to_string(), serde_json::json!(save_state.location_flags.was_fishing_village_visited));
+            j.insert("wasQuicktravelChurchDiscovered".to_string(), serde_json::json!(save_state.location_flags.was_quicktravel_church_discovered));
+            j.insert("wasQuicktravelFishingVillageDiscovered".to_string(), serde_json::json!(save_state.location_flags.was_quicktravel_fishing_village_discovered));
         }
     }
 
-    // Write files
-    let first_json = serde_json::to_string_pretty(first_raw)
-        .map_err(|e| format!("Failed to serialize 1st file: {}", e))?;
+    write_save_files(folder_path, base_name, first_raw, second_raw, lua_db, states, save_state.fog.fow_file.as_ref())
+}
 
-    let second_json = serde_json::to_string_pretty(second_raw)
-        .map_err(|e| format!("Failed to serialize 2nd file: {}", e))?;
+/// Snapshot the save, then write the save files as they are in memory.
+/// `.FOW.json` is only written when `fow` is given.
+pub fn write_save_files(
+    folder_path: &str,
+    base_name: &str,
+    first_raw: &serde_json::Value,
+    second_raw: &serde_json::Value,
+    lua_db: &LuaDatabase,
+    states: &StatesData,
+    fow: Option<&FowFile>,
+) -> AppResult<()> {
+    let snapshots = SnapshotStore::open()?;
+    snapshots.take(folder_path, None)?;
+    snapshots.prune(folder_path)?;
+
+    let folder = Path::new(folder_path);
+    let first_path = folder.join(format!("{}.1st.ntwtf.json", base_name));
+    let second_path = folder.join(format!("{}.2nd.ntwtf.json", base_name));
+    let lua_path = folder.join(format!("{}.ntwtf.lua", base_name));
+    let states_path = folder.join(format!("{}.states.lua", base_name));
+
+    // The JSON files are rewritten as edits of their copies on disk, so
+    // untouched values keep their exact text
+    let first_name = format!("{}.1st.ntwtf.json", base_name);
+    let second_name = format!("{}.2nd.ntwtf.json", base_name);
+    let fow_name = format!("{}.FOW.json", base_name);
+    let states_name = format!("{}.states.lua", base_name);
+    let mut on_disk = read_save_entries(folder, &[
+        (first_name.as_str(), FileRole::First),
+        (second_name.as_str(), FileRole::Second),
+        (fow_name.as_str(), FileRole::Fow),
+        (states_name.as_str(), FileRole::States),
+    ])?.into_iter();
+    let (first_on_disk, second_on_disk, fow_on_disk, states_on_disk) =
+        (on_disk.next().flatten(), on_disk.next().flatten(), on_disk.next().flatten(), on_disk.next().flatten());
+
+    let first_json = to_json_like(first_on_disk.as_deref(), first_raw)
+        .map_err(|e| AppError::Json { role: FileRole::First, path: first_path.display().to_string(), json_path: String::new(), source: e })?;
+
+    let second_json = to_json_like(second_on_disk.as_deref(), second_raw)
+        .map_err(|e| AppError::Json { role: FileRole::Second, path: second_path.display().to_string(), json_path: String::new(), source: e })?;
 
     let lua_bytes = lua_database::serialize_lua_database(lua_db)
-        .map_err(|e| format!("Failed to serialize lua database: {}", e))?;
+        .map_err(|e| AppError::io(FileRole::LuaDatabase, &lua_path, e))?;
+
+    // states.lua is spliced the same way
+    let states_content = match states_on_disk.as_deref().map(String::from_utf8_lossy) {
+        Some(text) => states_lua::rewrite_states(&text, states),
+        None => states_lua::serialize_states(states),
+    };
 
-    let states_content = states_lua::serialize_states(states);
+    let fow_json = fow.map(|fow| serde_json::to_value(fow).and_then(|value| to_json_like(fow_on_disk.as_deref(), &value)))
+        .transpose()
+        .map_err(|e| AppError::Json { role: FileRole::Fow, path: folder.join(&fow_name).display().to_string(), json_path: String::new(), source: e })?;
 
     if folder_path.ends_with(".zip") {
         let path = Path::new(folder_path);
-        let temp_path = path.with_extension("tmp_zip");
-        
+        let temp = TempFile { path: path.with_extension("tmp_zip"), committed: false };
+        let temp_path = &temp.path;
+
         {
-            let file = std::fs::File::open(path).map_err(|e| format!("Failed to open original zip: {}", e))?;
-            let mut old_archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid zip: {}", e))?;
+            let file = std::fs::File::open(path).map_err(|e| AppError::io(FileRole::Archive, path, e))?;
+            let mut old_archive = zip::ZipArchive::new(file).map_err(|e| AppError::zip(path, e))?;
             
-            let temp_file = std::fs::File::create(&temp_path).map_err(|e| format!("Failed to create temp zip: {}", e))?;
+            let temp_file = std::fs::File::create(temp_path).map_err(|e| AppError::io(FileRole::Archive, temp_path, e))?;
             let mut new_archive = zip::ZipWriter::new(temp_file);
             
-            let first_name = format!("{}.1st.ntwtf.json", base_name);
-            let second_name = format!("{}.2nd.ntwtf.json", base_name);
             let lua_name = format!("{}.ntwtf.lua", base_name);
-            let states_name = format!("{}.states.lua", base_name);
+            let mut fow_written = false;
             
+            new_archive.set_raw_comment(old_archive.comment().to_vec().into_boxed_slice());
+
             for i in 0..old_archive.len() {
-                let mut entry = old_archive.by_index(i).map_err(|e| format!("Failed to read zip entry: {}", e))?;
-                let name = entry.name().to_string();
-                
-                new_archive.start_file::<_, ()>(name.clone(), zip::write::FileOptions::default())
-                    .map_err(|e| format!("Failed to start zip file: {}", e))?;
-                
-                if name == first_name {
-                    new_archive.write_all(first_json.as_bytes()).map_err(|e| format!("Failed to write 1st file to zip: {}", e))?;
+                let name = old_archive.name_for_index(i).unwrap_or_default().to_string();
+                let entry_path = path.join(&name);
+
+                let edited = if name == first_name {
+                    Some((first_json.as_bytes(), FileRole::First))
                 } else if name == second_name {
-                    new_archive.write_all(second_json.as_bytes()).map_err(|e| format!("Failed to write 2nd file to zip: {}", e))?;
+                    Some((second_json.as_bytes(), FileRole::Second))
                 } else if name == lua_name {
-                    new_archive.write_all(&lua_bytes).map_err(|e| format!("Failed to write lua to zip: {}", e))?;
+                    Some((lua_bytes.as_slice(), FileRole::LuaDatabase))
                 } else if name == states_name {
-                    new_archive.write_all(states_content.as_bytes()).map_err(|e| format!("Failed to write states to zip: {}", e))?;
+                    Some((states_content.as_bytes(), FileRole::States))
+                } else if name == fow_name && fow_json.is_some() {
+                    fow_written = true;
+                    fow_json.as_deref().map(|json| (json.as_bytes(), FileRole::Fow))
                 } else {
-                    let mut content = Vec::new();
-                    entry.read_to_end(&mut content).map_err(|e| format!("Failed to read zip entry content: {}", e))?;
-                    new_archive.write_all(&content).map_err(|e| format!("Failed to copy zip entry: {}", e))?;
+                    None
+                };
+
+                // Only entries whose content actually changed are recompressed
+                let changed = match edited {
+                    Some((content, role)) => {
+                        let mut entry = old_archive.by_index(i).map_err(|e| AppError::zip(path, e))?;
+                        let mut original = Vec::new();
+                        entry.read_to_end(&mut original).map_err(|e| AppError::io(role, &entry_path, e))?;
+                        (original != content).then_some((content, role))
+                    }
+                    None => None,
+                };
+
+                let entry = old_archive.by_index_raw(i).map_err(|e| AppError::zip(path, e))?;
+                match changed {
+                    Some((content, role)) => {
+                        // Keeps the original's compression, timestamp and permissions
+                        new_archive.start_file(name, entry.options())
+                            .map_err(|e| AppError::zip(temp_path, e))?;
+                        new_archive.write_all(content).map_err(|e| AppError::io(role, &entry_path, e))?;
+                    }
+                    None => new_archive.raw_copy_file(entry).map_err(|e| AppError::zip(temp_path, e))?,
                 }
             }
-            new_archive.finish().map_err(|e| format!("Failed to finish zip: {}", e))?;
+            if let (Some(json), false) = (&fow_json, fow_written) {
+                new_archive.start_file(fow_name.clone(), zip::write::SimpleFileOptions::default())
+                    .map_err(|e| AppError::zip(temp_path, e))?;
+                new_archive.write_all(json.as_bytes()).map_err(|e| AppError::io(FileRole::Fow, path.join(&fow_name), e))?;
+            }
+            new_archive.finish().map_err(|e| AppError::zip(temp_path, e))?
+                .sync_all().map_err(|e| AppError::io(FileRole::Archive, temp_path, e))?;
         }
         
-        std::fs::rename(temp_path, path).map_err(|e| format!("Failed to rename temp zip: {}", e))?;
+        std::fs::rename(temp_path, path).map_err(|e| AppError::io(FileRole::Archive, path, e))?;
+        temp.commit();
     } else {
-        let folder = Path::new(folder_path);
-        let first_path = folder.join(format!("{}.1st.ntwtf.json", base_name));
-        let second_path = folder.join(format!("{}.2nd.ntwtf.json", base_name));
-        let lua_path = folder.join(format!("{}.ntwtf.lua", base_name));
-        let states_path = folder.join(format!("{}.states.lua", base_name));
-
-        std::fs::write(&first_path, &first_json)
-            .map_err(|e| format!("Failed to write 1st file: {}", e))?;
-
-        std::fs::write(&second_path, &second_json)
-            .map_err(|e| format!("Failed to write 2nd file: {}", e))?;
-
-        std::fs::write(&lua_path, &lua_bytes)
-            .map_err(|e| format!("Failed to write lua database: {}", e))?;
-
-        std::fs::write(&states_path, &states_content)
-            .map_err(|e| format!("Failed to write states file: {}", e))?;
+        let mut files = vec![
+            StagedFile { path: first_path, content: first_json.as_bytes(), role: FileRole::First },
+            StagedFile { path: second_path, content: second_json.as_bytes(), role: FileRole::Second },
+            StagedFile { path: lua_path, content: &lua_bytes, role: FileRole::LuaDatabase },
+            StagedFile { path: states_path, content: states_content.as_bytes(), role: FileRole::States },
+        ];
+        if let Some(json) = &fow_json {
+            files.push(StagedFile { path: folder.join(&fow_name), content: json.as_bytes(), role: FileRole::Fow });
+        }
+        atomic_write::replace_files(base_name, &files)?;
     }
 
     Ok(())
 }
 
-fn create_backup(save_path: &str) -> Result<(), String> {
-    let path = Path::new(save_path);
-    let backup_path = format!("{}.backup", save_path);
-    
-    if Path::new(&backup_path).exists() {
-        // Rotate backups
-        let backup2 = format!("{}.backup.2", save_path);
-        if Path::new(&backup2).exists() {
-            if Path::new(&backup2).is_dir() {
-                let _ = std::fs::remove_dir_all(&backup2);
-            } else {
-                let _ = std::fs::remove_file(&backup2);
-            }
-        }
-        let _ = std::fs::rename(&backup_path, &backup2);
+/// A temp file that is deleted when dropped, unless it was renamed into place.
+struct TempFile {
+    path: PathBuf,
+    committed: bool,
+}
+
+impl TempFile {
+    fn commit(mut self) {
+        self.committed = true;
     }
+}
 
-    if path.is_dir() {
-        std::fs::create_dir_all(&backup_path)
-            .map_err(|e| format!("Failed to create backup dir: {}", e))?;
-
-        if let Ok(entries) = std::fs::read_dir(path) {
-            for entry in entries.flatten() {
-                if entry.path().is_file() {
-                    let dest = Path::new(&backup_path).join(entry.file_name());
-                    let _ = std::fs::copy(entry.path(), dest);
-                }
-            }
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_file(&self.path);
         }
-    } else {
-        std::fs::copy(path, &backup_path)
-            .map_err(|e| format!("Failed to copy backup file: {}", e))?;
     }
+}
 
-    Ok(())
+/// Serialize a save JSON file in the layout of its copy on disk, falling
+/// back to plain pretty-printing when there is none.
+fn to_json_like(on_disk: Option<&[u8]>, value: &serde_json::Value) -> serde_json::Result<String> {
+    match on_disk.and_then(|content| std::str::from_utf8(content).ok()).and_then(|text| json_format::rewrite(text, value)) {
+        Some(json) => Ok(json),
+        None => serde_json::to_string_pretty(value),
+    }
 }
 
 /// Payload sent from frontend when saving
@@ -556,9 +673,7 @@ pub struct SaveUpdatePayload {
     pub money: i64,
     pub health: i64,
     pub morale: i64,
-    pub day: i64,
-    pub hours: i64,
-    pub minutes: i64,
+    pub clock: GameClock,
     pub abilities: Vec<AbilityDisplay>,
     pub skills: Vec<SkillDisplay>,
     // Inventory
@@ -566,6 +681,7 @@ pub struct SaveUpdatePayload {
     pub bullets: i64,
     // Thoughts
     pub thoughts: Vec<ThoughtDisplay>,
+    pub thought_cabinet: ThoughtCabinetDisplay,
     // Party
     pub area_id: String,
     pub party_state: PartyState,
@@ -576,11 +692,34 @@ pub struct SaveUpdatePayload {
     pub weather_preset: i64,
     pub reputation: ReputationDisplay,
     pub lua_edits: HashMap<String, String>,
-    // White checks to reset
-    pub reset_check_keys: Vec<String>,
-    pub reset_seen_check_keys: Vec<String>,
+    pub white_checks: WhiteChecksState,
+    pub containers: Vec<ContainerDisplay>,
     // States
-    pub door_states: HashMap<String, bool>,
+    pub doors: Vec<DoorDisplay>,
     pub area_states: HashMap<String, i64>,
     pub shown_orbs: HashMap<String, i64>,
+    pub fog: FogState,
+    pub journal: JournalState,
+    // Saving
+    #[serde(default)]
+    pub discard_salvaged: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_file_is_removed_unless_committed() {
+        let temp = tempfile::tempdir().unwrap();
+        let dropped = temp.path().join("dropped.tmp_zip");
+        std::fs::write(&dropped, "partial").unwrap();
+        drop(TempFile { path: dropped.clone(), committed: false });
+        assert!(!dropped.exists());
+
+        let committed = temp.path().join("committed.zip");
+        std::fs::write(&committed, "whole").unwrap();
+        TempFile { path: committed.clone(), committed: false }.commit();
+        assert!(committed.exists());
+    }
 }
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::error::{AppError, AppResult, FileRole};
+
+/// Matches `identifier` in tauri.conf.json, so the GUI's config dir is shared.
+pub const APP_IDENTIFIER: &str = "com.disco-elysium.save-editor";
+
+/// Editor settings shared by the GUI and the CLI.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Settings {
+    /// Folders to scan for saves on top of the known game locations.
+    #[serde(default)]
+    pub extra_save_roots: Vec<String>,
+    /// Where snapshots are kept; the user data dir when unset.
+    #[serde(default)]
+    pub snapshot_dir: Option<String>,
+    #[serde(default)]
+    pub snapshot_retention: SnapshotRetention,
+}
+
+/// Which snapshots `prune` keeps. Anything matched by any rule survives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotRetention {
+    /// The most recent snapshots, regardless of age.
+    pub keep_last: usize,
+    /// The newest snapshot of each of the most recent calendar days (UTC).
+    pub keep_daily: usize,
+    /// Never prune snapshots the user labeled.
+    pub keep_labeled: bool,
+}
+
+impl Default for SnapshotRetention {
+    fn default() -> Self {
+        Self { keep_last: 20, keep_daily: 14, keep_labeled: true }
+    }
+}
+
+/// Where the settings file lives, if the platform has a config dir.
+pub fn settings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_IDENTIFIER).join("settings.json"))
+}
+
+/// Load settings, falling back to defaults when no file has been written yet.
+pub fn load_settings() -> AppResult<Settings> {
+    let Some(path) = settings_path() else { return Ok(Settings::default()) };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Settings::default()),
+        Err(e) => return Err(AppError::io(FileRole::Settings, &path, e)),
+    };
+    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&content))
+        .map_err(|e| AppError::json(FileRole::Settings, &path, e))
+}
+
+pub fn save_settings(settings: &Settings) -> AppResult<()> {
+    let path = settings_path()
+        .ok_or_else(|| AppError::InvalidPath("config directory".to_string()))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| AppError::io(FileRole::Settings, parent, e))?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| AppError::Json { role: FileRole::Settings, path: path.display().to_string(), json_path: String::new(), source: e })?;
+    std::fs::write(&path, json).map_err(|e| AppError::io(FileRole::Settings, &path, e))
+}
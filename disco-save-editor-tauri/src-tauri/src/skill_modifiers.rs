@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use crate::error::{AppError, AppResult};
+use crate::game_data::GameDataService;
+use crate::models::*;
+
+const ITEM_CAUSE: &str = "INVENTORY_ITEM";
+const THOUGHT_CAUSE: &str = "THOUGHT";
+
+/// Everything the character sheet's derived numbers are computed from.
+pub struct StatInputs<'a> {
+    /// Ability values as edited, including their modifiers.
+    pub abilities: &'a [AbilityDisplay],
+    pub skills: &'a [SkillDisplay],
+    pub items: &'a [InventoryItemDisplay],
+    pub thoughts: &'a [ThoughtDisplay],
+    /// The sheet as the save has it, for damage, modifiers the editor can't
+    /// derive, and the bonuses of items and thoughts that didn't change.
+    pub previous: &'a CharacterSheet,
+}
+
+/// An item, or a researching or internalized thought, whose bonuses apply.
+struct Cause<'a> {
+    key: &'a str,
+    cause_type: &'static str,
+    modifier_type: &'static str,
+    bonus_text: &'a str,
+    /// Also active in the same way before the edit, so the save's own
+    /// entries for it are kept; the game leaves out conditional bonuses
+    /// that the text alone can't tell apart.
+    unchanged: bool,
+}
+
+enum Target {
+    Ability(String),
+    Skill(String),
+}
+
+/// Recompute every ability and skill: abilities are their initial dice plus
+/// modifiers, skills their ability's value plus rank plus modifiers (signature,
+/// items, thoughts, damage, and anything else the save had).
+pub fn compute(inputs: &StatInputs) -> CharacterStats {
+    let previous = inputs.previous;
+    let targets = targets(inputs);
+
+    let mut ability_extra: HashMap<String, Vec<ModifierEntry>> = HashMap::new();
+    let mut skill_extra: HashMap<String, Vec<ModifierEntry>> = HashMap::new();
+    for cause in causes(inputs) {
+        if cause.unchanged {
+            for (map, extra) in [
+                (&previous.ability_modifier_cause_map, &mut ability_extra),
+                (&previous.skill_modifier_cause_map, &mut skill_extra),
+            ] {
+                for (type_code, entries) in map {
+                    extra.entry(type_code.clone()).or_default()
+                        .extend(entries.iter().filter(|m| has_cause(m, cause.cause_type, cause.key)).cloned());
+                }
+            }
+            continue;
+        }
+        for (amount, name, explanation) in parse_bonuses(cause.bonus_text) {
+            let (type_code, extra, skill_type) = match targets.get(&name.to_lowercase()) {
+                Some(Target::Ability(code)) => (code, &mut ability_extra, "NONE".to_string()),
+                Some(Target::Skill(code)) => (code, &mut skill_extra, code.clone()),
+                None => continue,
+            };
+            extra.entry(type_code.clone()).or_default().push(ModifierEntry {
+                modifier_type: cause.modifier_type.to_string(),
+                amount,
+                explanation,
+                skill_type,
+                modifier_cause: Some(ModifierCause {
+                    modifier_key: cause.key.to_string(),
+                    modifier_cause_type: cause.cause_type.to_string(),
+                }),
+            });
+        }
+    }
+
+    let abilities: Vec<StatBreakdown> = inputs.abilities.iter().map(|ability| {
+        let before = previous.ability_modifier_cause_map.get(&ability.type_code).map(Vec::as_slice).unwrap_or_default();
+        let base = ability.value - before.iter()
+            .filter(|m| m.modifier_type != "INITIAL_DICE")
+            .map(|m| m.amount)
+            .sum::<i64>();
+
+        let mut modifiers = vec![ModifierEntry {
+            modifier_type: "INITIAL_DICE".to_string(),
+            amount: base,
+            explanation: previous_explanation(before, "INITIAL_DICE")
+                .unwrap_or_else(|| format!("{} base", ability.display_name)),
+            skill_type: "NONE".to_string(),
+            modifier_cause: Some(ability_cause(&ability.type_code)),
+        }];
+        modifiers.extend(ability_extra.remove(&ability.type_code).unwrap_or_default());
+        modifiers.extend(other_modifiers(before, &["INITIAL_DICE"]));
+
+        let value = modifiers.iter().map(|m| m.amount).sum();
+        StatBreakdown {
+            save_key: ability.save_key.clone(),
+            type_code: ability.type_code.clone(),
+            display_name: ability.display_name.clone(),
+            base,
+            rank_value: previous.abilities.get(&ability.save_key).map_or(0, |e| e.rank_value),
+            damage_value: 0,
+            value,
+            maximum_value: ability.maximum_value.max(value),
+            modifiers,
+        }
+    }).collect();
+
+    let skills: Vec<StatBreakdown> = inputs.skills.iter().map(|skill| {
+        let before = previous.skill_modifier_cause_map.get(&skill.type_code).map(Vec::as_slice).unwrap_or_default();
+        let damage = previous.skills.get(&skill.save_key).map_or(0, |e| e.damage_value);
+        let base = abilities.iter()
+            .find(|a| a.type_code == skill.ability_type)
+            .map_or(skill.calculated_ability, |a| a.value);
+
+        let mut modifiers = vec![ModifierEntry {
+            modifier_type: "CALCULATED_ABILITY".to_string(),
+            amount: 0,
+            explanation: previous_explanation(before, "CALCULATED_ABILITY")
+                .unwrap_or_else(|| format!("{} base", skill.display_name)),
+            skill_type: skill.type_code.clone(),
+            modifier_cause: Some(ability_cause(&skill.ability_type)),
+        }];
+        if skill.is_signature {
+            modifiers.push(ModifierEntry {
+                modifier_type: "SIGNATURE".to_string(),
+                amount: 1,
+                explanation: "Signature Skill".to_string(),
+                skill_type: "NONE".to_string(),
+                modifier_cause: None,
+            });
+        }
+        modifiers.extend(skill_extra.remove(&skill.type_code).unwrap_or_default());
+        modifiers.extend(other_modifiers(before, &["CALCULATED_ABILITY", "SIGNATURE", "DAMAGE"]));
+        if damage != 0 {
+            modifiers.push(ModifierEntry {
+                modifier_type: "DAMAGE".to_string(),
+                amount: damage,
+                explanation: "Damage".to_string(),
+                skill_type: "NONE".to_string(),
+                modifier_cause: None,
+            });
+        }
+
+        let value = base + skill.rank_value + modifiers.iter().map(|m| m.amount).sum::<i64>();
+        StatBreakdown {
+            save_key: skill.save_key.clone(),
+            type_code: skill.type_code.clone(),
+            display_name: skill.display_name.clone(),
+            base,
+            rank_value: skill.rank_value,
+            damage_value: damage,
+            value,
+            maximum_value: value - damage,
+            modifiers,
+        }
+    }).collect();
+
+    CharacterStats { abilities, skills }
+}
+
+/// Write the computed numbers and both modifier cause maps into the raw
+/// character sheet.
+pub fn apply_to_character_sheet(stats: &CharacterStats, sheet: &mut Map<String, Value>) {
+    for ability in &stats.abilities {
+        if let Some(Value::Object(e)) = sheet.get_mut(&ability.save_key) {
+            e.insert("value".to_string(), json!(ability.value));
+            e.insert("valueWithoutPerceptionsSubSkills".to_string(), json!(ability.value));
+            e.insert("maximumValue".to_string(), json!(ability.maximum_value));
+        }
+    }
+    for skill in &stats.skills {
+        if let Some(Value::Object(e)) = sheet.get_mut(&skill.save_key) {
+            e.insert("value".to_string(), json!(skill.value));
+            e.insert("valueWithoutPerceptionsSubSkills".to_string(), json!(skill.value));
+            e.insert("damageValue".to_string(), json!(skill.damage_value));
+            e.insert("maximumValue".to_string(), json!(skill.maximum_value));
+            e.insert("calculatedAbility".to_string(), json!(skill.base));
+            e.insert("rankValue".to_string(), json!(skill.rank_value));
+        }
+    }
+    sheet.insert("AbilityModifierCauseMap".to_string(), cause_map(&stats.abilities));
+    sheet.insert("SkillModifierCauseMap".to_string(), cause_map(&stats.skills));
+}
+
+/// A skill's modifiers from the sheet's `SkillModifierCauseMap`, with item,
+/// thought and ability causes named.
+pub fn skill_breakdown(sheet: &CharacterSheet, skill_type: &str, gd: &GameDataService) -> AppResult<SkillBreakdown> {
+    let mapping = gd.skill_key_map.find_by_skill_type(skill_type).ok_or_else(|| AppError::InvalidValue {
+        key: skill_type.to_string(),
+        message: "unknown skill".to_string(),
+    })?;
+    let entry = sheet.skills.get(&mapping.save_key).cloned().unwrap_or_default();
+
+    let modifiers: Vec<ModifierDisplay> = sheet.skill_modifier_cause_map.get(skill_type)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|m| ModifierDisplay {
+            modifier_type: m.modifier_type.clone(),
+            amount: m.amount,
+            explanation: m.explanation.clone(),
+            cause_key: m.modifier_cause.as_ref().map(|c| c.modifier_key.clone()),
+            cause_type: m.modifier_cause.as_ref().map(|c| c.modifier_cause_type.clone()),
+            cause_name: m.modifier_cause.as_ref().map(|c| cause_name(c, gd)),
+        })
+        .collect();
+    let modifier_sum = modifiers.iter().map(|m| m.amount).sum();
+
+    Ok(SkillBreakdown {
+        skill_type: skill_type.to_string(),
+        display_name: mapping.display_name.clone(),
+        calculated_ability: entry.calculated_ability,
+        rank_value: entry.rank_value,
+        modifiers,
+        modifier_sum,
+        total: entry.calculated_ability + entry.rank_value + modifier_sum,
+    })
+}
+
+/// The `+1 Half Light` style bonuses in an item's or thought's bonus text,
+/// as (amount, target name, explanation). Lines may list several bonuses
+/// separated by commas and end in a `: reason`; anything with a condition
+/// ("+2 Authority against men") names no skill and is left to the caller.
+pub fn parse_bonuses(text: &str) -> Vec<(i64, String, String)> {
+    let bonus = Regex::new(r"^([+-]\d+) (.+)$").expect("valid bonus pattern");
+    text.lines()
+        .map(|line| line.trim())
+        .map(|line| line.strip_prefix("Research:").unwrap_or(line))
+        .map(|line| line.split(':').next().unwrap_or_default().trim())
+        .flat_map(|head| head.split(", "))
+        .filter_map(|part| {
+            let captures = bonus.captures(part.trim())?;
+            let amount = captures[1].parse().ok()?;
+            let name = captures[2].trim_end_matches(" main attribute").to_string();
+            Some((amount, name, part.trim().to_string()))
+        })
+        .collect()
+}
+
+fn causes<'a>(inputs: &StatInputs<'a>) -> Vec<Cause<'a>> {
+    let previous = inputs.previous;
+    let items = inputs.items.iter().filter(|i| i.is_equipped).map(|item| Cause {
+        key: &item.name,
+        cause_type: ITEM_CAUSE,
+        modifier_type: "ITEM",
+        bonus_text: &item.bonus,
+        unchanged: previous.equipped_items.contains(&item.name),
+    });
+    let thoughts = inputs.thoughts.iter().filter_map(|thought| {
+        let (bonus_text, was) = match thought.state.as_str() {
+            "Processing" => (&thought.bonus_while_processing, &previous.cooking_thoughts),
+            "Internalized" => (&thought.bonus_when_completed, &previous.fixed_thoughts),
+            _ => return None,
+        };
+        Some(Cause {
+            key: &thought.name,
+            cause_type: THOUGHT_CAUSE,
+            modifier_type: "THC",
+            bonus_text,
+            unchanged: was.contains(&thought.name),
+        })
+    });
+    items.chain(thoughts).collect()
+}
+
+/// Bonus target names, lowercased: skill and ability display names, and
+/// ability codes as in "+1 PSY main attribute".
+fn targets(inputs: &StatInputs) -> HashMap<String, Target> {
+    let mut targets = HashMap::new();
+    for ability in inputs.abilities {
+        targets.insert(ability.display_name.to_lowercase(), Target::Ability(ability.type_code.clone()));
+        targets.insert(ability.type_code.to_lowercase(), Target::Ability(ability.type_code.clone()));
+    }
+    for skill in inputs.skills {
+        targets.insert(skill.display_name.to_lowercase(), Target::Skill(skill.type_code.clone()));
+    }
+    targets
+}
+
+/// Modifiers the editor doesn't derive, such as those of substances.
+fn other_modifiers<'a>(before: &'a [ModifierEntry], derived_types: &'a [&str]) -> impl Iterator<Item = ModifierEntry> + 'a {
+    before.iter()
+        .filter(move |m| !derived_types.contains(&m.modifier_type.as_str()))
+        .filter(|m| !m.modifier_cause.as_ref()
+            .is_some_and(|c| c.modifier_cause_type == ITEM_CAUSE || c.modifier_cause_type == THOUGHT_CAUSE))
+        .cloned()
+}
+
+fn has_cause(modifier: &ModifierEntry, cause_type: &str, key: &str) -> bool {
+    modifier.modifier_cause.as_ref()
+        .is_some_and(|c| c.modifier_cause_type == cause_type && c.modifier_key == key)
+}
+
+fn previous_explanation(before: &[ModifierEntry], modifier_type: &str) -> Option<String> {
+    before.iter().find(|m| m.modifier_type == modifier_type).map(|m| m.explanation.clone())
+}
+
+fn cause_name(cause: &ModifierCause, gd: &GameDataService) -> String {
+    let key = &cause.modifier_key;
+    let name = match cause.modifier_cause_type.as_str() {
+        ITEM_CAUSE => gd.items.get(key).map(|i| i.display_name.clone()),
+        THOUGHT_CAUSE => gd.thoughts.get(key).map(|t| t.display_name.clone()),
+        "ABILITY" => gd.skill_key_map.abilities.iter()
+            .find(|a| &a.skill_type == key)
+            .map(|a| a.display_name.clone()),
+        _ => None,
+    };
+    name.filter(|n| !n.is_empty()).unwrap_or_else(|| key.clone())
+}
+
+fn ability_cause(ability: &str) -> ModifierCause {
+    ModifierCause {
+        modifier_key: ability.to_string(),
+        modifier_cause_type: "ABILITY".to_string(),
+    }
+}
+
+fn cause_map(stats: &[StatBreakdown]) -> Value {
+    Value::Object(stats.iter().map(|stat| {
+        let modifiers = stat.modifiers.iter().map(|m| json!({
+            "type": m.modifier_type,
+            "amount": m.amount,
+            "explanation": m.explanation,
+            "skillType": m.skill_type,
+            "modifierCause": m.modifier_cause.as_ref().map(|c| json!({
+                "ModifierKey": c.modifier_key,
+                "ModifierCauseType": c.modifier_cause_type,
+            })),
+        })).collect();
+        (stat.type_code.clone(), Value::Array(modifiers))
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::inventory;
+    use crate::lua_database::ParseMode;
+    use crate::save_service::{self, LoadedSave};
+
+    struct Example {
+        loaded: LoadedSave,
+        abilities: Vec<AbilityDisplay>,
+        skills: Vec<SkillDisplay>,
+        items: Vec<InventoryItemDisplay>,
+        thoughts: Vec<ThoughtDisplay>,
+    }
+
+    /// The example save, with its sheet as the editor shows it.
+    fn example() -> Example {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut gd = GameDataService::default();
+        gd.load(&root.join("game_data").to_string_lossy()).unwrap();
+        let zip = root.join("../../example/MARTINAISE, DAY 1, 15-24(2_6_2026 4-25-35 PM).ntwtf.zip");
+        let loaded = save_service::load_save(&zip.to_string_lossy(), &gd, ParseMode::Strict).unwrap();
+        let cs = &loaded.character_sheet;
+
+        let abilities = cs.abilities.iter().map(|(key, entry)| {
+            let mapping = gd.skill_key_map.find_ability_by_save_key(key).unwrap();
+            AbilityDisplay {
+                save_key: key.clone(),
+                display_name: mapping.display_name.clone(),
+                type_code: mapping.skill_type.clone(),
+                value: entry.value,
+                maximum_value: entry.maximum_value,
+                is_signature: entry.is_signature,
+            }
+        }).collect();
+        let skills = cs.skills.iter().map(|(key, entry)| {
+            let mapping = gd.skill_key_map.find_by_save_key(key).unwrap();
+            SkillDisplay {
+                save_key: key.clone(),
+                display_name: mapping.display_name.clone(),
+                type_code: mapping.skill_type.clone(),
+                ability_type: mapping.ability.clone(),
+                description: String::new(),
+                value: entry.value,
+                maximum_value: entry.maximum_value,
+                calculated_ability: entry.calculated_ability,
+                rank_value: entry.rank_value,
+                has_advancement: entry.has_advancement,
+                is_signature: entry.is_signature,
+                modifier_count: 0,
+            }
+        }).collect();
+        let items = cs.gained_items.iter().map(|name| InventoryItemDisplay {
+            is_equipped: cs.equipped_items.contains(name),
+            ..inventory::item_display(name, &gd, None)
+        }).collect();
+        let thoughts = gd.thoughts.values().map(|t| ThoughtDisplay {
+            name: t.name.clone(),
+            display_name: t.display_name.clone(),
+            description: String::new(),
+            bonus_while_processing: t.bonus_while_processing.clone(),
+            bonus_when_completed: t.bonus_when_completed.clone(),
+            completion_description: String::new(),
+            thought_type: t.thought_type.clone(),
+            time_to_internalize: t.time_to_internalize,
+            requirement: String::new(),
+            is_cursed: false,
+            state: if cs.fixed_thoughts.contains(&t.name) {
+                "Internalized"
+            } else if cs.cooking_thoughts.contains(&t.name) {
+                "Processing"
+            } else {
+                "NotAcquired"
+            }.to_string(),
+            time_left: 0.0,
+            is_fresh: true,
+        }).collect();
+
+        Example { loaded, abilities, skills, items, thoughts }
+    }
+
+    impl Example {
+        fn compute(&self) -> CharacterStats {
+            compute(&StatInputs {
+                abilities: &self.abilities,
+                skills: &self.skills,
+                items: &self.items,
+                thoughts: &self.thoughts,
+                previous: &self.loaded.character_sheet,
+            })
+        }
+
+        fn raw_sheet(&self) -> Map<String, Value> {
+            self.loaded.second_raw["characterSheet"].as_object().unwrap().clone()
+        }
+
+        fn skill<'a>(&self, stats: &'a CharacterStats, type_code: &str) -> &'a StatBreakdown {
+            stats.skills.iter().find(|s| s.type_code == type_code).unwrap()
+        }
+
+        /// A skill type and the bonus a thought's text gives it.
+        fn bonus_target(&self, text: &str) -> Option<(String, i64)> {
+            parse_bonuses(text).into_iter().find_map(|(amount, name, _)| {
+                let skill = self.skills.iter().find(|s| s.display_name.eq_ignore_ascii_case(&name))?;
+                Some((skill.type_code.clone(), amount))
+            })
+        }
+    }
+
+    fn thought_modifiers(stat: &StatBreakdown, thought: &str) -> i64 {
+        stat.modifiers.iter().filter(|m| has_cause(m, THOUGHT_CAUSE, thought)).map(|m| m.amount).sum()
+    }
+
+    #[test]
+    fn recompute_without_edits_reproduces_the_save() {
+        let example = example();
+        let stats = example.compute();
+        let mut sheet = example.raw_sheet();
+        apply_to_character_sheet(&stats, &mut sheet);
+        assert_eq!(Value::Object(sheet), Value::Object(example.raw_sheet()));
+
+        let volition = example.skill(&stats, "VOLITION");
+        assert_eq!((volition.damage_value, volition.maximum_value), (-1, 5));
+    }
+
+    #[test]
+    fn adding_a_thought_adds_its_bonus() {
+        let mut example = example();
+        let before = example.compute();
+        let (index, skill, amount) = example.thoughts.iter().enumerate()
+            .filter(|(_, t)| t.state == "NotAcquired")
+            .find_map(|(i, t)| example.bonus_target(&t.bonus_when_completed).map(|(skill, amount)| (i, skill, amount)))
+            .unwrap();
+        example.thoughts[index].state = "Internalized".to_string();
+        let name = example.thoughts[index].name.clone();
+
+        let after = example.compute();
+        let stat = example.skill(&after, &skill);
+        assert_eq!(thought_modifiers(stat, &name), amount);
+        assert_eq!(stat.value, example.skill(&before, &skill).value + amount);
+    }
+
+    #[test]
+    fn removing_a_thought_removes_its_bonus() {
+        let mut example = example();
+        let before = example.compute();
+        assert_eq!(thought_modifiers(example.skill(&before, "PAIN_THRESHOLD"), "guillaume_le_million"), 1);
+
+        let thought = example.thoughts.iter_mut().find(|t| t.name == "guillaume_le_million").unwrap();
+        thought.state = "Forgotten".to_string();
+        let after = example.compute();
+        let stat = example.skill(&after, "PAIN_THRESHOLD");
+        assert_eq!(thought_modifiers(stat, "guillaume_le_million"), 0);
+        assert_eq!(stat.value, example.skill(&before, "PAIN_THRESHOLD").value - 1);
+    }
+
+    #[test]
+    fn changing_equipment_moves_item_bonuses() {
+        let mut example = example();
+        let before = example.compute();
+        let tie = example.items.iter().position(|i| i.name == "neck_tie").unwrap();
+        assert!(example.items[tie].is_equipped);
+
+        example.items[tie].is_equipped = false;
+        let after = example.compute();
+        let stat = example.skill(&after, "INLAND_EMPIRE");
+        assert!(!stat.modifiers.iter().any(|m| has_cause(m, ITEM_CAUSE, "neck_tie")));
+        assert_eq!(stat.value, example.skill(&before, "INLAND_EMPIRE").value - 1);
+
+        example.items[tie].is_equipped = true;
+        let again = example.compute();
+        assert_eq!(example.skill(&again, "INLAND_EMPIRE").value, example.skill(&before, "INLAND_EMPIRE").value);
+    }
+}
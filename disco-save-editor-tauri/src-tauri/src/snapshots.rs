@@ -0,0 +1,396 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use crate::atomic_write::{self, StagedFile};
+use crate::error::{AppError, AppResult, FileRole};
+use crate::models::*;
+use crate::save_service;
+use crate::settings::{self, Settings, SnapshotRetention, APP_IDENTIFIER};
+
+const INFO_FILE: &str = "snapshot.json";
+
+/// Versioned copies of saves, kept outside the game's save folder.
+///
+/// Layout: `<root>/<save name>-<path hash>/<snapshot id>/` holds `snapshot.json`
+/// and an exact copy of the save folder or zip under its original name, so a
+/// snapshot can be read with the same code as a live save.
+pub struct SnapshotStore {
+    root: PathBuf,
+    retention: SnapshotRetention,
+}
+
+impl SnapshotStore {
+    pub fn new(root: PathBuf, retention: SnapshotRetention) -> Self {
+        Self { root, retention }
+    }
+
+    pub fn from_settings(settings: &Settings) -> AppResult<Self> {
+        let root = match &settings.snapshot_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::data_dir()
+                .ok_or_else(|| AppError::InvalidPath("data directory".to_string()))?
+                .join(APP_IDENTIFIER)
+                .join("snapshots"),
+        };
+        Ok(Self::new(root, settings.snapshot_retention.clone()))
+    }
+
+    /// The store configured in the user's settings.
+    pub fn open() -> AppResult<Self> {
+        Self::from_settings(&settings::load_settings()?)
+    }
+
+    /// Snapshot the save as it is on disk. Unlabeled snapshots are skipped
+    /// when nothing changed since the latest one, which is returned instead.
+    pub fn take(&self, save_path: &str, label: Option<String>) -> AppResult<SnapshotInfo> {
+        let save = Path::new(save_path);
+        let files = read_save_files(save)?;
+        let hash = content_hash(&files);
+
+        if label.is_none() {
+            if let Some(latest) = self.list(save_path)?.into_iter().next() {
+                if latest.hash == hash {
+                    return Ok(latest);
+                }
+            }
+        }
+
+        let save_dir = self.save_dir(save_path);
+        let now = chrono::Utc::now();
+        let mut id = now.format("%Y%m%dT%H%M%S%3fZ").to_string();
+        let mut n = 1;
+        while save_dir.join(&id).exists() {
+            id = format!("{}-{}", now.format("%Y%m%dT%H%M%S%3fZ"), n);
+            n += 1;
+        }
+
+        // Build under a temporary name so a half-written snapshot is never listed
+        let partial = save_dir.join(format!(".{}.partial", id));
+        let snapshot_io = |e| AppError::io(FileRole::Snapshot, &partial, e);
+        std::fs::create_dir_all(&partial).map_err(snapshot_io)?;
+        let copy = partial.join(save.file_name().ok_or_else(|| AppError::InvalidPath(save_path.to_string()))?);
+        let size = copy_save(save, &copy).map_err(snapshot_io)?;
+
+        let info = SnapshotInfo {
+            id: id.clone(),
+            created_at: now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            hash,
+            label: label.filter(|l| !l.trim().is_empty()),
+            save_path: save_path.to_string(),
+            size,
+            details: save_service::read_save_details(save_path).ok(),
+        };
+        let json = serde_json::to_string_pretty(&info)
+            .map_err(|e| AppError::Json { role: FileRole::Snapshot, path: partial.display().to_string(), json_path: String::new(), source: e })?;
+        std::fs::write(partial.join(INFO_FILE), json).map_err(snapshot_io)?;
+        std::fs::rename(&partial, save_dir.join(&id)).map_err(snapshot_io)?;
+
+        Ok(info)
+    }
+
+    /// Snapshots of a save, newest first.
+    pub fn list(&self, save_path: &str) -> AppResult<Vec<SnapshotInfo>> {
+        let save_dir = self.save_dir(save_path);
+        let entries = match std::fs::read_dir(&save_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AppError::io(FileRole::Snapshot, &save_dir, e)),
+        };
+
+        let mut snapshots: Vec<SnapshotInfo> = entries.flatten()
+            .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|e| std::fs::read_to_string(e.path().join(INFO_FILE)).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(snapshots)
+    }
+
+    /// Compare a snapshot with a later one, or with the save on disk when `to` is None.
+    pub fn diff(&self, save_path: &str, from: &str, to: Option<&str>) -> AppResult<SnapshotDiff> {
+        let from_info = self.get(save_path, from)?;
+        let from_copy = self.copy_path(save_path, from);
+        let (to_info, to_path) = match to {
+            Some(id) => (Some(self.get(save_path, id)?), self.copy_path(save_path, id)),
+            None => (None, PathBuf::from(save_path)),
+        };
+
+        let before = read_save_files(&from_copy)?;
+        let after = read_save_files(&to_path)?;
+        let mut files = Vec::new();
+        for (name, content) in &before {
+            match after.get(name) {
+                None => files.push(FileChange { name: name.clone(), change: FileChangeKind::Removed }),
+                Some(other) if other != content => files.push(FileChange { name: name.clone(), change: FileChangeKind::Modified }),
+                Some(_) => {}
+            }
+        }
+        for name in after.keys().filter(|name| !before.contains_key(*name)) {
+            files.push(FileChange { name: name.clone(), change: FileChangeKind::Added });
+        }
+
+        Ok(SnapshotDiff {
+            to_details: match &to_info {
+                Some(info) => info.details.clone(),
+                None => save_service::read_save_details(save_path).ok(),
+            },
+            from: from_info,
+            to: to_info,
+            files,
+        })
+    }
+
+    /// Put a snapshot back in place of the save. The current state is
+    /// snapshotted first, so a restore can itself be undone. Files of a
+    /// folder save that the snapshot doesn't have are removed.
+    pub fn restore(&self, save_path: &str, id: &str) -> AppResult<SnapshotInfo> {
+        let info = self.get(save_path, id)?;
+        self.take(save_path, Some(format!("Before restoring {}", id)))?;
+
+        let copy = self.copy_path(save_path, id);
+        let save = Path::new(save_path);
+        let name = save.file_name().unwrap_or_default().to_string_lossy();
+        let base = save_service::save_base_name(&name);
+        let mut contents = Vec::new();
+        let mut removed = Vec::new();
+        if copy.is_dir() {
+            let prefix = format!("{}.", base);
+            let snapshot_files: HashSet<String> = list_files(&copy)
+                .map_err(|e| AppError::io(FileRole::Snapshot, &copy, e))?
+                .into_iter().map(|(name, _)| name).collect();
+            for (name, file) in list_files(save).map_err(|e| AppError::io(FileRole::Archive, save, e))? {
+                if name.starts_with(&prefix) && !snapshot_files.contains(&name) {
+                    removed.push(file);
+                }
+            }
+            for (name, _) in list_files(&copy).map_err(|e| AppError::io(FileRole::Snapshot, &copy, e))? {
+                let content = std::fs::read(copy.join(&name)).map_err(|e| AppError::io(FileRole::Snapshot, copy.join(&name), e))?;
+                contents.push((save.join(name), content));
+            }
+        } else {
+            let content = std::fs::read(&copy).map_err(|e| AppError::io(FileRole::Snapshot, &copy, e))?;
+            contents.push((save.to_path_buf(), content));
+        }
+
+        let staged: Vec<StagedFile> = contents.iter()
+            .map(|(path, content)| StagedFile { path: path.clone(), content, role: FileRole::Snapshot })
+            .collect();
+        atomic_write::replace_and_remove(base, &staged, &removed)?;
+        Ok(info)
+    }
+
+    /// Delete the snapshots the retention policy doesn't keep. Returns what was removed.
+    pub fn prune(&self, save_path: &str) -> AppResult<Vec<SnapshotInfo>> {
+        let snapshots = self.list(save_path)?;
+        let mut days_kept = HashSet::new();
+        let mut removed = Vec::new();
+
+        for (i, snapshot) in snapshots.into_iter().enumerate() {
+            let day = snapshot.id.get(..8).unwrap_or_default().to_string();
+            let keep_for_day = days_kept.len() < self.retention.keep_daily && !days_kept.contains(&day);
+            if keep_for_day {
+                days_kept.insert(day);
+            }
+
+            let keep = i < self.retention.keep_last
+                || keep_for_day
+                || (self.retention.keep_labeled && snapshot.label.is_some());
+            if !keep {
+                let dir = self.save_dir(save_path).join(&snapshot.id);
+                std::fs::remove_dir_all(&dir).map_err(|e| AppError::io(FileRole::Snapshot, &dir, e))?;
+                removed.push(snapshot);
+            }
+        }
+        Ok(removed)
+    }
+
+    fn get(&self, save_path: &str, id: &str) -> AppResult<SnapshotInfo> {
+        self.list(save_path)?.into_iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| AppError::UnknownSnapshot(id.to_string()))
+    }
+
+    /// The copy of the save inside a snapshot.
+    fn copy_path(&self, save_path: &str, id: &str) -> PathBuf {
+        let name = Path::new(save_path).file_name().unwrap_or_default();
+        self.save_dir(save_path).join(id).join(name)
+    }
+
+    /// One folder per save, named after it and a hash of its full path so
+    /// same-named saves in different folders don't mix.
+    fn save_dir(&self, save_path: &str) -> PathBuf {
+        let path = std::fs::canonicalize(save_path).unwrap_or_else(|_| PathBuf::from(save_path));
+        let path_hash = hex(&Sha256::digest(path.to_string_lossy().as_bytes()));
+        let name = Path::new(save_path).file_name().unwrap_or_default().to_string_lossy();
+        self.root.join(format!("{}-{}", save_service::save_base_name(&name), &path_hash[..12]))
+    }
+}
+
+/// A save's files by name: a folder's regular files, or a zip's entries uncompressed.
+fn read_save_files(path: &Path) -> AppResult<BTreeMap<String, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    if path.is_dir() {
+        for (name, file) in list_files(path).map_err(|e| AppError::io(FileRole::Snapshot, path, e))? {
+            let content = std::fs::read(&file).map_err(|e| AppError::io(FileRole::Snapshot, &file, e))?;
+            files.insert(name, content);
+        }
+    } else {
+        let file = std::fs::File::open(path).map_err(|e| AppError::io(FileRole::Archive, path, e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| AppError::zip(path, e))?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(|e| AppError::zip(path, e))?;
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).map_err(|e| AppError::io(FileRole::Archive, path, e))?;
+            files.insert(entry.name().to_string(), content);
+        }
+    }
+    Ok(files)
+}
+
+fn list_files(dir: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.push((entry.file_name().to_string_lossy().to_string(), entry.path()));
+        }
+    }
+    Ok(files)
+}
+
+/// Copy a save folder's files or a zip, returning the bytes copied.
+fn copy_save(from: &Path, to: &Path) -> std::io::Result<u64> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        let mut size = 0;
+        for (name, file) in list_files(from)? {
+            size += std::fs::copy(file, to.join(name))?;
+        }
+        Ok(size)
+    } else {
+        std::fs::copy(from, to)
+    }
+}
+
+fn content_hash(files: &BTreeMap<String, Vec<u8>>) -> String {
+    let mut hasher = Sha256::new();
+    for (name, content) in files {
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(content);
+    }
+    hex(&hasher.finalize())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A folder save with two files, and a store next to it.
+    fn setup(retention: SnapshotRetention) -> (tempfile::TempDir, String, SnapshotStore) {
+        let temp = tempfile::tempdir().unwrap();
+        let save = temp.path().join("SAVE.ntwtf");
+        std::fs::create_dir(&save).unwrap();
+        std::fs::write(save.join("SAVE.1st.json"), "first").unwrap();
+        std::fs::write(save.join("SAVE.2nd.json"), "second").unwrap();
+        let store = SnapshotStore::new(temp.path().join("snapshots"), retention);
+        (temp, save.display().to_string(), store)
+    }
+
+    /// A snapshot with a chosen id, for retention tests.
+    fn fake(store: &SnapshotStore, save_path: &str, id: &str, label: Option<&str>) {
+        let dir = store.save_dir(save_path).join(id);
+        std::fs::create_dir_all(&dir).unwrap();
+        let info = SnapshotInfo {
+            id: id.to_string(),
+            created_at: String::new(),
+            hash: String::new(),
+            label: label.map(str::to_string),
+            save_path: save_path.to_string(),
+            size: 0,
+            details: None,
+        };
+        std::fs::write(dir.join(INFO_FILE), serde_json::to_string(&info).unwrap()).unwrap();
+    }
+
+    fn ids(snapshots: &[SnapshotInfo]) -> Vec<&str> {
+        snapshots.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn lists_snapshots_newest_first() {
+        let (_temp, save, store) = setup(SnapshotRetention::default());
+        let first = store.take(&save, None).unwrap();
+        std::fs::write(Path::new(&save).join("SAVE.1st.json"), "changed").unwrap();
+        let second = store.take(&save, None).unwrap();
+
+        assert_ne!(first.hash, second.hash);
+        let listed = store.list(&save).unwrap();
+        assert_eq!(ids(&listed), [second.id.as_str(), first.id.as_str()]);
+        let copy = store.copy_path(&save, &first.id);
+        assert_eq!(std::fs::read(copy.join("SAVE.1st.json")).unwrap(), b"first");
+    }
+
+    #[test]
+    fn unchanged_save_is_snapshotted_once() {
+        let (_temp, save, store) = setup(SnapshotRetention::default());
+        let first = store.take(&save, None).unwrap();
+        let again = store.take(&save, None).unwrap();
+
+        assert_eq!(first.id, again.id);
+        assert_eq!(first.hash, content_hash(&read_save_files(Path::new(&save)).unwrap()));
+        assert_eq!(first.hash.len(), 64);
+        assert_eq!(store.list(&save).unwrap().len(), 1);
+
+        let labeled = store.take(&save, Some("keep".to_string())).unwrap();
+        assert_ne!(labeled.id, first.id);
+        assert_eq!(store.list(&save).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn prune_keeps_recent_daily_and_labeled() {
+        let retention = SnapshotRetention { keep_last: 2, keep_daily: 2, keep_labeled: true };
+        let (_temp, save, store) = setup(retention.clone());
+        fake(&store, &save, "20260103T120000000Z", None);
+        fake(&store, &save, "20260103T110000000Z", None);
+        fake(&store, &save, "20260102T120000000Z", None);
+        fake(&store, &save, "20260102T110000000Z", Some("labeled"));
+        fake(&store, &save, "20260101T120000000Z", None);
+
+        let removed = store.prune(&save).unwrap();
+        assert_eq!(ids(&removed), ["20260101T120000000Z"]);
+        assert_eq!(store.list(&save).unwrap().len(), 4);
+
+        let store = SnapshotStore::new(store.root.clone(), SnapshotRetention { keep_labeled: false, ..retention });
+        let removed = store.prune(&save).unwrap();
+        assert_eq!(ids(&removed), ["20260102T110000000Z"]);
+    }
+
+    #[test]
+    fn restore_puts_the_snapshot_back() {
+        let (_temp, save, store) = setup(SnapshotRetention::default());
+        let dir = Path::new(&save);
+        let snapshot = store.take(&save, None).unwrap();
+        std::fs::write(dir.join("SAVE.1st.json"), "changed").unwrap();
+        std::fs::write(dir.join("SAVE.FOW.json"), "added").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not the save's").unwrap();
+
+        store.restore(&save, &snapshot.id).unwrap();
+
+        let mut names: Vec<String> = list_files(dir).unwrap().into_iter().map(|(name, _)| name).collect();
+        names.sort();
+        assert_eq!(names, ["SAVE.1st.json", "SAVE.2nd.json", "notes.txt"]);
+        assert_eq!(std::fs::read(dir.join("SAVE.1st.json")).unwrap(), b"first");
+
+        let before = store.list(&save).unwrap().into_iter().next().unwrap();
+        assert_eq!(before.label.as_deref(), Some(format!("Before restoring {}", snapshot.id).as_str()));
+        let saved = store.copy_path(&save, &before.id);
+        assert_eq!(std::fs::read(saved.join("SAVE.FOW.json")).unwrap(), b"added");
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use regex::Regex;
 use crate::models::StatesData;
 
@@ -43,3 +44,55 @@ pub fn serialize_states(data: &StatesData) -> String {
 
     sb
 }
+
+/// Serialize states as edits of the file's original text: changed values
+/// are spliced in place, removed entries are taken out with their line, and
+/// new ones are appended. Untouched entries keep their exact text, so an
+/// unedited file comes back byte for byte.
+pub fn rewrite_states(original: &str, data: &StatesData) -> String {
+    let entry_re = Regex::new(r#"(AreaState|ShownOrbs)\["(.+?)"\]\s*=\s*\{(LocationState|OrbSeen)=(\d+)\};?[ \t]*(\r?\n)?"#).unwrap();
+
+    let mut out = String::with_capacity(original.len());
+    let mut seen: HashSet<(bool, String)> = HashSet::new();
+    let mut last = 0;
+    for cap in entry_re.captures_iter(original) {
+        let whole = cap.get(0).unwrap();
+        let is_area = &cap[1] == "AreaState";
+        let key = cap[2].to_string();
+        let map = if is_area { &data.area_states } else { &data.shown_orbs };
+        out.push_str(&original[last..whole.start()]);
+        match map.get(&key) {
+            Some(value) if value.to_string() == cap[4] => out.push_str(whole.as_str()),
+            Some(value) => {
+                let number = cap.get(4).unwrap();
+                out.push_str(&original[whole.start()..number.start()]);
+                out.push_str(&value.to_string());
+                out.push_str(&original[number.end()..whole.end()]);
+            }
+            None => {}
+        }
+        last = whole.end();
+        seen.insert((is_area, key));
+    }
+    out.push_str(&original[last..]);
+
+    let mut added: Vec<(bool, &String, i64)> = data.area_states.iter().map(|(k, v)| (true, k, *v))
+        .chain(data.shown_orbs.iter().map(|(k, v)| (false, k, *v)))
+        .filter(|(is_area, key, _)| !seen.contains(&(*is_area, key.to_string())))
+        .collect();
+    added.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    let newline = if original.contains("\r\n") { "\r\n" } else { "\n" };
+    if !added.is_empty() && !out.is_empty() && !out.ends_with('\n') {
+        out.push_str(newline);
+    }
+    for (is_area, key, value) in added {
+        let line = if is_area {
+            format!("AreaState[\"{}\"]={{LocationState={}}};", key, value)
+        } else {
+            format!("ShownOrbs[\"{}\"]={{OrbSeen={}}};", key, value)
+        };
+        out.push_str(&line);
+        out.push_str(newline);
+    }
+    out
+}
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use serde_json::{json, Value};
+use crate::error::{AppError, AppResult};
+use crate::models::*;
+
+/// The cabinet's slots as the save has them.
+pub fn build_cabinet(view: &ThoughtCabinetViewState) -> ThoughtCabinetDisplay {
+    ThoughtCabinetDisplay {
+        slots: view.slot_states.iter().map(|slot| {
+            let status = match slot.item1.as_str() {
+                "FILLED" => SlotStatus::Filled,
+                "EMPTY" => SlotStatus::Empty,
+                "BUYABLE" => SlotStatus::Buyable,
+                _ => SlotStatus::Locked,
+            };
+            CabinetSlot {
+                status,
+                thought: slot.item2.clone().filter(|t| status == SlotStatus::Filled && !t.is_empty()),
+            }
+        }).collect(),
+        selected_project_name: view.selected_project_name.clone(),
+    }
+}
+
+/// Open the next slot, preferring ones the player could already buy.
+pub fn unlock_slot(cabinet: &mut ThoughtCabinetDisplay) -> AppResult<()> {
+    let index = cabinet.slots.iter().position(|s| s.status == SlotStatus::Buyable)
+        .or_else(|| cabinet.slots.iter().position(|s| s.status == SlotStatus::Locked))
+        .ok_or_else(|| invalid("every slot is already unlocked"))?;
+    cabinet.slots[index].status = SlotStatus::Empty;
+    Ok(())
+}
+
+/// Close the last empty slot; it can be bought back in game.
+pub fn lock_slot(cabinet: &mut ThoughtCabinetDisplay) -> AppResult<()> {
+    let slot = cabinet.slots.iter_mut().rev().find(|s| s.status == SlotStatus::Empty)
+        .ok_or_else(|| invalid("no empty slot to lock"))?;
+    slot.status = SlotStatus::Buyable;
+    Ok(())
+}
+
+/// Check that every cooking and fixed thought has an unlocked slot.
+pub fn validate(cabinet: &ThoughtCabinetDisplay, thoughts: &[ThoughtDisplay]) -> AppResult<()> {
+    place_thoughts(cabinet, thoughts).map(|_| ())
+}
+
+/// The slots with the current cooking and fixed thoughts in them. Thoughts
+/// keep the slot they're in; slots of thoughts that left the cabinet are
+/// emptied, and new thoughts take the first empty slots.
+pub fn place_thoughts(cabinet: &ThoughtCabinetDisplay, thoughts: &[ThoughtDisplay]) -> AppResult<Vec<CabinetSlot>> {
+    let in_cabinet: Vec<&str> = thoughts.iter()
+        .filter(|t| matches!(t.state.as_str(), "Processing" | "Internalized"))
+        .map(|t| t.name.as_str())
+        .collect();
+
+    let mut slots = cabinet.slots.clone();
+    let mut placed: HashSet<String> = HashSet::new();
+    for slot in slots.iter_mut().filter(|s| s.status == SlotStatus::Filled) {
+        match slot.thought.take() {
+            Some(thought) if in_cabinet.contains(&thought.as_str()) && !placed.contains(&thought) => {
+                placed.insert(thought.clone());
+                slot.thought = Some(thought);
+            }
+            _ => slot.status = SlotStatus::Empty,
+        }
+    }
+
+    for thought in in_cabinet.iter().filter(|t| !placed.contains(**t)) {
+        let slot = slots.iter_mut().find(|s| s.status == SlotStatus::Empty).ok_or_else(|| {
+            let unlocked = cabinet.slots.iter().filter(|s| s.status.is_unlocked()).count();
+            invalid(&format!(
+                "{} thoughts are cooking or fixed but only {} slots are unlocked",
+                in_cabinet.len(),
+                unlocked,
+            ))
+        })?;
+        slot.status = SlotStatus::Filled;
+        slot.thought = Some(thought.to_string());
+    }
+    Ok(slots)
+}
+
+/// Write thought states and cabinet slots back into the raw 2nd file.
+/// Entries of `thoughtListState` are updated in place so `isFresh` and
+/// thoughts the player hasn't found yet survive.
+pub fn apply_to_second_raw(
+    thoughts: &[ThoughtDisplay],
+    slots: &[CabinetSlot],
+    selected_project_name: &str,
+    second_raw: &mut Value,
+) {
+    let Some(Value::Object(cabinet)) = second_raw.get_mut("thoughtCabinetState") else { return };
+
+    let list = cabinet.entry("thoughtListState".to_string()).or_insert_with(|| json!([]));
+    if let Value::Array(list) = list {
+        for thought in thoughts {
+            let existing = list.iter_mut()
+                .find(|entry| entry.get("name").and_then(Value::as_str) == Some(thought.name.as_str()));
+            match existing {
+                Some(Value::Object(entry)) => {
+                    entry.insert("state".to_string(), json!(game_state(&thought.state)));
+                    entry.insert("timeLeft".to_string(), json!(thought.time_left));
+                    entry.insert("isFresh".to_string(), json!(thought.is_fresh));
+                }
+                None if thought.state != "NotAcquired" => list.push(json!({
+                    "name": thought.name,
+                    "isFresh": thought.is_fresh,
+                    "state": game_state(&thought.state),
+                    "timeLeft": thought.time_left,
+                })),
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(Value::Object(view)) = cabinet.get_mut("thoughtCabinetViewState") {
+        let slot_states: Vec<Value> = slots.iter().map(|slot| json!({
+            "Item1": slot.status,
+            "Item2": slot.thought,
+        })).collect();
+        view.insert("slotStates".to_string(), Value::Array(slot_states));
+        view.insert("selectedProjectName".to_string(), json!(selected_project_name));
+    }
+}
+
+fn game_state(state: &str) -> &'static str {
+    match state {
+        "Internalized" => "FIXED",
+        "Processing" => "COOKING",
+        "Forgotten" => "FORGOTTEN",
+        "NotAcquired" => "UNKNOWN",
+        _ => "KNOWN",
+    }
+}
+
+fn invalid(message: &str) -> AppError {
+    AppError::InvalidValue { key: "thought_cabinet".to_string(), message: message.to_string() }
+}
@@ -0,0 +1,217 @@
+use serde_json::{json, Value};
+use crate::error::{AppError, AppResult};
+use crate::models::*;
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// The clock as the editor shows it, from `sunshineClockTimeHolder`. An
+/// override the editor can't read is shown as none and left as it is.
+pub fn clock(holder: &SunshineClockTimeHolder) -> GameClock {
+    let time = &holder.time;
+    GameClock {
+        day: time.day_counter,
+        real_day: time.real_day_counter,
+        hours: time.hours(),
+        minutes: time.minutes(),
+        seconds: time.seconds,
+        time_override: read_override(&holder.time_override),
+    }
+}
+
+pub fn validate(clock: &GameClock) -> AppResult<()> {
+    if !(0..24).contains(&clock.hours) {
+        return Err(invalid("hours", "must be between 0 and 23"));
+    }
+    if !(0..60).contains(&clock.minutes) {
+        return Err(invalid("minutes", "must be between 0 and 59"));
+    }
+    validate_time(&timestamp(clock), "")?;
+    if let Some(time_override) = &clock.time_override {
+        validate_time(time_override, "time_override.")?;
+    }
+    Ok(())
+}
+
+/// Let `minutes` of game time pass: the clock rolls over into the next days,
+/// cooking thoughts and active substances count down, and Kim's time asleep
+/// in his room counts up while he is there. A thought whose timer runs out
+/// is internalized.
+pub fn advance(
+    clock: &mut GameClock,
+    thoughts: &mut [ThoughtDisplay],
+    items: &mut [InventoryItemDisplay],
+    party: &mut PartyState,
+    minutes: i64,
+) -> AppResult<()> {
+    if minutes <= 0 {
+        return Err(invalid("minutes", "must be more than 0"));
+    }
+    validate(clock)?;
+
+    let total = clock.hours * 60 + clock.minutes + minutes;
+    let days = total / MINUTES_PER_DAY;
+    clock.day += days;
+    clock.real_day += days;
+    clock.hours = total % MINUTES_PER_DAY / 60;
+    clock.minutes = total % 60;
+
+    for thought in thoughts.iter_mut().filter(|t| t.state == "Processing") {
+        thought.time_left = (thought.time_left - minutes as f64).max(0.0);
+        if thought.time_left == 0.0 {
+            thought.state = "Internalized".to_string();
+        }
+    }
+    for item in items.iter_mut().filter(|i| i.is_owned && i.is_substance) {
+        item.substance_time_left = (item.substance_time_left - minutes).max(0);
+    }
+    if party.is_kim_sleeping_in_his_room {
+        party.time_since_kim_went_sleeping += minutes;
+    }
+    Ok(())
+}
+
+/// Write the clock into the raw 2nd file's `sunshineClockTimeHolder`. The
+/// override is only written when it was changed, so one the editor can't
+/// read survives.
+pub fn apply_to_second_raw(clock: &GameClock, second_raw: &mut Value) {
+    let Value::Object(obj) = second_raw else { return };
+    let holder_key = if obj.contains_key("sunshineClockTimeHolder") { "sunshineClockTimeHolder" } else { "SunshineClockTimeHolder" };
+    let Some(Value::Object(holder)) = obj.get_mut(holder_key) else { return };
+
+    if let Some(Value::Object(time)) = holder.get_mut("time") {
+        let stamp = timestamp(clock);
+        time.insert("dayCounter".to_string(), json!(stamp.day_counter));
+        time.insert("realDayCounter".to_string(), json!(stamp.real_day_counter));
+        time.insert("dayMinutes".to_string(), json!(stamp.day_minutes));
+        time.insert("seconds".to_string(), json!(stamp.seconds));
+    }
+
+    let before = holder.get("timeOverride").and_then(read_override);
+    if before != clock.time_override {
+        let time_override = match &clock.time_override {
+            Some(stamp) => json!({
+                "dayCounter": stamp.day_counter,
+                "realDayCounter": stamp.real_day_counter,
+                "dayMinutes": stamp.day_minutes,
+                "seconds": stamp.seconds,
+            }),
+            None => Value::Null,
+        };
+        holder.insert("timeOverride".to_string(), time_override);
+    }
+}
+
+fn timestamp(clock: &GameClock) -> GameTimestamp {
+    GameTimestamp {
+        day_counter: clock.day,
+        real_day_counter: clock.real_day,
+        day_minutes: clock.hours * 60 + clock.minutes,
+        seconds: clock.seconds,
+    }
+}
+
+fn read_override(value: &Value) -> Option<GameTimestamp> {
+    if value.is_null() {
+        return None;
+    }
+    serde_json::from_value(value.clone()).ok()
+}
+
+fn validate_time(time: &GameTimestamp, prefix: &str) -> AppResult<()> {
+    if time.day_counter < 1 || time.real_day_counter < 1 {
+        return Err(invalid(&format!("{prefix}day"), "days start at 1"));
+    }
+    if !(0..MINUTES_PER_DAY).contains(&time.day_minutes) {
+        return Err(invalid(&format!("{prefix}hours"), "time of day must be between 00:00 and 23:59"));
+    }
+    if !(0..60).contains(&time.seconds) {
+        return Err(invalid(&format!("{prefix}seconds"), "must be between 0 and 59"));
+    }
+    Ok(())
+}
+
+fn invalid(key: &str, message: &str) -> AppError {
+    AppError::InvalidValue { key: key.to_string(), message: message.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock_at(day: i64, hours: i64, minutes: i64) -> GameClock {
+        GameClock { day, real_day: day, hours, minutes, seconds: 0, time_override: None }
+    }
+
+    fn thought(state: &str, time_left: f64) -> ThoughtDisplay {
+        ThoughtDisplay {
+            name: "thought".to_string(),
+            display_name: String::new(),
+            description: String::new(),
+            bonus_while_processing: String::new(),
+            bonus_when_completed: String::new(),
+            completion_description: String::new(),
+            thought_type: String::new(),
+            time_to_internalize: 120.0,
+            requirement: String::new(),
+            is_cursed: false,
+            state: state.to_string(),
+            time_left,
+            is_fresh: false,
+        }
+    }
+
+    fn substance(is_owned: bool, time_left: i64) -> InventoryItemDisplay {
+        InventoryItemDisplay {
+            name: "substance".to_string(),
+            display_name: String::new(),
+            description: String::new(),
+            bonus: String::new(),
+            is_owned,
+            is_equipped: false,
+            equip_slot: String::new(),
+            is_quest_item: false,
+            is_cursed: false,
+            is_substance: true,
+            substance_uses: 1,
+            substance_time_left: time_left,
+            is_fresh: false,
+            stack_name: String::new(),
+        }
+    }
+
+    #[test]
+    fn rolls_over_into_the_next_days() {
+        let mut clock = clock_at(1, 23, 30);
+        advance(&mut clock, &mut [], &mut [], &mut PartyState::default(), 45).unwrap();
+        assert_eq!(clock, clock_at(2, 0, 15));
+
+        advance(&mut clock, &mut [], &mut [], &mut PartyState::default(), 2 * MINUTES_PER_DAY + 60).unwrap();
+        assert_eq!(clock, clock_at(4, 1, 15));
+    }
+
+    #[test]
+    fn internalizes_thoughts_whose_timer_runs_out() {
+        let mut thoughts = [thought("Processing", 30.0), thought("Processing", 90.0), thought("Known", 10.0)];
+        advance(&mut clock_at(1, 12, 0), &mut thoughts, &mut [], &mut PartyState::default(), 60).unwrap();
+
+        assert_eq!((thoughts[0].state.as_str(), thoughts[0].time_left), ("Internalized", 0.0));
+        assert_eq!((thoughts[1].state.as_str(), thoughts[1].time_left), ("Processing", 30.0));
+        assert_eq!((thoughts[2].state.as_str(), thoughts[2].time_left), ("Known", 10.0));
+    }
+
+    #[test]
+    fn substance_timers_stop_at_zero() {
+        let mut items = [substance(true, 30), substance(true, 120), substance(false, 30)];
+        let mut party = PartyState { is_kim_sleeping_in_his_room: true, time_since_kim_went_sleeping: 5, ..PartyState::default() };
+        advance(&mut clock_at(1, 12, 0), &mut [], &mut items, &mut party, 60).unwrap();
+
+        let left: Vec<i64> = items.iter().map(|i| i.substance_time_left).collect();
+        assert_eq!(left, [0, 60, 30]);
+        assert_eq!(party.time_since_kim_went_sleeping, 65);
+    }
+
+    #[test]
+    fn rejects_no_time_passing() {
+        assert!(advance(&mut clock_at(1, 12, 0), &mut [], &mut [], &mut PartyState::default(), 0).is_err());
+    }
+}
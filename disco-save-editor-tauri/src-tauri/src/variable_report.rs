@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+use crate::game_data::GameDataService;
+use crate::lua_database;
+use crate::lua_index::variable_display;
+use crate::models::*;
+
+/// Compare a save's Lua variables with the game's defaults: every variable
+/// whose value differs from its `initial_value`, grouped by the prefix before
+/// its first dot (the area for location flags), and every variable in the
+/// save's variable table that the game data doesn't define.
+pub fn changed_from_default(lua_db: &LuaDatabase, game_data: &GameDataService) -> VariableReport {
+    let mut groups: BTreeMap<String, Vec<LuaVariableDisplay>> = BTreeMap::new();
+    for (key, value) in lua_database::flatten_lua(lua_db) {
+        let display = variable_display(key, &value, game_data);
+        if display.differs_from_initial {
+            groups.entry(variable_prefix(&display.key).to_string()).or_default().push(display);
+        }
+    }
+    let groups: Vec<VariableGroup> = groups.into_iter()
+        .map(|(prefix, mut variables)| {
+            variables.sort_by(|a, b| a.key.cmp(&b.key));
+            VariableGroup { prefix, variables }
+        })
+        .collect();
+
+    // Variables live as scalars directly in a top-level table; nested tables
+    // are actor, item and conversation records, and `Conversation_SimX_*`
+    // entries are the dialogue system's per-conversation visit records.
+    let mut unknown: Vec<LuaVariableDisplay> = lua_db.tables()
+        .flat_map(|table| &table.entries)
+        .filter(|(_, value)| !matches!(value, LuaValue::Table(_)))
+        .map(|(key, value)| (key.to_display_string(), value))
+        .filter(|(key, _)| !key.starts_with("Conversation_SimX_") && !game_data.all_variables.contains_key(key))
+        .map(|(key, value)| variable_display(key, value, game_data))
+        .collect();
+    unknown.sort_by(|a, b| a.key.cmp(&b.key));
+    unknown.dedup_by(|a, b| a.key == b.key);
+
+    VariableReport {
+        changed_count: groups.iter().map(|g| g.variables.len()).sum(),
+        groups,
+        unknown,
+    }
+}
+
+fn variable_prefix(key: &str) -> &str {
+    key.split_once('.').map_or(key, |(prefix, _)| prefix)
+}
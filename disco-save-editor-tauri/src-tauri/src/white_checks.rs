@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use serde_json::{json, Map, Value};
+use crate::error::{AppError, AppResult};
+use crate::game_data::GameDataService;
+use crate::models::*;
+
+/// The three white check caches, each sorted by flag name.
+pub fn build_white_checks_state(holder: &FailedWhiteChecksHolder, gd: &GameDataService) -> WhiteChecksState {
+    let mut failed: Vec<WhiteCheckDisplay> = holder.white_check_cache.iter()
+        .filter_map(|(key, element)| {
+            let check: WhiteCheck = serde_json::from_value(element.clone()).ok()?;
+            Some(check_display(key, &check, false, gd))
+        })
+        .collect();
+
+    let mut reopened: Vec<WhiteCheckDisplay> = holder.reopened_white_checks.values()
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(|element| serde_json::from_value::<WhiteCheck>(element.clone()).ok())
+        .map(|check| check_display(&check.flag_name, &check, false, gd))
+        .collect();
+
+    let mut seen: Vec<WhiteCheckDisplay> = holder.seen_white_check_cache.iter()
+        .map(|(key, check)| check_display(key, check, check.is_only_seen, gd))
+        .collect();
+
+    for checks in [&mut failed, &mut reopened, &mut seen] {
+        checks.sort_by(|a, b| a.key.cmp(&b.key));
+    }
+    WhiteChecksState { failed, reopened, seen }
+}
+
+/// Give a failed check another try, the way raising its skill does in game.
+pub fn reopen_check(checks: &mut WhiteChecksState, key: &str) -> AppResult<()> {
+    let index = checks.failed.iter().position(|c| c.key == key)
+        .ok_or_else(|| invalid(key, "check is not failed"))?;
+    let check = checks.failed.remove(index);
+    insert_sorted(&mut checks.reopened, check);
+    Ok(())
+}
+
+/// Mark a seen or reopened check as failed.
+pub fn fail_check(checks: &mut WhiteChecksState, key: &str) -> AppResult<()> {
+    if checks.failed.iter().any(|c| c.key == key) {
+        return Err(invalid(key, "check is already failed"));
+    }
+    let mut check = take_check(&mut checks.reopened, key)
+        .or_else(|| take_check(&mut checks.seen, key))
+        .ok_or_else(|| invalid(key, "check is not in the save"))?;
+    check.is_seen_only = false;
+    insert_sorted(&mut checks.failed, check);
+    Ok(())
+}
+
+pub fn edit_check(
+    checks: &mut WhiteChecksState,
+    key: &str,
+    difficulty: i64,
+    last_skill_value: i64,
+    last_target_value: i64,
+) -> AppResult<()> {
+    for (name, value) in [("difficulty", difficulty), ("last_skill_value", last_skill_value), ("last_target_value", last_target_value)] {
+        if value < 0 {
+            return Err(invalid(name, "must not be negative"));
+        }
+    }
+    let check = checks.failed.iter_mut()
+        .chain(checks.reopened.iter_mut())
+        .chain(checks.seen.iter_mut())
+        .find(|c| c.key == key)
+        .ok_or_else(|| invalid(key, "check is not in the save"))?;
+    check.difficulty = difficulty;
+    check.last_skill_value = last_skill_value;
+    check.last_target_value = last_target_value;
+    Ok(())
+}
+
+/// Forget a check entirely so the game offers it as new.
+pub fn remove_check(checks: &mut WhiteChecksState, key: &str) -> AppResult<()> {
+    take_check(&mut checks.failed, key)
+        .or_else(|| take_check(&mut checks.reopened, key))
+        .or_else(|| take_check(&mut checks.seen, key))
+        .map(|_| ())
+        .ok_or_else(|| invalid(key, "check is not in the save"))
+}
+
+/// Write the checks back into the raw 2nd file. Each check keeps its raw
+/// object (actor, modifiers, ...) with the edited values patched in, and
+/// `ChecksBySkill`/`ChecksByVariable` are updated for checks that became or
+/// stopped being failed.
+pub fn apply_to_second_raw(checks: &WhiteChecksState, second_raw: &mut Value) {
+    let Some(Value::Object(holder)) = second_raw.get_mut("failedWhiteChecksHolder") else { return };
+
+    let mut raw_checks: HashMap<String, Value> = HashMap::new();
+    for field in ["SeenWhiteCheckCache", "WhiteCheckCache"] {
+        if let Some(Value::Object(cache)) = holder.get(field) {
+            raw_checks.extend(cache.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+    if let Some(Value::Object(by_actor)) = holder.get("ReopenedWhiteChecksByActorName") {
+        for element in by_actor.values().filter_map(Value::as_array).flatten() {
+            if let Some(flag) = element.get("FlagName").and_then(Value::as_str) {
+                raw_checks.entry(flag.to_string()).or_insert_with(|| element.clone());
+            }
+        }
+    }
+    let previously_failed: HashSet<String> = match holder.get("WhiteCheckCache") {
+        Some(Value::Object(cache)) => cache.keys().cloned().collect(),
+        _ => HashSet::new(),
+    };
+
+    let patched = |check: &WhiteCheckDisplay| -> Option<Value> {
+        let mut raw = raw_checks.get(&check.key)?.clone();
+        if let Value::Object(o) = &mut raw {
+            o.insert("difficulty".to_string(), json!(check.difficulty));
+            o.insert("LastSkillValue".to_string(), json!(check.last_skill_value));
+            o.insert("LastTargetValue".to_string(), json!(check.last_target_value));
+            o.insert("isOnlySeen".to_string(), json!(check.is_seen_only));
+        }
+        Some(raw)
+    };
+
+    let failed: Map<String, Value> = checks.failed.iter()
+        .filter_map(|c| Some((c.key.clone(), patched(c)?)))
+        .collect();
+    let seen: Map<String, Value> = checks.seen.iter()
+        .filter_map(|c| Some((c.key.clone(), patched(c)?)))
+        .collect();
+    let mut reopened: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for check in &checks.reopened {
+        if let Some(raw) = patched(check) {
+            reopened.entry(check.actor_name.clone()).or_default().push(raw);
+        }
+    }
+
+    let removed: HashSet<&str> = previously_failed.iter()
+        .map(String::as_str)
+        .filter(|k| !failed.contains_key(*k))
+        .collect();
+    let added: Vec<(&String, &Value)> = failed.iter()
+        .filter(|(k, _)| !previously_failed.contains(*k))
+        .collect();
+
+    for field in ["ChecksBySkill", "ChecksByVariable"] {
+        let index = holder.entry(field.to_string()).or_insert_with(|| json!({}));
+        let Value::Object(index) = index else { continue };
+        for list in index.values_mut().filter_map(Value::as_array_mut) {
+            list.retain(|entry| !removed.contains(entry_flag(entry).unwrap_or_default()));
+            // Entries holding whole checks pick up the edited values.
+            for entry in list.iter_mut().filter(|e| e.is_object()) {
+                if let Some(raw) = entry_flag(entry).and_then(|flag| failed.get(flag)) {
+                    *entry = raw.clone();
+                }
+            }
+        }
+        index.retain(|_, list| list.as_array().map_or(true, |l| !l.is_empty()));
+
+        for (flag, raw) in &added {
+            let index_keys = match field {
+                "ChecksBySkill" => raw.get("SkillType").and_then(Value::as_str).map(str::to_string).into_iter().collect(),
+                _ => modifier_variables(raw),
+            };
+            for index_key in index_keys {
+                let list = index.entry(index_key).or_insert_with(|| json!([]));
+                if let Value::Array(list) = list {
+                    let entry = index_entry(list, flag, raw);
+                    list.push(entry);
+                }
+            }
+        }
+    }
+
+    holder.insert("WhiteCheckCache".to_string(), Value::Object(failed));
+    holder.insert("SeenWhiteCheckCache".to_string(), Value::Object(seen));
+    holder.insert("ReopenedWhiteChecksByActorName".to_string(), json!(reopened));
+}
+
+fn check_display(key: &str, check: &WhiteCheck, is_seen_only: bool, gd: &GameDataService) -> WhiteCheckDisplay {
+    WhiteCheckDisplay {
+        key: key.to_string(),
+        flag_name: check.flag_name.clone(),
+        skill_type: check.skill_type.clone(),
+        skill_display_name: gd.get_skill_display_name_by_type(&check.skill_type),
+        difficulty: check.difficulty,
+        last_skill_value: check.last_skill_value,
+        last_target_value: check.last_target_value,
+        check_precondition: check.check_precondition.clone(),
+        is_seen_only,
+        actor_name: actor_name(&check.actor),
+    }
+}
+
+/// The actor's "Name" field, as in the dialogue database.
+fn actor_name(actor: &Value) -> String {
+    actor.get("fields")
+        .and_then(Value::as_array)
+        .and_then(|fields| fields.iter().find(|f| f.get("title").and_then(Value::as_str) == Some("Name")))
+        .and_then(|f| f.get("value").and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Variables whose value changes the check's modifiers. Modifiers are keyed
+/// by variable name, or by a function name like `CheckItem` otherwise.
+fn modifier_variables(raw: &Value) -> Vec<String> {
+    let Some(Value::Object(modifiers)) = raw.get("CheckModifiers") else { return Vec::new() };
+    modifiers.iter()
+        .filter(|(name, list)| {
+            let reference = format!("Variable[\"{}\"]", name);
+            list.as_array().is_some_and(|l| l.iter().any(|m| {
+                m.get("expression").and_then(Value::as_str).is_some_and(|e| e.contains(&reference))
+            }))
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Index lists hold either flag names or whole checks; follow whatever the
+/// list already holds, defaulting to flag names.
+fn index_entry(list: &[Value], flag: &str, raw: &Value) -> Value {
+    match list.first() {
+        Some(Value::Object(_)) => raw.clone(),
+        _ => json!(flag),
+    }
+}
+
+fn entry_flag(entry: &Value) -> Option<&str> {
+    match entry {
+        Value::String(flag) => Some(flag),
+        _ => entry.get("FlagName").and_then(Value::as_str),
+    }
+}
+
+fn take_check(checks: &mut Vec<WhiteCheckDisplay>, key: &str) -> Option<WhiteCheckDisplay> {
+    let index = checks.iter().position(|c| c.key == key)?;
+    Some(checks.remove(index))
+}
+
+fn insert_sorted(checks: &mut Vec<WhiteCheckDisplay>, check: WhiteCheckDisplay) {
+    let index = checks.partition_point(|c| c.key < check.key);
+    checks.insert(index, check);
+}
+
+fn invalid(key: &str, message: &str) -> AppError {
+    AppError::InvalidValue { key: key.to_string(), message: message.to_string() }
+}
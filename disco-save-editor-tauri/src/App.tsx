@@ -7,7 +7,7 @@ import { Separator } from "./components/ui/separator";
 import { Badge } from "./components/ui/badge";
 import {
   User, Backpack, Brain, BookOpen, Users, Globe, RotateCcw,
-  Package, Layers, Save, FolderOpen, Moon, Sun, Loader2, AlertTriangle
+  Package, Layers, Save, FolderOpen, Moon, Sun, Loader2, AlertTriangle, History
 } from "lucide-react";
 
 import HomePage from "./pages/HomePage";
@@ -20,6 +20,7 @@ import WorldPage from "./pages/WorldPage";
 import WhiteChecksPage from "./pages/WhiteChecksPage";
 import ContainersPage from "./pages/ContainersPage";
 import StatesPage from "./pages/StatesPage";
+import HistoryPage from "./pages/HistoryPage";
 
 const PAGES = [
   { id: "character", label: "Character", icon: User },
@@ -31,6 +32,7 @@ const PAGES = [
   { id: "whitechecks", label: "White Checks", icon: RotateCcw },
   { id: "containers", label: "Containers", icon: Package },
   { id: "states", label: "States", icon: Layers },
+  { id: "history", label: "History", icon: History },
 ] as const;
 
 type PageId = (typeof PAGES)[number]["id"];
@@ -59,6 +61,7 @@ export default function App() {
       case "whitechecks": return <WhiteChecksPage />;
       case "containers": return <ContainersPage />;
       case "states": return <StatesPage />;
+      case "history": return <HistoryPage />;
     }
   };
 
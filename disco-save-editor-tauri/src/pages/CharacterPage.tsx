@@ -1,14 +1,17 @@
+import { useState } from "react";
 import { useStore } from "@/store";
 import { Card, CardContent, CardHeader, CardTitle, CardDescription } from "@/components/ui/card";
 import { Input } from "@/components/ui/input";
+import { Button } from "@/components/ui/button";
 import { Label } from "@/components/ui/label";
 import { Badge } from "@/components/ui/badge";
 import { Separator } from "@/components/ui/separator";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { Switch } from "@/components/ui/switch";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
+import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle } from "@/components/ui/dialog";
 import { User, Coins, TrendingUp, Heart, Brain, Star, Info, Clock } from "lucide-react";
-import type { AbilityDisplay, SkillDisplay } from "@/types";
+import type { AbilityDisplay, GameClock, GameTimestamp, SkillDisplay } from "@/types";
 
 const ABILITY_COLORS: Record<string, string> = {
   INT: "bg-blue-500/20 text-blue-400 border-blue-500/30",
@@ -18,7 +21,8 @@ const ABILITY_COLORS: Record<string, string> = {
 };
 
 export default function CharacterPage() {
-  const { currentSave, updateField } = useStore();
+  const { currentSave, updateField, skillBreakdown, loadSkillBreakdown, clearSkillBreakdown, advanceTime } = useStore();
+  const [advanceMinutes, setAdvanceMinutes] = useState(60);
   if (!currentSave) return null;
 
   const handleNumberField = (key: keyof typeof currentSave, val: string) => {
@@ -26,6 +30,21 @@ export default function CharacterPage() {
     if (!isNaN(n)) updateField(key, n as never);
   };
 
+  const updateClock = (field: keyof GameClock, value: number) => {
+    updateField("clock", { ...currentSave.clock, [field]: value });
+  };
+
+  const updateOverride = (changes: Partial<GameTimestamp> | null) => {
+    const { clock } = currentSave;
+    const current: GameTimestamp = clock.time_override ?? {
+      day_counter: clock.day,
+      real_day_counter: clock.real_day,
+      day_minutes: clock.hours * 60 + clock.minutes,
+      seconds: clock.seconds,
+    };
+    updateField("clock", { ...clock, time_override: changes && { ...current, ...changes } });
+  };
+
   const updateAbility = (idx: number, field: keyof AbilityDisplay, value: number | boolean) => {
     const updated = [...currentSave.abilities];
     updated[idx] = { ...updated[idx], [field]: value };
@@ -83,12 +102,10 @@ export default function CharacterPage() {
           ))}
         </div>
 
-        <div className="grid grid-cols-2 md:grid-cols-4 gap-4">
+        <div className="grid grid-cols-2 gap-4">
           {[
             { label: "Health / Endurance", key: "health" as const, icon: <Heart className="h-4 w-4 text-red-500" /> },
             { label: "Morale / Volition", key: "morale" as const, icon: <Brain className="h-4 w-4 text-purple-500" /> },
-            { label: "Current Day", key: "day" as const, icon: <Clock className="h-4 w-4" /> },
-            { label: "Current Hours", key: "hours" as const, icon: <Clock className="h-4 w-4" /> },
           ].map((f) => (
             <Card key={f.key}>
               <CardContent className="p-4">
@@ -107,6 +124,93 @@ export default function CharacterPage() {
           ))}
         </div>
 
+        <Card>
+          <CardContent className="p-4 space-y-4">
+            <div className="flex items-center gap-2">
+              <Clock className="h-4 w-4" />
+              <Label className="text-[10px] font-bold uppercase tracking-widest text-muted-foreground">Clock</Label>
+            </div>
+            <div className="grid grid-cols-2 md:grid-cols-5 gap-4">
+              {[
+                { label: "Day", key: "day" as const, max: undefined },
+                { label: "Real Day", key: "real_day" as const, max: undefined },
+                { label: "Hours", key: "hours" as const, max: 23 },
+                { label: "Minutes", key: "minutes" as const, max: 59 },
+                { label: "Seconds", key: "seconds" as const, max: 59 },
+              ].map((f) => (
+                <div key={f.key} className="space-y-1.5">
+                  <Label className="text-[10px] font-bold uppercase text-muted-foreground">{f.label}</Label>
+                  <Input
+                    type="number"
+                    min={f.max === undefined ? 1 : 0}
+                    max={f.max}
+                    value={currentSave.clock[f.key] as number}
+                    onChange={(e) => updateClock(f.key, parseInt(e.target.value, 10) || 0)}
+                    className="h-9 font-mono"
+                  />
+                </div>
+              ))}
+            </div>
+            <Separator />
+            <div className="flex items-center gap-4 flex-wrap">
+              <div className="flex items-center gap-2">
+                <Switch
+                  checked={currentSave.clock.time_override !== null}
+                  onCheckedChange={(v) => updateOverride(v ? {} : null)}
+                  className="scale-75"
+                />
+                <Label className="text-xs" title="Time shown instead of the clock during scripted scenes">Time override</Label>
+              </div>
+              {currentSave.clock.time_override && (
+                <>
+                  <div className="flex items-center gap-1">
+                    <Label className="text-[10px] uppercase text-muted-foreground">Day</Label>
+                    <Input
+                      type="number"
+                      min={1}
+                      value={currentSave.clock.time_override.day_counter}
+                      onChange={(e) => {
+                        const day = parseInt(e.target.value, 10) || 0;
+                        updateOverride({ day_counter: day, real_day_counter: day });
+                      }}
+                      className="h-7 w-16 text-xs font-mono"
+                    />
+                  </div>
+                  <div className="flex items-center gap-1">
+                    <Label className="text-[10px] uppercase text-muted-foreground">Minute of day</Label>
+                    <Input
+                      type="number"
+                      min={0}
+                      max={1439}
+                      value={currentSave.clock.time_override.day_minutes}
+                      onChange={(e) => updateOverride({ day_minutes: parseInt(e.target.value, 10) || 0 })}
+                      className="h-7 w-20 text-xs font-mono"
+                    />
+                  </div>
+                </>
+              )}
+              <div className="flex items-center gap-2 ml-auto">
+                <Input
+                  type="number"
+                  min={1}
+                  value={advanceMinutes}
+                  onChange={(e) => setAdvanceMinutes(parseInt(e.target.value, 10) || 0)}
+                  className="h-8 w-24 font-mono text-xs"
+                />
+                <Button
+                  variant="outline"
+                  size="sm"
+                  disabled={advanceMinutes <= 0}
+                  onClick={() => advanceTime(advanceMinutes)}
+                  title="Let game time pass: thoughts and substances count down and Kim's sleep timer counts up"
+                >
+                  Advance minutes
+                </Button>
+              </div>
+            </div>
+          </CardContent>
+        </Card>
+
         <Tabs defaultValue="abilities" className="w-full">
           <TabsList className="grid w-full grid-cols-2 max-w-[400px]">
             <TabsTrigger value="abilities">Abilities</TabsTrigger>
@@ -181,10 +285,10 @@ export default function CharacterPage() {
                             </CardTitle>
                             {s.modifier_count > 0 && (
                                 <Tooltip>
-                                  <TooltipTrigger>
-                                    <Badge variant="outline" className="text-[9px] h-4 bg-primary/5">{s.modifier_count} MODS</Badge>
+                                  <TooltipTrigger onClick={() => loadSkillBreakdown(s.type_code)}>
+                                    <Badge variant="outline" className="text-[9px] h-4 bg-primary/5 cursor-pointer">{s.modifier_count} MODS</Badge>
                                   </TooltipTrigger>
-                                  <TooltipContent>This skill has active modifiers in the save file</TooltipContent>
+                                  <TooltipContent>Click to see where this skill's value comes from</TooltipContent>
                                 </Tooltip>
                             )}
                           </CardHeader>
@@ -204,7 +308,8 @@ export default function CharacterPage() {
                                 <Input
                                   type="number"
                                   value={s.value}
-                                  onChange={(e) => updateSkill(idx, "value", parseInt(e.target.value) || 0)}
+                                  readOnly
+                                  title="Recomputed on save from the governing ability, invested points, equipped items and thoughts"
                                   className="h-7 text-xs font-mono bg-muted/30"
                                 />
                               </div>
@@ -230,6 +335,43 @@ export default function CharacterPage() {
           </TabsContent>
         </Tabs>
       </div>
+
+      <Dialog open={skillBreakdown !== null} onOpenChange={(open) => !open && clearSkillBreakdown()}>
+        <DialogContent className="max-w-md">
+          {skillBreakdown && (
+            <>
+              <DialogHeader>
+                <DialogTitle>{skillBreakdown.display_name}</DialogTitle>
+                <DialogDescription>Modifiers as of the last save.</DialogDescription>
+              </DialogHeader>
+              <div className="space-y-1 text-xs font-mono">
+                <div className="flex justify-between">
+                  <span>Ability</span>
+                  <span>{skillBreakdown.calculated_ability}</span>
+                </div>
+                <div className="flex justify-between">
+                  <span>Invested</span>
+                  <span>{skillBreakdown.rank_value}</span>
+                </div>
+                {skillBreakdown.modifiers.map((m, i) => (
+                  <div key={i} className="flex justify-between gap-3">
+                    <span className="truncate">
+                      <Badge variant="outline" className="text-[8px] h-3.5 px-1 mr-2">{m.modifier_type}</Badge>
+                      {m.cause_name && m.cause_type !== "ABILITY" ? `${m.cause_name}: ${m.explanation}` : m.explanation}
+                    </span>
+                    <span>{m.amount > 0 ? `+${m.amount}` : m.amount}</span>
+                  </div>
+                ))}
+                <Separator className="my-2" />
+                <div className="flex justify-between font-bold">
+                  <span>Total</span>
+                  <span>{skillBreakdown.total}</span>
+                </div>
+              </div>
+            </>
+          )}
+        </DialogContent>
+      </Dialog>
     </TooltipProvider>
   );
 }
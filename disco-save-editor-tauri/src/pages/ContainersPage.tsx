@@ -1,15 +1,22 @@
 import { useState } from "react";
 import { useStore } from "@/store";
-import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
 import { Input } from "@/components/ui/input";
 import { Badge } from "@/components/ui/badge";
+import { Button } from "@/components/ui/button";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { Accordion, AccordionContent, AccordionItem, AccordionTrigger } from "@/components/ui/accordion";
-import { Search, Package } from "lucide-react";
+import { Search, Package, Plus, Trash2, Dices } from "lucide-react";
+import type { ContainerDisplay, ContainerItemDisplay } from "@/types";
 
 export default function ContainersPage() {
-  const { currentSave } = useStore();
+  const {
+    currentSave, addContainerItem, removeContainerItem, editContainerItem, rerollContainers,
+  } = useStore();
   const [search, setSearch] = useState("");
+  const [seed, setSeed] = useState(0);
+  const [newItems, setNewItems] = useState<Record<string, string>>({});
 
   if (!currentSave) return null;
 
@@ -22,6 +29,74 @@ export default function ContainersPage() {
   const nonEmpty = filtered.filter((c) => c.item_count > 0);
   const empty = filtered.filter((c) => c.item_count === 0);
 
+  const handleAdd = async (containerId: string) => {
+    const name = (newItems[containerId] ?? "").trim();
+    await addContainerItem(containerId, {
+      name,
+      probability: 1,
+      value: 0,
+      deviation: 0,
+      calculated_value: 0,
+      bonus_loot: false,
+    });
+    setNewItems((s) => ({ ...s, [containerId]: "" }));
+  };
+
+  const numberField = (
+    label: string,
+    value: number,
+    disabled: boolean,
+    onChange: (value: number) => void,
+  ) => (
+    <div className="flex items-center gap-1">
+      <Label className="text-[10px] uppercase text-muted-foreground">{label}</Label>
+      <Input
+        type="number"
+        min={0}
+        value={value}
+        disabled={disabled}
+        onChange={(e) => onChange(parseInt(e.target.value) || 0)}
+        className="h-6 w-16 text-xs font-mono"
+      />
+    </div>
+  );
+
+  const renderItem = (container: ContainerDisplay, item: ContainerItemDisplay, i: number) => {
+    const edit = (changes: Partial<ContainerItemDisplay>) =>
+      editContainerItem(container.container_id, i, { ...item, ...changes });
+    return (
+      <div key={`${item.name}-${i}`} className="flex items-center gap-3 text-xs p-2 rounded bg-accent/30 flex-wrap">
+        <span className="flex-1 truncate font-mono">{item.name}</span>
+        {numberField("Prob %", Math.round(item.probability * 100), container.is_shared, (v) =>
+          edit({ probability: Math.min(v, 100) / 100 }))}
+        {numberField("Value", item.value, container.is_shared, (v) => edit({ value: v }))}
+        {numberField("±", item.deviation, container.is_shared, (v) => edit({ deviation: v }))}
+        <span className="text-muted-foreground w-16 text-right" title="Rolled value, 0 until the container is opened">
+          = {item.calculated_value}
+        </span>
+        <div className="flex items-center gap-1">
+          <Label className="text-[10px] uppercase text-muted-foreground">Bonus</Label>
+          <Switch
+            checked={item.bonus_loot}
+            disabled={container.is_shared}
+            onCheckedChange={(v) => edit({ bonus_loot: v })}
+            className="scale-75"
+          />
+        </div>
+        <Button
+          variant="ghost"
+          size="sm"
+          className="h-6 w-6 p-0"
+          disabled={container.is_shared}
+          onClick={() => removeContainerItem(container.container_id, i)}
+          title="Remove from container"
+        >
+          <Trash2 className="h-3.5 w-3.5" />
+        </Button>
+      </div>
+    );
+  };
+
   return (
     <div className="space-y-6">
       <div className="flex items-center justify-between">
@@ -29,7 +104,15 @@ export default function ContainersPage() {
           <Package className="h-5 w-5" />
           Containers
         </h2>
-        <div className="flex gap-2">
+        <div className="flex items-center gap-2">
+          <Label className="text-xs text-muted-foreground">Reroll seed</Label>
+          <Input
+            type="number"
+            min={0}
+            value={seed}
+            onChange={(e) => setSeed(Math.max(0, parseInt(e.target.value) || 0))}
+            className="h-8 w-28 font-mono text-xs"
+          />
           <Badge variant="outline">{currentSave.containers.length} total</Badge>
           <Badge variant="secondary">{currentSave.containers.filter((c) => c.item_count > 0).length} with items</Badge>
         </div>
@@ -60,23 +143,58 @@ export default function ContainersPage() {
                       Value: {container.total_value}
                     </Badge>
                   )}
+                  {container.is_shared && (
+                    <Badge variant="outline" className="text-[10px]" title="Loot is shared with another container and can't be edited">
+                      Shared
+                    </Badge>
+                  )}
                 </div>
               </AccordionTrigger>
               <AccordionContent>
                 <div className="px-3 pb-2 space-y-1">
-                  {container.items.map((item, i) => (
-                    <div
-                      key={`${item.name}-${i}`}
-                      className="flex items-center gap-3 text-xs p-2 rounded bg-accent/30"
-                    >
-                      <span className="flex-1 truncate">{item.name}</span>
-                      <span className="text-muted-foreground">Prob: {item.probability}%</span>
-                      <span className="text-muted-foreground">Val: {item.calculated_value}</span>
-                      {item.bonus_loot && (
-                        <Badge className="text-[9px] px-1 py-0 bg-yellow-500/20 text-yellow-400">Bonus</Badge>
+                  {container.items.map((item, i) => renderItem(container, item, i))}
+                  {!container.is_shared && (
+                    <div className="flex gap-2 pt-1">
+                      <Input
+                        placeholder="Add item (e.g. money)"
+                        value={newItems[container.container_id] ?? ""}
+                        onChange={(e) => setNewItems((s) => ({ ...s, [container.container_id]: e.target.value }))}
+                        onKeyDown={(e) => e.key === "Enter" && handleAdd(container.container_id)}
+                        className="h-7 text-xs font-mono"
+                      />
+                      <Button
+                        variant="outline"
+                        size="sm"
+                        className="h-7"
+                        disabled={!(newItems[container.container_id] ?? "").trim()}
+                        onClick={() => handleAdd(container.container_id)}
+                      >
+                        <Plus className="h-3 w-3" />
+                      </Button>
+                      <Button
+                        variant="outline"
+                        size="sm"
+                        className="h-7 text-xs"
+                        onClick={() => rerollContainers(container.container_id, seed)}
+                        title="Roll this container's loot from the seed"
+                      >
+                        <Dices className="h-3 w-3 mr-1" />
+                        Reroll
+                      </Button>
+                      {container.area && (
+                        <Button
+                          variant="outline"
+                          size="sm"
+                          className="h-7 text-xs"
+                          onClick={() => rerollContainers(container.area, seed)}
+                          title={`Roll every container in ${container.area} and below it from the seed`}
+                        >
+                          <Dices className="h-3 w-3 mr-1" />
+                          Reroll Area
+                        </Button>
                       )}
                     </div>
-                  ))}
+                  )}
                 </div>
               </AccordionContent>
             </AccordionItem>
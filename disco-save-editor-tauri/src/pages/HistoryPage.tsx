@@ -0,0 +1,197 @@
+import { useEffect, useState } from "react";
+import { useStore } from "@/store";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Badge } from "@/components/ui/badge";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { History, Camera, RotateCcw, Scissors, GitCompare, Loader2, X } from "lucide-react";
+import type { SaveDetails, SaveDiff } from "@/types";
+
+function describe(details: SaveDetails | null): string {
+  if (!details) return "-";
+  return `${details.area_id} · Day ${details.day}, ${String(details.hours).padStart(2, "0")}:${String(details.minutes).padStart(2, "0")} · Level ${details.level}`;
+}
+
+function SaveDiffCard({ diff, onClose }: { diff: SaveDiff; onClose: () => void }) {
+  const sections = [
+    ["1st file", diff.first],
+    ["2nd file", diff.second],
+    ["Lua", diff.lua],
+    ["States", diff.states],
+  ] as const;
+  const show = (v: unknown) => (v === null ? "-" : JSON.stringify(v));
+  const total = sections.reduce((n, [, changes]) => n + changes.length, 0);
+
+  return (
+    <Card>
+      <CardHeader className="py-3 px-4">
+        <div className="flex items-center justify-between">
+          <CardTitle className="text-sm">{total} differences</CardTitle>
+          <Button variant="ghost" size="sm" onClick={onClose}>
+            <X className="h-4 w-4" />
+          </Button>
+        </div>
+        <CardDescription className="text-xs truncate">{diff.a} → {diff.b}</CardDescription>
+      </CardHeader>
+      <CardContent className="px-4 pb-3">
+        <ScrollArea className="h-72">
+          {sections.map(([title, changes]) => changes.length > 0 && (
+            <div key={title} className="mb-3">
+              <p className="text-xs font-bold uppercase text-muted-foreground mb-1">{title} ({changes.length})</p>
+              {changes.map((c) => (
+                <div key={c.key} className="text-[11px] font-mono flex gap-2">
+                  <span className="truncate">{c.key}</span>
+                  <span className="text-muted-foreground shrink-0">{show(c.before)} → {show(c.after)}</span>
+                  {c.label && <span className="italic text-muted-foreground truncate">{c.label}</span>}
+                </div>
+              ))}
+            </div>
+          ))}
+        </ScrollArea>
+      </CardContent>
+    </Card>
+  );
+}
+
+export default function HistoryPage() {
+  const {
+    currentSave, dirty, snapshots, snapshotsLoading, snapshotDiff,
+    loadSnapshots, createSnapshot, diffSnapshot, restoreSnapshot, pruneSnapshots,
+    saves, discoverSaves, saveDiff, diffSaves, clearSaveDiff,
+  } = useStore();
+  const [label, setLabel] = useState("");
+  const [compareWith, setCompareWith] = useState("");
+
+  useEffect(() => {
+    loadSnapshots();
+    discoverSaves();
+  }, [loadSnapshots, discoverSaves]);
+
+  if (!currentSave) return null;
+
+  const handleCreate = async () => {
+    await createSnapshot(label.trim() || null);
+    setLabel("");
+  };
+
+  const handleRestore = async (id: string) => {
+    const warning = dirty ? "Unsaved changes will be lost. " : "";
+    if (window.confirm(`${warning}Restore snapshot ${id}? The current save is snapshotted first.`)) {
+      await restoreSnapshot(id);
+    }
+  };
+
+  return (
+    <div className="space-y-6 max-w-4xl mx-auto">
+      <div className="flex items-center justify-between">
+        <div className="space-y-1">
+          <h2 className="text-2xl font-bold flex items-center gap-2">
+            <History className="h-6 w-6 text-primary" />
+            History
+          </h2>
+          <p className="text-sm text-muted-foreground">
+            Snapshots are taken before every save. Label one to keep it from being pruned, or compare this save with another.
+          </p>
+        </div>
+        <Button variant="outline" onClick={pruneSnapshots} disabled={snapshotsLoading}>
+          <Scissors className="h-4 w-4 mr-2" />
+          Prune
+        </Button>
+      </div>
+
+      <div className="flex gap-2">
+        <Input
+          placeholder="Label (optional)"
+          value={label}
+          onChange={(e) => setLabel(e.target.value)}
+        />
+        <Button onClick={handleCreate}>
+          <Camera className="h-4 w-4 mr-2" />
+          Snapshot Now
+        </Button>
+      </div>
+
+      <div className="flex gap-2">
+        <Select value={compareWith} onValueChange={setCompareWith}>
+          <SelectTrigger>
+            <SelectValue placeholder="Compare with another save..." />
+          </SelectTrigger>
+          <SelectContent>
+            {saves.filter((s) => s.path !== currentSave.folder_path).map((s) => (
+              <SelectItem key={s.path} value={s.path}>{s.name}</SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+        <Button variant="outline" disabled={!compareWith} onClick={() => diffSaves(currentSave.folder_path, compareWith)}>
+          <GitCompare className="h-4 w-4 mr-2" />
+          Compare
+        </Button>
+      </div>
+
+      {saveDiff && <SaveDiffCard diff={saveDiff} onClose={clearSaveDiff} />}
+
+      {snapshotDiff && (
+        <Card>
+          <CardHeader className="py-3 px-4">
+            <CardTitle className="text-sm">
+              {snapshotDiff.from.id} → {snapshotDiff.to ? snapshotDiff.to.id : "current save"}
+            </CardTitle>
+            <CardDescription className="text-xs">
+              {describe(snapshotDiff.from.details)} → {describe(snapshotDiff.to_details)}
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="px-4 pb-3 text-xs space-y-1">
+            {snapshotDiff.files.length === 0 ? (
+              <p className="text-muted-foreground">No differences</p>
+            ) : (
+              snapshotDiff.files.map((f) => (
+                <div key={f.name} className="flex items-center gap-2">
+                  <Badge variant={f.change === "modified" ? "secondary" : "outline"}>{f.change}</Badge>
+                  <span className="truncate">{f.name}</span>
+                </div>
+              ))
+            )}
+          </CardContent>
+        </Card>
+      )}
+
+      {snapshotsLoading && snapshots.length === 0 ? (
+        <div className="flex justify-center py-10 text-muted-foreground">
+          <Loader2 className="h-6 w-6 animate-spin" />
+        </div>
+      ) : snapshots.length === 0 ? (
+        <p className="text-sm text-muted-foreground">No snapshots of this save yet.</p>
+      ) : (
+        <div className="space-y-2">
+          {snapshots.map((s) => (
+            <Card key={s.id}>
+              <CardHeader className="py-3 px-4">
+                <div className="flex items-center justify-between gap-2">
+                  <div className="min-w-0">
+                    <CardTitle className="text-sm font-medium flex items-center gap-2">
+                      {new Date(s.created_at).toLocaleString()}
+                      {s.label && <Badge variant="secondary">{s.label}</Badge>}
+                    </CardTitle>
+                    <CardDescription className="text-xs">
+                      {describe(s.details)} · {(s.size / 1024).toFixed(0)} KB · {s.hash.slice(0, 12)}
+                    </CardDescription>
+                  </div>
+                  <div className="flex gap-1 shrink-0">
+                    <Button variant="ghost" size="sm" onClick={() => diffSnapshot(s.id, null)} title="Compare with the current save">
+                      <GitCompare className="h-4 w-4" />
+                    </Button>
+                    <Button variant="ghost" size="sm" onClick={() => handleRestore(s.id)} title="Restore">
+                      <RotateCcw className="h-4 w-4" />
+                    </Button>
+                  </div>
+                </div>
+              </CardHeader>
+            </Card>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
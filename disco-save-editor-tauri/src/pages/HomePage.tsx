@@ -3,18 +3,22 @@ import { useStore } from "@/store";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
 import { ScrollArea } from "@/components/ui/scroll-area";
-import { FolderOpen, RefreshCw, Loader2 } from "lucide-react";
+import { FolderOpen, FolderPlus, RefreshCw, Loader2, X } from "lucide-react";
 
 interface HomePageProps {
   onLoaded: () => void;
 }
 
 export default function HomePage({ onLoaded }: HomePageProps) {
-  const { saves, savesLoading, discoverSaves, locateSaveFile, loadSave, currentSave } = useStore();
+  const {
+    saves, savesLoading, discoverSaves, locateSaveFile, loadSave, currentSave,
+    extraSaveRoots, loadExtraSaveRoots, addSaveRoot, removeSaveRoot,
+  } = useStore();
 
   useEffect(() => {
     discoverSaves();
-  }, [discoverSaves]);
+    loadExtraSaveRoots();
+  }, [discoverSaves, loadExtraSaveRoots]);
 
   useEffect(() => {
     if (currentSave) {
@@ -40,6 +44,10 @@ export default function HomePage({ onLoaded }: HomePageProps) {
             <FolderOpen className="h-4 w-4 mr-2" />
             Browse
           </Button>
+          <Button variant="outline" onClick={addSaveRoot} disabled={savesLoading}>
+            <FolderPlus className="h-4 w-4 mr-2" />
+            Add Folder
+          </Button>
           <Button variant="outline" onClick={discoverSaves} disabled={savesLoading}>
             {savesLoading ? <Loader2 className="h-4 w-4 animate-spin mr-2" /> : <RefreshCw className="h-4 w-4 mr-2" />}
             Refresh
@@ -47,6 +55,19 @@ export default function HomePage({ onLoaded }: HomePageProps) {
         </div>
       </div>
 
+      {extraSaveRoots.length > 0 && (
+        <div className="flex flex-wrap gap-2">
+          {extraSaveRoots.map((root) => (
+            <span key={root} className="flex items-center gap-1 rounded-md border px-2 py-1 text-xs text-muted-foreground">
+              <span className="truncate max-w-[20rem]">{root}</span>
+              <button onClick={() => removeSaveRoot(root)} title="Stop scanning this folder">
+                <X className="h-3 w-3" />
+              </button>
+            </span>
+          ))}
+        </div>
+      )}
+
       {savesLoading && saves.length === 0 ? (
         <div className="flex flex-col items-center justify-center py-20 text-muted-foreground">
           <Loader2 className="h-8 w-8 animate-spin mb-4" />
@@ -78,9 +99,21 @@ export default function HomePage({ onLoaded }: HomePageProps) {
                 <CardHeader className="py-3 px-4">
                   <div className="flex items-center justify-between">
                     <CardTitle className="text-sm font-medium">{s.name}</CardTitle>
-                    <CardDescription className="text-xs">{s.last_modified}</CardDescription>
+                    <CardDescription className="text-xs">
+                      {new Date(s.last_modified).toLocaleString()}
+                    </CardDescription>
                   </div>
-                  <CardDescription className="text-xs truncate">{s.path}</CardDescription>
+                  {s.details && (
+                    <CardDescription className="text-xs">
+                      {s.details.area_id} · Day {s.details.day},{" "}
+                      {String(s.details.hours).padStart(2, "0")}:{String(s.details.minutes).padStart(2, "0")} ·
+                      Level {s.details.level} · {s.details.xp_amount} XP · {(s.details.money / 100).toFixed(2)} réal
+                    </CardDescription>
+                  )}
+                  <CardDescription className="text-xs truncate">
+                    {s.is_zip ? "Zip" : "Folder"}
+                    {s.snapshot_count > 0 && ` · ${s.snapshot_count} snapshot${s.snapshot_count === 1 ? "" : "s"}`} · {s.path}
+                  </CardDescription>
                 </CardHeader>
               </Card>
             ))}
@@ -10,10 +10,12 @@ import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
 import { Search, Plus, Package, Trash2, Info, ShoppingBag, ShieldCheck, Zap, Ghost } from "lucide-react";
-import type { InventoryItemDisplay } from "@/types";
 
 export default function InventoryPage() {
-  const { currentSave, updateField, catalogItems, catalogLoading, loadCatalogItems } = useStore();
+  const {
+    currentSave, updateField, catalogItems, catalogLoading, loadCatalogItems,
+    addInventoryItem, removeInventoryItem, editInventoryItem, equipInventoryItem, unequipInventoryItem,
+  } = useStore();
   const [search, setSearch] = useState("");
   const [catalogSearch, setCatalogSearch] = useState("");
 
@@ -36,33 +38,8 @@ export default function InventoryPage() {
         i.name.toLowerCase().includes(catalogSearch.toLowerCase()))
   );
 
-  const updateItem = (idx: number, field: keyof InventoryItemDisplay, value: unknown) => {
-    const updated = [...currentSave.owned_items];
-    updated[idx] = { ...updated[idx], [field]: value };
-    updateField("owned_items", updated);
-  };
-
-  const removeItem = (idx: number) => {
-    const updated = currentSave.owned_items.filter((_, i) => i !== idx);
-    updateField("owned_items", updated);
-  };
-
-  const addItem = (item: any) => {
-    const newItem: InventoryItemDisplay = {
-      name: item.name,
-      display_name: item.display_name,
-      description: item.description,
-      bonus: item.bonus,
-      is_owned: true,
-      is_equipped: false,
-      equip_slot: "",
-      is_quest_item: item.is_quest_item,
-      is_cursed: item.is_cursed,
-      is_substance: item.is_substance,
-      substance_uses: item.is_substance ? 1 : 0,
-    };
-    updateField("owned_items", [...currentSave.owned_items, newItem]);
-  };
+  const stackDisplayName = (stack: string) =>
+    currentSave.owned_items.find((o) => o.name === stack)?.display_name ?? stack;
 
   return (
     <TooltipProvider>
@@ -126,7 +103,8 @@ export default function InventoryPage() {
                         variant="ghost"
                         size="icon"
                         className="h-6 w-6 text-muted-foreground hover:text-destructive"
-                        onClick={() => removeItem(idx)}
+                        onClick={() => removeInventoryItem(item.name)}
+                        title={currentSave.owned_items.some((o) => o.stack_name === item.name) ? "Remove along with everything stacked in it" : "Remove"}
                       >
                         <Trash2 className="h-3.5 w-3.5" />
                       </Button>
@@ -137,6 +115,7 @@ export default function InventoryPage() {
                           {item.is_quest_item && <Badge variant="secondary" className="bg-amber-500/10 text-amber-500 border-amber-500/20 text-[8px] h-4">QUEST</Badge>}
                           {item.is_cursed && <Badge variant="secondary" className="bg-red-500/10 text-red-500 border-red-500/20 text-[8px] h-4">CURSED</Badge>}
                           {item.is_substance && <Badge variant="secondary" className="bg-green-500/10 text-green-500 border-green-500/20 text-[8px] h-4">STIM</Badge>}
+                          {item.stack_name && <Badge variant="outline" className="text-[8px] h-4">IN {stackDisplayName(item.stack_name).toUpperCase()}</Badge>}
                         </div>
                         <p className="text-[11px] leading-snug line-clamp-3 text-muted-foreground italic">
                           {item.description}
@@ -149,9 +128,10 @@ export default function InventoryPage() {
                              <Label className="text-[10px] font-bold">Equipped</Label>
                              <Switch
                               checked={item.is_equipped}
-                              onCheckedChange={(v) => updateItem(idx, "is_equipped", v)}
+                              onCheckedChange={(v) => (v ? equipInventoryItem(item.name) : unequipInventoryItem(item.name))}
                               className="scale-75"
                             />
+                            {item.equip_slot && <Badge variant="outline" className="text-[8px] h-4">{item.equip_slot}</Badge>}
                           </div>
                           {item.bonus && (
                             <Tooltip>
@@ -164,15 +144,36 @@ export default function InventoryPage() {
                             </Tooltip>
                           )}
                         </div>
+                        <div className="flex items-center gap-2">
+                          <Label className="text-[10px] font-bold">New</Label>
+                          <Switch
+                            checked={item.is_fresh}
+                            onCheckedChange={(v) => editInventoryItem(item.name, v, item.substance_uses, item.substance_time_left)}
+                            className="scale-75"
+                          />
+                        </div>
                         {item.is_substance && (
-                          <div className="flex items-center gap-2">
-                            <Label className="text-[10px] uppercase font-bold text-muted-foreground">Uses Remaining</Label>
-                            <Input
-                              type="number"
-                              value={item.substance_uses}
-                              onChange={(e) => updateItem(idx, "substance_uses", parseInt(e.target.value) || 0)}
-                              className="h-7 w-16 text-xs font-mono"
-                            />
+                          <div className="flex items-center gap-3">
+                            <div className="flex items-center gap-2">
+                              <Label className="text-[10px] uppercase font-bold text-muted-foreground" title="-1 until first used">Uses</Label>
+                              <Input
+                                type="number"
+                                min={-1}
+                                value={item.substance_uses}
+                                onChange={(e) => editInventoryItem(item.name, item.is_fresh, parseInt(e.target.value) || 0, item.substance_time_left)}
+                                className="h-7 w-16 text-xs font-mono"
+                              />
+                            </div>
+                            <div className="flex items-center gap-2">
+                              <Label className="text-[10px] uppercase font-bold text-muted-foreground" title="Minutes until the effect wears off">Time Left</Label>
+                              <Input
+                                type="number"
+                                min={0}
+                                value={item.substance_time_left}
+                                onChange={(e) => editInventoryItem(item.name, item.is_fresh, item.substance_uses, parseInt(e.target.value) || 0)}
+                                className="h-7 w-16 text-xs font-mono"
+                              />
+                            </div>
                           </div>
                         )}
                       </div>
@@ -207,7 +208,7 @@ export default function InventoryPage() {
                           variant="secondary"
                           size="sm"
                           className="h-7 text-[10px] px-2 font-bold"
-                          onClick={() => addItem(item)}
+                          onClick={() => addInventoryItem(item.name)}
                         >
                           <Plus className="h-3 w-3 mr-1" />
                           ACQUIRE
@@ -7,41 +7,43 @@ import { Switch } from "@/components/ui/switch";
 import { Label } from "@/components/ui/label";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { ScrollArea } from "@/components/ui/scroll-area";
-import { Accordion, AccordionContent, AccordionItem, AccordionTrigger } from "@/components/ui/accordion";
-import { Search, CheckCircle, Clock, BookOpen, AlertCircle, Calendar } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Search, CheckCircle, Clock, BookOpen, AlertCircle, Calendar, Plus, Trash2, X } from "lucide-react";
+import type { TaskDisplay } from "@/types";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
 
 export default function JournalPage() {
-  const { currentSave, updateField } = useStore();
+  const {
+    currentSave, acquireTask, removeTask, resolveTask, unresolveTask, addSubtask, removeSubtask, setTaskNew,
+  } = useStore();
   const [search, setSearch] = useState("");
+  const [newTask, setNewTask] = useState("");
+  const [newSubtasks, setNewSubtasks] = useState<Record<string, string>>({});
 
   if (!currentSave) return null;
 
-  const activeTasks = currentSave.tasks.filter((t) => !t.is_resolved);
-  const completedTasks = currentSave.tasks.filter((t) => t.is_resolved);
+  const { tasks } = currentSave.journal;
+  const activeTasks = tasks.filter((t) => !t.is_resolved);
+  const completedTasks = tasks.filter((t) => t.is_resolved);
 
-  const filterTasks = (tasks: typeof currentSave.tasks) =>
-    tasks.filter(
+  const filterTasks = (list: TaskDisplay[]) =>
+    list.filter(
       (t) =>
         t.task_name.toLowerCase().includes(search.toLowerCase()) ||
         t.description.toLowerCase().includes(search.toLowerCase())
     );
 
-  const toggleResolved = (taskName: string) => {
-    const updated = currentSave.tasks.map((t) =>
-      t.task_name === taskName ? { ...t, is_resolved: !t.is_resolved } : t
-    );
-    updateField("tasks", updated);
+  const handleAcquire = async () => {
+    await acquireTask(newTask.trim());
+    setNewTask("");
   };
 
-  const toggleNew = (taskName: string) => {
-    const updated = currentSave.tasks.map((t) =>
-      t.task_name === taskName ? { ...t, is_new: !t.is_new } : t
-    );
-    updateField("tasks", updated);
+  const handleAddSubtask = async (taskName: string) => {
+    await addSubtask(taskName, (newSubtasks[taskName] ?? "").trim());
+    setNewSubtasks((s) => ({ ...s, [taskName]: "" }));
   };
 
-  const renderTask = (task: typeof currentSave.tasks[0]) => (
+  const renderTask = (task: TaskDisplay) => (
     <Card key={task.task_name} className={`text-sm transition-all hover:border-primary/30 ${task.is_resolved ? 'bg-muted/20' : 'border-primary/10'}`}>
       <CardHeader className="py-4 px-5 space-y-2">
         <div className="flex items-center justify-between">
@@ -60,7 +62,7 @@ export default function JournalPage() {
                <Label className="text-[10px] font-bold">New</Label>
                <Switch
                 checked={task.is_new}
-                onCheckedChange={() => toggleNew(task.task_name)}
+                onCheckedChange={(checked) => setTaskNew(task.task_name, checked)}
                 className="scale-75"
               />
             </div>
@@ -71,7 +73,7 @@ export default function JournalPage() {
                     <Label className="text-[10px] font-bold">Resolved</Label>
                     <Switch
                       checked={task.is_resolved}
-                      onCheckedChange={() => toggleResolved(task.task_name)}
+                      onCheckedChange={(checked) => (checked ? resolveTask(task.task_name) : unresolveTask(task.task_name))}
                       className="scale-75"
                     />
                   </div>
@@ -79,6 +81,9 @@ export default function JournalPage() {
                 <TooltipContent>Marking as resolved moves it to the Completed tab</TooltipContent>
               </Tooltip>
             </TooltipProvider>
+            <Button variant="ghost" size="sm" onClick={() => removeTask(task.task_name)} title="Remove from journal">
+              <Trash2 className="h-4 w-4" />
+            </Button>
           </div>
         </div>
         <CardDescription className="text-xs leading-relaxed italic text-muted-foreground/80">
@@ -86,19 +91,44 @@ export default function JournalPage() {
         </CardDescription>
       </CardHeader>
       <CardContent className="px-5 pb-4 space-y-3">
-        {task.subtasks && task.subtasks.length > 0 && (
+        {task.subtasks.length > 0 && (
           <div className="space-y-2">
             <Label className="text-[10px] font-bold uppercase tracking-widest text-muted-foreground">Objectives</Label>
             <div className="grid grid-cols-1 gap-1.5">
-              {task.subtasks.map((sub, idx) => (
-                <div key={idx} className="flex items-center gap-2 text-[11px] bg-muted/40 p-2 rounded-md">
+              {task.subtasks.map((sub) => (
+                <div key={sub.name} className="flex items-center gap-2 text-[11px] bg-muted/40 p-2 rounded-md">
                   <div className="h-1 w-1 bg-primary rounded-full" />
-                  {sub.replace(/_/g, " ")}
+                  <span className="flex-1">{sub.description || sub.name.replace(/_/g, " ")}</span>
+                  <button
+                    className="text-muted-foreground hover:text-destructive"
+                    onClick={() => removeSubtask(task.task_name, sub.name)}
+                    title="Remove objective"
+                  >
+                    <X className="h-3 w-3" />
+                  </button>
                 </div>
               ))}
             </div>
           </div>
         )}
+        <div className="flex gap-2">
+          <Input
+            placeholder="Add objective (e.g. TASK.find_song_name)"
+            value={newSubtasks[task.task_name] ?? ""}
+            onChange={(e) => setNewSubtasks((s) => ({ ...s, [task.task_name]: e.target.value }))}
+            onKeyDown={(e) => e.key === "Enter" && handleAddSubtask(task.task_name)}
+            className="h-7 text-[11px] font-mono"
+          />
+          <Button
+            variant="outline"
+            size="sm"
+            className="h-7"
+            disabled={!(newSubtasks[task.task_name] ?? "").trim()}
+            onClick={() => handleAddSubtask(task.task_name)}
+          >
+            <Plus className="h-3 w-3" />
+          </Button>
+        </div>
         <div className="flex items-center justify-between pt-2 border-t border-dashed">
           <div className="flex items-center gap-1.5 text-[10px] font-mono text-muted-foreground">
             <Calendar className="h-3 w-3" />
@@ -123,10 +153,24 @@ export default function JournalPage() {
           <p className="text-sm text-muted-foreground">Track and manage active tasks and completed case objectives.</p>
         </div>
         <Badge variant="outline" className="px-4 py-1">
-          {currentSave.tasks.length} Total Logs
+          {tasks.length} Total Logs
         </Badge>
       </div>
 
+      <div className="flex gap-2">
+        <Input
+          placeholder="Acquire a task from the game data (e.g. TASK.interrogate_vigilantes)"
+          value={newTask}
+          onChange={(e) => setNewTask(e.target.value)}
+          onKeyDown={(e) => e.key === "Enter" && newTask.trim() && handleAcquire()}
+          className="h-10 font-mono text-xs"
+        />
+        <Button onClick={handleAcquire} disabled={!newTask.trim()} className="h-10">
+          <Plus className="h-4 w-4 mr-2" />
+          Acquire
+        </Button>
+      </div>
+
       <div className="relative">
         <Search className="absolute left-3 top-1/2 -translate-y-1/2 h-4 w-4 text-muted-foreground" />
         <Input
@@ -8,17 +8,29 @@ import { Badge } from "@/components/ui/badge";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
-import { Search, DoorOpen, Map, Circle, Info, Hash, Lock, Unlock, Eye, Sparkles } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Search, DoorOpen, Map, Circle, Info, Hash, Lock, Unlock, Eye, EyeOff, Sparkles, CloudFog } from "lucide-react";
+import type { FogStatus } from "@/types";
+
+// Same grouping as the backend: `fog-whirling-kitchen` is in `whirling`
+function unrevealerArea(id: string): string {
+  return id.replace(/^fog-/, "").split("-")[0];
+}
 
 export default function StatesPage() {
-  const { currentSave, updateField } = useStore();
+  const { currentSave, updateField, revealFogArea, hideFogArea } = useStore();
   const [doorSearch, setDoorSearch] = useState("");
   const [areaSearch, setAreaSearch] = useState("");
   const [orbSearch, setOrbSearch] = useState("");
 
   if (!currentSave) return null;
 
-  const { door_states, area_states, shown_orbs } = currentSave;
+  const { door_states, area_states, shown_orbs, fog } = currentSave;
+
+  const fogAreas: Record<string, [string, FogStatus][]> = {};
+  for (const [id, status] of Object.entries(fog.unrevealers)) {
+    (fogAreas[unrevealerArea(id)] ??= []).push([id, status]);
+  }
 
   const doorEntries = Object.entries(door_states).filter(([k]) =>
     k.toLowerCase().includes(doorSearch.toLowerCase())
@@ -58,7 +70,7 @@ export default function StatesPage() {
         </div>
 
         <Tabs defaultValue="doors" className="w-full">
-          <TabsList className="grid grid-cols-3 max-w-[500px] mb-6">
+          <TabsList className="grid grid-cols-4 max-w-[660px] mb-6">
             <TabsTrigger value="doors" className="flex items-center gap-2">
               <DoorOpen className="h-4 w-4" />
               Doors
@@ -74,6 +86,11 @@ export default function StatesPage() {
               Orbs
               <Badge variant="secondary" className="h-5 px-1.5 text-[10px]">{Object.keys(shown_orbs).length}</Badge>
             </TabsTrigger>
+            <TabsTrigger value="fog" className="flex items-center gap-2">
+              <CloudFog className="h-4 w-4" />
+              Fog
+              <Badge variant="secondary" className="h-5 px-1.5 text-[10px]">{Object.keys(fogAreas).length}</Badge>
+            </TabsTrigger>
           </TabsList>
 
           <TabsContent value="doors" className="space-y-4 outline-none">
@@ -192,6 +209,41 @@ export default function StatesPage() {
               </div>
             </ScrollArea>
           </TabsContent>
+
+          <TabsContent value="fog" className="space-y-4 outline-none">
+            <p className="text-xs text-muted-foreground">
+              {fog.fow_file === null
+                ? "This save has no .FOW.json file."
+                : `.FOW.json holds ${Object.keys(fog.fow_file).length} entries.`}
+            </p>
+            <ScrollArea className="h-[calc(100vh-320px)] pr-4">
+              <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-3 pb-8">
+                {Object.entries(fogAreas).sort(([a], [b]) => a.localeCompare(b)).map(([area, unrevealers]) => (
+                  <Card key={area} className="hover:border-primary/20 transition-colors">
+                    <CardHeader className="py-3 px-4 flex flex-row items-center justify-between space-y-0 bg-muted/10 border-b">
+                      <CardTitle className="text-xs font-mono truncate">{area}</CardTitle>
+                      <div className="flex gap-1">
+                        <Button variant="ghost" size="sm" className="h-7 px-2" onClick={() => revealFogArea(area)} title="Reveal">
+                          <Eye className="h-3 w-3" />
+                        </Button>
+                        <Button variant="ghost" size="sm" className="h-7 px-2" onClick={() => hideFogArea(area)} title="Hide">
+                          <EyeOff className="h-3 w-3" />
+                        </Button>
+                      </div>
+                    </CardHeader>
+                    <CardContent className="px-4 py-3 space-y-1">
+                      {unrevealers.map(([id, status]) => (
+                        <div key={id} className="flex items-center justify-between gap-2">
+                          <span className="text-[10px] font-mono truncate text-muted-foreground">{id}</span>
+                          <Badge variant={status === "DONE" ? "default" : "outline"} className="text-[9px] h-4">{status}</Badge>
+                        </div>
+                      ))}
+                    </CardContent>
+                  </Card>
+                ))}
+              </div>
+            </ScrollArea>
+          </TabsContent>
         </Tabs>
       </div>
     </TooltipProvider>
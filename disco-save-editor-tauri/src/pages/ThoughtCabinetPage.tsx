@@ -7,7 +7,7 @@ import { Button } from "@/components/ui/button";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
-import { Search, Brain, Clock, Zap, CheckCircle2, XCircle, Info } from "lucide-react";
+import { Search, Brain, Clock, Zap, CheckCircle2, XCircle, Info, Lock, Unlock } from "lucide-react";
 import type { ThoughtDisplay } from "@/types";
 
 const STATE_ORDER = ["NotAcquired", "Gained", "Processing", "Internalized", "Forgotten"] as const;
@@ -20,7 +20,7 @@ const STATE_COLORS: Record<string, string> = {
 };
 
 export default function ThoughtCabinetPage() {
-  const { currentSave, updateField } = useStore();
+  const { currentSave, updateField, editThoughtCabinet } = useStore();
   const [search, setSearch] = useState("");
   const [filterState, setFilterState] = useState<string>("all");
 
@@ -65,6 +65,9 @@ export default function ThoughtCabinetPage() {
     Processing: currentSave.thoughts.filter((t) => t.state === "Processing").length,
     Internalized: currentSave.thoughts.filter((t) => t.state === "Internalized").length,
   };
+  const { slots, selected_project_name } = currentSave.thought_cabinet;
+  const unlockedSlots = slots.filter((s) => s.status === "FILLED" || s.status === "EMPTY").length;
+  const overfull = counts.Processing + counts.Internalized > unlockedSlots;
 
   return (
     <TooltipProvider>
@@ -95,6 +98,49 @@ export default function ThoughtCabinetPage() {
           <Badge variant="outline" className={STATE_COLORS.Internalized}>{counts.Internalized} Internalized</Badge>
         </div>
 
+        <Card>
+          <CardHeader className="py-3 px-5">
+            <div className="flex items-center justify-between gap-4">
+              <div>
+                <CardTitle className="text-sm">Slots</CardTitle>
+                <CardDescription className="text-xs">
+                  {unlockedSlots} of {slots.length} unlocked
+                  {selected_project_name && <> &middot; Viewing: {selected_project_name}</>}
+                </CardDescription>
+              </div>
+              <div className="flex gap-2 items-center">
+                {overfull && (
+                  <Badge variant="destructive">
+                    {counts.Processing + counts.Internalized} thoughts need slots
+                  </Badge>
+                )}
+                <Button variant="outline" size="sm" onClick={() => editThoughtCabinet("unlock_thought_slot")} className="gap-2">
+                  <Unlock className="h-4 w-4" />
+                  Unlock Slot
+                </Button>
+                <Button variant="outline" size="sm" onClick={() => editThoughtCabinet("lock_thought_slot")} className="gap-2">
+                  <Lock className="h-4 w-4" />
+                  Lock Slot
+                </Button>
+              </div>
+            </div>
+          </CardHeader>
+          <CardContent className="px-5 pb-4">
+            <div className="grid grid-cols-4 sm:grid-cols-6 gap-2">
+              {slots.map((slot, i) => (
+                <div
+                  key={i}
+                  className={`rounded border px-2 py-1.5 text-[10px] truncate ${slot.status === "FILLED" ? "bg-yellow-500/10 border-yellow-500/30" : slot.status === "EMPTY" ? "bg-muted/50" : "opacity-50 border-dashed"}`}
+                  title={slot.thought ?? slot.status}
+                >
+                  <span className="font-bold uppercase text-muted-foreground">{slot.status}</span>
+                  {slot.thought && <div className="truncate">{slot.thought}</div>}
+                </div>
+              ))}
+            </div>
+          </CardContent>
+        </Card>
+
         <div className="flex flex-col sm:flex-row gap-4">
           <div className="relative flex-1">
             <Search className="absolute left-3 top-1/2 -translate-y-1/2 h-4 w-4 text-muted-foreground" />
@@ -136,6 +182,9 @@ export default function ThoughtCabinetPage() {
                           {t.is_cursed && (
                             <Badge variant="destructive" className="text-[10px] uppercase font-bold py-0 h-4">Cursed</Badge>
                           )}
+                          {t.is_fresh && t.state !== "NotAcquired" && (
+                            <Badge variant="outline" className="text-[10px] uppercase font-bold py-0 h-4">New</Badge>
+                          )}
                         </div>
                       </div>
                       <Badge
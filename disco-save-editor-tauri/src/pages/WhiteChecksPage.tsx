@@ -1,85 +1,103 @@
 import { useState } from "react";
 import { useStore } from "@/store";
-import { Card, CardContent, CardHeader, CardTitle, CardDescription } from "@/components/ui/card";
 import { Input } from "@/components/ui/input";
 import { Badge } from "@/components/ui/badge";
 import { Button } from "@/components/ui/button";
-import { Checkbox } from "@/components/ui/checkbox";
 import { Label } from "@/components/ui/label";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { ScrollArea } from "@/components/ui/scroll-area";
-import { Search, RotateCcw } from "lucide-react";
+import { Search, RotateCcw, Trash2, XCircle } from "lucide-react";
+import type { WhiteCheckDisplay } from "@/types";
+
+type CheckList = "failed" | "reopened" | "seen";
 
 export default function WhiteChecksPage() {
   const {
-    currentSave, resetCheckKeys, resetSeenCheckKeys,
-    addResetCheck, removeResetCheck, addResetSeenCheck, removeResetSeenCheck
+    currentSave, reopenWhiteCheck, failWhiteCheck, editWhiteCheck, removeWhiteCheck
   } = useStore();
   const [search, setSearch] = useState("");
 
   if (!currentSave) return null;
+  const { failed, reopened, seen } = currentSave.white_checks;
 
-  const filterChecks = (checks: typeof currentSave.failed_checks) =>
+  const filterChecks = (checks: WhiteCheckDisplay[]) =>
     checks.filter(
       (c) =>
         c.key.toLowerCase().includes(search.toLowerCase()) ||
         c.skill_display_name.toLowerCase().includes(search.toLowerCase()) ||
-        c.flag_name.toLowerCase().includes(search.toLowerCase())
+        c.flag_name.toLowerCase().includes(search.toLowerCase()) ||
+        c.actor_name.toLowerCase().includes(search.toLowerCase())
     );
 
-  const selectAllFailed = () => {
-    currentSave.failed_checks.forEach((c) => {
-      if (!resetCheckKeys.includes(c.key)) addResetCheck(c.key);
-    });
-  };
-
-  const clearAllFailed = () => {
-    resetCheckKeys.forEach((k) => removeResetCheck(k));
+  // One at a time, since each edit starts from the previous result.
+  const removeAll = async (checks: WhiteCheckDisplay[]) => {
+    for (const c of checks) await removeWhiteCheck(c.key);
   };
 
-  const selectAllSeen = () => {
-    currentSave.seen_checks.forEach((c) => {
-      if (!resetSeenCheckKeys.includes(c.key)) addResetSeenCheck(c.key);
-    });
-  };
-
-  const clearAllSeen = () => {
-    resetSeenCheckKeys.forEach((k) => removeResetSeenCheck(k));
-  };
-
-  const renderCheck = (check: typeof currentSave.failed_checks[0], isSeen: boolean) => {
-    const isSelected = isSeen
-      ? resetSeenCheckKeys.includes(check.key)
-      : resetCheckKeys.includes(check.key);
-    const toggle = () => {
-      if (isSeen) {
-        isSelected ? removeResetSeenCheck(check.key) : addResetSeenCheck(check.key);
-      } else {
-        isSelected ? removeResetCheck(check.key) : addResetCheck(check.key);
-      }
-    };
+  const numberField = (label: string, value: number, onChange: (value: number) => void) => (
+    <div className="space-y-1">
+      <Label className="text-[10px] uppercase tracking-wider text-muted-foreground">{label}</Label>
+      <Input
+        type="number"
+        min={0}
+        value={value}
+        onChange={(e) => onChange(parseInt(e.target.value) || 0)}
+        className="h-7 w-20 text-xs"
+      />
+    </div>
+  );
 
-    return (
-      <div
-        key={check.key}
-        className={`flex items-start gap-3 p-3 rounded border transition-colors ${isSelected ? "bg-primary/10 border-primary/30" : "hover:bg-accent/50"}`}
-      >
-        <Checkbox checked={isSelected} onCheckedChange={toggle} className="mt-0.5" />
-        <div className="flex-1 min-w-0">
-          <div className="flex items-center gap-2 flex-wrap">
-            <span className="font-mono text-xs truncate">{check.flag_name}</span>
-            <Badge variant="outline" className="text-[10px]">{check.skill_display_name}</Badge>
-            <Badge variant="secondary" className="text-[10px]">DC {check.difficulty}</Badge>
-          </div>
-          <div className="flex gap-4 mt-1 text-[10px] text-muted-foreground">
-            <span>Skill: {check.last_skill_value}</span>
-            <span>Target: {check.last_target_value}</span>
-            {check.check_precondition && <span>Cond: {check.check_precondition}</span>}
-          </div>
+  const renderCheck = (check: WhiteCheckDisplay, list: CheckList) => (
+    <div key={check.key} className="flex items-start gap-3 p-3 rounded border hover:bg-accent/50 transition-colors">
+      <div className="flex-1 min-w-0">
+        <div className="flex items-center gap-2 flex-wrap">
+          <span className="font-mono text-xs truncate">{check.flag_name}</span>
+          <Badge variant="outline" className="text-[10px]">{check.skill_display_name}</Badge>
+          {check.actor_name && <Badge variant="secondary" className="text-[10px]">{check.actor_name}</Badge>}
+        </div>
+        {check.check_precondition && (
+          <div className="mt-1 text-[10px] text-muted-foreground truncate">Cond: {check.check_precondition}</div>
+        )}
+        <div className="flex gap-3 mt-2">
+          {numberField("Difficulty", check.difficulty, (v) =>
+            editWhiteCheck(check.key, v, check.last_skill_value, check.last_target_value))}
+          {numberField("Last Skill", check.last_skill_value, (v) =>
+            editWhiteCheck(check.key, check.difficulty, v, check.last_target_value))}
+          {numberField("Last Target", check.last_target_value, (v) =>
+            editWhiteCheck(check.key, check.difficulty, check.last_skill_value, v))}
         </div>
       </div>
-    );
-  };
+      <div className="flex items-center gap-1">
+        {list === "failed" ? (
+          <Button variant="ghost" size="sm" onClick={() => reopenWhiteCheck(check.key)} title="Reopen for another try">
+            <RotateCcw className="h-4 w-4" />
+          </Button>
+        ) : (
+          <Button variant="ghost" size="sm" onClick={() => failWhiteCheck(check.key)} title="Mark as failed">
+            <XCircle className="h-4 w-4" />
+          </Button>
+        )}
+        <Button variant="ghost" size="sm" onClick={() => removeWhiteCheck(check.key)} title="Forget this check">
+          <Trash2 className="h-4 w-4" />
+        </Button>
+      </div>
+    </div>
+  );
+
+  const renderList = (checks: WhiteCheckDisplay[], list: CheckList) => (
+    <>
+      <div className="flex gap-2 mb-4">
+        <Button variant="outline" size="sm" onClick={() => removeAll(checks)} disabled={checks.length === 0}>
+          Forget All
+        </Button>
+      </div>
+      <ScrollArea className="h-[calc(100vh-20rem)]">
+        <div className="space-y-2">
+          {filterChecks(checks).map((c) => renderCheck(c, list))}
+        </div>
+      </ScrollArea>
+    </>
+  );
 
   return (
     <div className="space-y-6">
@@ -88,13 +106,6 @@ export default function WhiteChecksPage() {
           <RotateCcw className="h-5 w-5" />
           White Checks
         </h2>
-        <div className="flex items-center gap-2">
-          {resetCheckKeys.length + resetSeenCheckKeys.length > 0 && (
-            <Badge variant="destructive">
-              {resetCheckKeys.length + resetSeenCheckKeys.length} selected for reset
-            </Badge>
-          )}
-        </div>
       </div>
 
       <div className="relative">
@@ -109,37 +120,14 @@ export default function WhiteChecksPage() {
 
       <Tabs defaultValue="failed">
         <TabsList>
-          <TabsTrigger value="failed">
-            Failed Checks ({currentSave.failed_checks.length})
-          </TabsTrigger>
-          <TabsTrigger value="seen">
-            Seen Checks ({currentSave.seen_checks.length})
-          </TabsTrigger>
+          <TabsTrigger value="failed">Failed Checks ({failed.length})</TabsTrigger>
+          <TabsTrigger value="reopened">Reopened ({reopened.length})</TabsTrigger>
+          <TabsTrigger value="seen">Seen Checks ({seen.length})</TabsTrigger>
         </TabsList>
 
-        <TabsContent value="failed">
-          <div className="flex gap-2 mb-4">
-            <Button variant="outline" size="sm" onClick={selectAllFailed}>Select All</Button>
-            <Button variant="outline" size="sm" onClick={clearAllFailed}>Clear Selection</Button>
-          </div>
-          <ScrollArea className="h-[calc(100vh-20rem)]">
-            <div className="space-y-2">
-              {filterChecks(currentSave.failed_checks).map((c) => renderCheck(c, false))}
-            </div>
-          </ScrollArea>
-        </TabsContent>
-
-        <TabsContent value="seen">
-          <div className="flex gap-2 mb-4">
-            <Button variant="outline" size="sm" onClick={selectAllSeen}>Select All</Button>
-            <Button variant="outline" size="sm" onClick={clearAllSeen}>Clear Selection</Button>
-          </div>
-          <ScrollArea className="h-[calc(100vh-20rem)]">
-            <div className="space-y-2">
-              {filterChecks(currentSave.seen_checks).map((c) => renderCheck(c, true))}
-            </div>
-          </ScrollArea>
-        </TabsContent>
+        <TabsContent value="failed">{renderList(failed, "failed")}</TabsContent>
+        <TabsContent value="reopened">{renderList(reopened, "reopened")}</TabsContent>
+        <TabsContent value="seen">{renderList(seen, "seen")}</TabsContent>
       </Tabs>
     </div>
   );
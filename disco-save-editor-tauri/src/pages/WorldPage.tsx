@@ -10,11 +10,140 @@ import { Separator } from "@/components/ui/separator";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
-import { Search, Globe, MapPin, Cloud, Database, Info, Loader2, Sparkles, Wind, Navigation } from "lucide-react";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { Search, Globe, MapPin, Cloud, Database, Info, Loader2, Sparkles, Wind, Navigation, ChevronLeft, ChevronRight, ListChecks, DoorOpen } from "lucide-react";
+import type { DoorDisplay, MatchMode, VariableCategory } from "@/types";
+
+const LUA_PAGE_SIZE = 100;
+const VARIABLE_CATEGORIES: VariableCategory[] = ["tasks", "xp", "reputation", "character", "locations", "inventory"];
+
+function VariableReportCard() {
+  const { variableReport, variableReportLoading, loadVariableReport } = useStore();
+
+  return (
+    <Card className="shadow-md border-primary/10">
+      <CardHeader className="bg-muted/30 border-b">
+        <div className="flex items-center justify-between">
+          <div className="space-y-1">
+            <CardTitle className="text-base font-bold flex items-center gap-2 uppercase">
+              <ListChecks className="h-5 w-5 text-primary" />
+              Changed From Default
+            </CardTitle>
+            <CardDescription className="text-xs">
+              Every variable this playthrough has changed from its initial value, grouped by area.
+            </CardDescription>
+          </div>
+          <Button variant="outline" onClick={loadVariableReport} disabled={variableReportLoading}>
+            {variableReportLoading && <Loader2 className="h-4 w-4 animate-spin mr-2" />}
+            {variableReport ? "Refresh" : "Generate Report"}
+          </Button>
+        </div>
+      </CardHeader>
+      {variableReport && (
+        <CardContent className="pt-4">
+          <p className="text-xs text-muted-foreground mb-3">
+            {variableReport.changed_count} changed in {variableReport.groups.length} groups · {variableReport.unknown.length} not in the game data
+          </p>
+          <ScrollArea className="h-[400px]">
+            <div className="space-y-4 pr-3">
+              {variableReport.groups.map((group) => (
+                <div key={group.prefix}>
+                  <p className="text-xs font-bold uppercase mb-1">
+                    {group.prefix} <span className="text-muted-foreground">({group.variables.length})</span>
+                  </p>
+                  {group.variables.map((v) => (
+                    <div key={v.key} className="flex gap-3 text-[11px] py-0.5">
+                      <span className="font-mono truncate w-1/3">{v.key}</span>
+                      <span className="font-mono text-muted-foreground shrink-0">{v.initial_value} → {v.value}</span>
+                      <span className="truncate text-muted-foreground italic">{v.description}</span>
+                    </div>
+                  ))}
+                </div>
+              ))}
+              {variableReport.unknown.length > 0 && (
+                <div>
+                  <p className="text-xs font-bold uppercase mb-1">
+                    Not in game data <span className="text-muted-foreground">({variableReport.unknown.length})</span>
+                  </p>
+                  {variableReport.unknown.map((v) => (
+                    <div key={v.key} className="flex gap-3 text-[11px] py-0.5">
+                      <span className="font-mono truncate w-1/3">{v.key}</span>
+                      <span className="font-mono text-muted-foreground truncate">{v.value}</span>
+                    </div>
+                  ))}
+                </div>
+              )}
+            </div>
+          </ScrollArea>
+        </CardContent>
+      )}
+    </Card>
+  );
+}
+
+function DoorsCard() {
+  const { currentSave, setDoorOpen } = useStore();
+  if (!currentSave) return null;
+
+  const areas = currentSave.doors.reduce<Record<string, DoorDisplay[]>>((acc, d) => {
+    (acc[d.area || "other"] ??= []).push(d);
+    return acc;
+  }, {});
+
+  return (
+    <Card className="md:col-span-3 shadow-md border-primary/10">
+      <CardHeader className="pb-3 border-b bg-muted/20">
+        <CardTitle className="text-sm font-bold flex items-center gap-2 uppercase tracking-wider">
+          <DoorOpen className="h-4 w-4 text-primary" />
+          Doors
+        </CardTitle>
+        <CardDescription className="text-xs">
+          Opening or locking a door sets both its door state and its dialogue variable, where it has them.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="pt-4 space-y-4">
+        {Object.entries(areas).map(([area, doors]) => (
+          <div key={area}>
+            <p className="text-xs font-bold uppercase mb-1">
+              {area} <span className="text-muted-foreground">({doors.length})</span>
+            </p>
+            {doors.map((d) => {
+              const isOpen = d.is_open_state ?? d.is_open_variable ?? false;
+              const mismatch = d.is_open_state !== null && d.is_open_variable !== null && d.is_open_state !== d.is_open_variable;
+              return (
+                <div key={d.id} className="flex items-center gap-3 text-[11px] py-0.5">
+                  <span className="flex-1 truncate">{d.name}</span>
+                  <span className="font-mono text-muted-foreground truncate w-1/3">
+                    {[d.state_key, d.variable].filter(Boolean).join(" · ")}
+                  </span>
+                  {mismatch && (
+                    <Badge variant="destructive" className="text-[10px]" title="The door state and the variable disagree">
+                      Mismatch
+                    </Badge>
+                  )}
+                  <Label className="text-[10px] uppercase text-muted-foreground">{isOpen ? "Open" : "Locked"}</Label>
+                  <Switch checked={isOpen} onCheckedChange={(v) => setDoorOpen(d.id, v)} className="scale-75" />
+                </div>
+              );
+            })}
+          </div>
+        ))}
+      </CardContent>
+    </Card>
+  );
+}
 
 export default function WorldPage() {
-  const { currentSave, updateField, luaVariables, luaLoading, loadLuaVariables, setLuaEdit, luaEdits } = useStore();
+  const {
+    currentSave, updateField, luaVariables, luaTotal, luaSearch, luaLoading, loadLuaVariables, setLuaEdit, luaEdits,
+  } = useStore();
   const [luaFilter, setLuaFilter] = useState("");
+  const [luaMode, setLuaMode] = useState<MatchMode>("substring");
+  const [luaType, setLuaType] = useState("all");
+  const [luaCategory, setLuaCategory] = useState("all");
+  const [luaChangedOnly, setLuaChangedOnly] = useState(false);
+  const [newVarKey, setNewVarKey] = useState("");
+  const [newVarValue, setNewVarValue] = useState("");
 
   if (!currentSave) return null;
 
@@ -29,9 +158,27 @@ export default function WorldPage() {
   };
 
   const handleLuaSearch = () => {
-    loadLuaVariables(luaFilter || undefined);
+    loadLuaVariables({
+      query: luaFilter,
+      mode: luaMode,
+      var_type: luaType === "all" ? null : luaType,
+      category: luaCategory === "all" ? null : (luaCategory as VariableCategory),
+      changed_only: luaChangedOnly,
+      offset: 0,
+      limit: LUA_PAGE_SIZE,
+    });
+  };
+
+  const pageStart = luaSearch?.offset ?? 0;
+
+  const handleAddVariable = () => {
+    setLuaEdit(newVarKey.trim(), newVarValue);
+    setNewVarKey("");
+    setNewVarValue("");
   };
 
+  const pendingNewVars = Object.keys(luaEdits).filter((key) => !luaVariables.some((v) => v.key === key));
+
   const reputationInfo = {
     communist: { label: "Mazovian Socio-Economics", color: "text-red-500", desc: "Left-wing revolutionary thought." },
     ultraliberal: { label: "Indirect Taxation", color: "text-yellow-500", desc: "Free market and hustle culture." },
@@ -170,6 +317,8 @@ export default function WorldPage() {
               </div>
             </CardContent>
           </Card>
+
+          <DoorsCard />
         </div>
 
         <Separator className="opacity-50" />
@@ -205,7 +354,7 @@ export default function WorldPage() {
                 />
               </div>
               <Button 
-                onClick={handleLuaSearch} 
+                onClick={() => handleLuaSearch()} 
                 className="h-10 px-6 font-bold"
                 disabled={luaLoading}
               >
@@ -214,6 +363,73 @@ export default function WorldPage() {
               </Button>
             </div>
 
+            <div className="flex flex-wrap items-center gap-3">
+              <Select value={luaMode} onValueChange={(v) => setLuaMode(v as MatchMode)}>
+                <SelectTrigger className="h-9 w-36">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="substring">Contains</SelectItem>
+                  <SelectItem value="prefix">Starts with</SelectItem>
+                  <SelectItem value="regex">Regex</SelectItem>
+                </SelectContent>
+              </Select>
+              <Select value={luaType} onValueChange={setLuaType}>
+                <SelectTrigger className="h-9 w-36">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="all">Any type</SelectItem>
+                  <SelectItem value="Boolean">Boolean</SelectItem>
+                  <SelectItem value="Number">Number</SelectItem>
+                  <SelectItem value="String">String</SelectItem>
+                </SelectContent>
+              </Select>
+              <Select value={luaCategory} onValueChange={setLuaCategory}>
+                <SelectTrigger className="h-9 w-40">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="all">Any category</SelectItem>
+                  {VARIABLE_CATEGORIES.map((c) => (
+                    <SelectItem key={c} value={c} className="capitalize">{c}</SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+              <div className="flex items-center gap-2">
+                <Switch id="lua-changed-only" checked={luaChangedOnly} onCheckedChange={setLuaChangedOnly} />
+                <Label htmlFor="lua-changed-only" className="text-xs">Changed from default</Label>
+              </div>
+            </div>
+
+            <div className="flex gap-3">
+              <Input
+                placeholder="Add a variable the game hasn't written yet (e.g. 'apt.door_twenty_opens')"
+                value={newVarKey}
+                onChange={(e) => setNewVarKey(e.target.value)}
+                className="flex-1 h-9 font-mono text-[11px]"
+              />
+              <Input
+                placeholder="Value"
+                value={newVarValue}
+                onChange={(e) => setNewVarValue(e.target.value)}
+                onKeyDown={(e) => e.key === "Enter" && newVarKey.trim() && handleAddVariable()}
+                className="w-48 h-9 font-mono text-[11px]"
+              />
+              <Button variant="outline" className="h-9" disabled={!newVarKey.trim()} onClick={handleAddVariable}>
+                Add
+              </Button>
+            </div>
+            {pendingNewVars.length > 0 && (
+              <div className="flex flex-wrap gap-2">
+                {pendingNewVars.map((key) => (
+                  <Badge key={key} variant="secondary" className="font-mono text-[10px]">
+                    {key} = {luaEdits[key]}
+                  </Badge>
+                ))}
+              </div>
+            )}
+
             {luaVariables.length > 0 ? (
               <div className="rounded-md border bg-muted/10">
                 <ScrollArea className="h-[400px]">
@@ -226,15 +442,25 @@ export default function WorldPage() {
                         <Badge 
                           variant="outline" 
                           className={`text-[9px] font-mono w-14 justify-center py-0.5 ${
-                            v.var_type === 'number' ? 'border-blue-500/30 text-blue-500' : 
-                            v.var_type === 'boolean' ? 'border-green-500/30 text-green-500' : 'border-amber-500/30 text-amber-500'
+                            v.var_type.toLowerCase() === 'number' ? 'border-blue-500/30 text-blue-500' : 
+                            v.var_type.toLowerCase() === 'boolean' ? 'border-green-500/30 text-green-500' : 'border-amber-500/30 text-amber-500'
                           }`}
                         >
                           {v.var_type.toUpperCase()}
                         </Badge>
-                        <span className="font-mono text-[11px] flex-1 truncate text-foreground/80 group-hover:text-primary transition-colors">
-                          {v.key}
-                        </span>
+                        <div className="flex-1 min-w-0">
+                          <p className="font-mono text-[11px] truncate text-foreground/80 group-hover:text-primary transition-colors">
+                            {v.key}
+                          </p>
+                          {v.description && (
+                            <p className="text-[10px] truncate text-muted-foreground">{v.description}</p>
+                          )}
+                        </div>
+                        {v.differs_from_initial && (
+                          <Badge variant="secondary" className="text-[9px]" title={`Default: ${v.initial_value}`}>
+                            CHANGED
+                          </Badge>
+                        )}
                         <div className="flex items-center gap-2">
                            <Input
                             value={luaEdits[v.key] ?? v.value}
@@ -246,6 +472,29 @@ export default function WorldPage() {
                     ))}
                   </div>
                 </ScrollArea>
+                <div className="flex items-center justify-between px-3 py-2 border-t border-primary/5 text-[11px] text-muted-foreground">
+                  <span>
+                    {pageStart + 1}–{pageStart + luaVariables.length} of {luaTotal}
+                  </span>
+                  <div className="flex gap-1">
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      disabled={luaLoading || pageStart === 0}
+                      onClick={() => luaSearch && loadLuaVariables({ ...luaSearch, offset: Math.max(0, pageStart - LUA_PAGE_SIZE) })}
+                    >
+                      <ChevronLeft className="h-4 w-4" />
+                    </Button>
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      disabled={luaLoading || pageStart + luaVariables.length >= luaTotal}
+                      onClick={() => luaSearch && loadLuaVariables({ ...luaSearch, offset: pageStart + LUA_PAGE_SIZE })}
+                    >
+                      <ChevronRight className="h-4 w-4" />
+                    </Button>
+                  </div>
+                </div>
               </div>
             ) : luaLoading ? (
               <div className="h-[200px] flex flex-col items-center justify-center border-2 border-dashed rounded-xl opacity-50">
@@ -254,11 +503,15 @@ export default function WorldPage() {
               </div>
             ) : (
               <div className="h-[100px] flex items-center justify-center border-2 border-dashed rounded-xl opacity-40">
-                <p className="text-xs uppercase tracking-widest font-bold">Search to view variables</p>
+                <p className="text-xs uppercase tracking-widest font-bold">
+                  {luaSearch ? "No matching variables" : "Search to view variables"}
+                </p>
               </div>
             )}
           </CardContent>
         </Card>
+
+        <VariableReportCard />
       </div>
     </TooltipProvider>
   );
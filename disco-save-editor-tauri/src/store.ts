@@ -1,13 +1,48 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import type {
+  AppError,
   FullSaveState,
   SaveSummary,
   CatalogItem,
+  SkillBreakdown,
   LuaVariableDisplay,
+  LuaSearchQuery,
+  LuaSearchPage,
+  VariableReport,
   SaveUpdatePayload,
+  SnapshotInfo,
+  SnapshotDiff,
+  FogState,
+  GameTimestamp,
+  JournalState,
+  WhiteChecksState,
+  ThoughtCabinetDisplay,
+  InventoryItemDisplay,
+  ContainerDisplay,
+  ContainerItemDisplay,
+  DoorDisplay,
+  SaveDiff,
+  TimeAdvance,
 } from "./types";
 
+function errorMessage(e: unknown): string {
+  if (typeof e === "object" && e !== null && "message" in e) {
+    return (e as AppError).message;
+  }
+  return String(e);
+}
+
+/** The save's in-game clock, for journal entries made now */
+function gameTime(save: FullSaveState): GameTimestamp {
+  return {
+    day_counter: save.clock.day,
+    real_day_counter: save.clock.real_day,
+    day_minutes: save.clock.hours * 60 + save.clock.minutes,
+    seconds: save.clock.seconds,
+  };
+}
+
 interface AppStore {
   // UI
   darkMode: boolean;
@@ -18,41 +53,93 @@ interface AppStore {
   savesLoading: boolean;
   discoverSaves: () => Promise<void>;
   locateSaveFile: () => Promise<void>;
+  extraSaveRoots: string[];
+  loadExtraSaveRoots: () => Promise<void>;
+  addSaveRoot: () => Promise<void>;
+  removeSaveRoot: (root: string) => Promise<void>;
+  saveDiff: SaveDiff | null;
+  diffSaves: (a: string, b: string) => Promise<void>;
+  clearSaveDiff: () => void;
 
   // Current save
   currentSave: FullSaveState | null;
   saveLoading: boolean;
   dirty: boolean;
-  loadSave: (path: string) => Promise<void>;
+  loadSave: (path: string, salvage?: boolean) => Promise<void>;
 
   // Lua variables (loaded on demand)
   luaVariables: LuaVariableDisplay[];
+  luaTotal: number;
+  luaSearch: LuaSearchQuery | null;
   luaLoading: boolean;
-  loadLuaVariables: (filter?: string) => Promise<void>;
+  loadLuaVariables: (search: LuaSearchQuery) => Promise<void>;
+
+  // Changed-from-default report
+  variableReport: VariableReport | null;
+  variableReportLoading: boolean;
+  loadVariableReport: () => Promise<void>;
 
   // Catalog items
   catalogItems: CatalogItem[];
   catalogLoading: boolean;
   loadCatalogItems: () => Promise<void>;
 
+  // Modifier breakdown of one skill, as last saved
+  skillBreakdown: SkillBreakdown | null;
+  loadSkillBreakdown: (skillType: string) => Promise<void>;
+  clearSkillBreakdown: () => void;
+
   // Mutations
   luaEdits: Record<string, string>;
-  resetCheckKeys: string[];
-  resetSeenCheckKeys: string[];
   updateField: <K extends keyof FullSaveState>(
     key: K,
     value: FullSaveState[K]
   ) => void;
   setLuaEdit: (key: string, value: string) => void;
-  addResetCheck: (key: string) => void;
-  removeResetCheck: (key: string) => void;
-  addResetSeenCheck: (key: string) => void;
-  removeResetSeenCheck: (key: string) => void;
+  revealFogArea: (area: string) => Promise<void>;
+  hideFogArea: (area: string) => Promise<void>;
+  editJournal: (command: string, args: Record<string, unknown>) => Promise<void>;
+  acquireTask: (taskName: string) => Promise<void>;
+  removeTask: (taskName: string) => Promise<void>;
+  resolveTask: (taskName: string) => Promise<void>;
+  unresolveTask: (taskName: string) => Promise<void>;
+  addSubtask: (taskName: string, subtask: string) => Promise<void>;
+  removeSubtask: (taskName: string, subtask: string) => Promise<void>;
+  setTaskNew: (taskName: string, isNew: boolean) => Promise<void>;
+  editWhiteChecks: (command: string, args: Record<string, unknown>) => Promise<void>;
+  reopenWhiteCheck: (key: string) => Promise<void>;
+  failWhiteCheck: (key: string) => Promise<void>;
+  editWhiteCheck: (key: string, difficulty: number, lastSkillValue: number, lastTargetValue: number) => Promise<void>;
+  removeWhiteCheck: (key: string) => Promise<void>;
+  editThoughtCabinet: (command: string) => Promise<void>;
+  editInventory: (command: string, args: Record<string, unknown>) => Promise<void>;
+  addInventoryItem: (name: string) => Promise<void>;
+  removeInventoryItem: (name: string) => Promise<void>;
+  editInventoryItem: (name: string, isFresh: boolean, substanceUses: number, substanceTimeLeft: number) => Promise<void>;
+  equipInventoryItem: (name: string) => Promise<void>;
+  unequipInventoryItem: (name: string) => Promise<void>;
+  editContainers: (command: string, args: Record<string, unknown>) => Promise<void>;
+  addContainerItem: (containerId: string, item: ContainerItemDisplay) => Promise<void>;
+  removeContainerItem: (containerId: string, index: number) => Promise<void>;
+  editContainerItem: (containerId: string, index: number, item: ContainerItemDisplay) => Promise<void>;
+  rerollContainers: (target: string, seed: number) => Promise<void>;
+  setDoorOpen: (id: string, isOpen: boolean) => Promise<void>;
+  advanceTime: (minutes: number) => Promise<void>;
 
   // Save to disk
   saving: boolean;
   saveChanges: () => Promise<void>;
 
+  // Snapshots of the current save
+  snapshots: SnapshotInfo[];
+  snapshotsLoading: boolean;
+  snapshotDiff: SnapshotDiff | null;
+  loadSnapshots: () => Promise<void>;
+  createSnapshot: (label: string | null) => Promise<void>;
+  diffSnapshot: (from: string, to: string | null) => Promise<void>;
+  restoreSnapshot: (id: string) => Promise<void>;
+  pruneSnapshots: () => Promise<void>;
+
   // Error
   error: string | null;
   clearError: () => void;
@@ -77,7 +164,7 @@ export const useStore = create<AppStore>((set, get) => ({
       const saves = await invoke<SaveSummary[]>("discover_saves");
       set({ saves, savesLoading: false });
     } catch (e) {
-      set({ savesLoading: false, error: String(e) });
+      set({ savesLoading: false, error: errorMessage(e) });
     }
   },
   locateSaveFile: async () => {
@@ -87,36 +174,94 @@ export const useStore = create<AppStore>((set, get) => ({
         await get().loadSave(path);
       }
     } catch (e) {
-      set({ error: String(e) });
+      set({ error: errorMessage(e) });
+    }
+  },
+  extraSaveRoots: [],
+  loadExtraSaveRoots: async () => {
+    try {
+      const extraSaveRoots = await invoke<string[]>("get_extra_save_roots");
+      set({ extraSaveRoots });
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  addSaveRoot: async () => {
+    try {
+      const root = await invoke<string | null>("pick_save_folder");
+      if (root && !get().extraSaveRoots.includes(root)) {
+        const roots = [...get().extraSaveRoots, root];
+        await invoke("set_extra_save_roots", { roots });
+        set({ extraSaveRoots: roots });
+        await get().discoverSaves();
+      }
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  removeSaveRoot: async (root: string) => {
+    try {
+      const roots = get().extraSaveRoots.filter((r) => r !== root);
+      await invoke("set_extra_save_roots", { roots });
+      set({ extraSaveRoots: roots });
+      await get().discoverSaves();
+    } catch (e) {
+      set({ error: errorMessage(e) });
     }
   },
+  saveDiff: null,
+  diffSaves: async (a: string, b: string) => {
+    try {
+      const saveDiff = await invoke<SaveDiff>("diff_saves", { a, b });
+      set({ saveDiff });
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  clearSaveDiff: () => set({ saveDiff: null }),
 
   currentSave: null,
   saveLoading: false,
   dirty: false,
-  loadSave: async (path: string) => {
-    set({ saveLoading: true, error: null, dirty: false, luaEdits: {}, resetCheckKeys: [], resetSeenCheckKeys: [] });
+  loadSave: async (path: string, salvage = false) => {
+    set({
+      saveLoading: true, error: null, dirty: false, luaEdits: {},
+      luaVariables: [], luaTotal: 0, luaSearch: null, variableReport: null,
+    });
     try {
       const state = await invoke<FullSaveState>("load_save", {
         folderPath: path,
+        salvage,
       });
       set({ currentSave: state, saveLoading: false });
     } catch (e) {
-      set({ saveLoading: false, error: String(e) });
+      set({ saveLoading: false, error: errorMessage(e) });
     }
   },
 
   luaVariables: [],
+  luaTotal: 0,
+  luaSearch: null,
   luaLoading: false,
-  loadLuaVariables: async (filter?: string) => {
+  loadLuaVariables: async (search) => {
     set({ luaLoading: true });
     try {
-      const vars = await invoke<LuaVariableDisplay[]>("get_lua_variables", {
-        filter: filter || null,
-      });
-      set({ luaVariables: vars, luaLoading: false });
+      const page = await invoke<LuaSearchPage>("get_lua_variables", { search });
+      set({ luaVariables: page.variables, luaTotal: page.total, luaSearch: search, luaLoading: false });
     } catch (e) {
-      set({ luaLoading: false, error: String(e) });
+      set({ luaLoading: false, error: errorMessage(e) });
+    }
+  },
+
+  variableReport: null,
+  variableReportLoading: false,
+  loadVariableReport: async () => {
+    set({ variableReportLoading: true });
+    try {
+      const report = await invoke<VariableReport>("get_variable_report");
+      set({ variableReport: report, variableReportLoading: false });
+    } catch (e) {
+      set({ variableReportLoading: false, error: errorMessage(e) });
     }
   },
 
@@ -128,13 +273,22 @@ export const useStore = create<AppStore>((set, get) => ({
       const items = await invoke<CatalogItem[]>("get_catalog_items");
       set({ catalogItems: items, catalogLoading: false });
     } catch (e) {
-      set({ catalogLoading: false, error: String(e) });
+      set({ catalogLoading: false, error: errorMessage(e) });
     }
   },
 
+  skillBreakdown: null,
+  loadSkillBreakdown: async (skillType) => {
+    try {
+      const breakdown = await invoke<SkillBreakdown>("get_skill_breakdown", { skillType });
+      set({ skillBreakdown: breakdown });
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  clearSkillBreakdown: () => set({ skillBreakdown: null }),
+
   luaEdits: {},
-  resetCheckKeys: [],
-  resetSeenCheckKeys: [],
   updateField: (key, value) => {
     set((s) => {
       if (!s.currentSave) return s;
@@ -150,27 +304,133 @@ export const useStore = create<AppStore>((set, get) => ({
       dirty: true,
     }));
   },
-  addResetCheck: (key) => {
-    set((s) => ({
-      resetCheckKeys: [...s.resetCheckKeys, key],
-      dirty: true,
-    }));
+  revealFogArea: async (area) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const fog = await invoke<FogState>("reveal_fog_area", { fog: save.fog, area });
+      get().updateField("fog", fog);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
   },
-  removeResetCheck: (key) => {
-    set((s) => ({
-      resetCheckKeys: s.resetCheckKeys.filter((k) => k !== key),
-    }));
+  hideFogArea: async (area) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const fog = await invoke<FogState>("hide_fog_area", { fog: save.fog, area });
+      get().updateField("fog", fog);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
   },
-  addResetSeenCheck: (key) => {
-    set((s) => ({
-      resetSeenCheckKeys: [...s.resetSeenCheckKeys, key],
-      dirty: true,
-    }));
+  editJournal: async (command, args) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const journal = await invoke<JournalState>(command, { journal: save.journal, ...args });
+      get().updateField("journal", journal);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
   },
-  removeResetSeenCheck: (key) => {
-    set((s) => ({
-      resetSeenCheckKeys: s.resetSeenCheckKeys.filter((k) => k !== key),
-    }));
+  acquireTask: async (taskName) => {
+    const save = get().currentSave;
+    if (save) await get().editJournal("acquire_task", { taskName, time: gameTime(save) });
+  },
+  removeTask: (taskName) => get().editJournal("remove_task", { taskName }),
+  resolveTask: async (taskName) => {
+    const save = get().currentSave;
+    if (save) await get().editJournal("resolve_task", { taskName, time: gameTime(save) });
+  },
+  unresolveTask: (taskName) => get().editJournal("unresolve_task", { taskName }),
+  addSubtask: async (taskName, subtask) => {
+    const save = get().currentSave;
+    if (save) await get().editJournal("add_subtask", { taskName, subtask, time: gameTime(save) });
+  },
+  removeSubtask: (taskName, subtask) => get().editJournal("remove_subtask", { taskName, subtask }),
+  setTaskNew: (taskName, isNew) => get().editJournal("set_task_new", { taskName, isNew }),
+  editWhiteChecks: async (command, args) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const whiteChecks = await invoke<WhiteChecksState>(command, { whiteChecks: save.white_checks, ...args });
+      get().updateField("white_checks", whiteChecks);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  reopenWhiteCheck: (key) => get().editWhiteChecks("reopen_white_check", { key }),
+  failWhiteCheck: (key) => get().editWhiteChecks("fail_white_check", { key }),
+  editWhiteCheck: (key, difficulty, lastSkillValue, lastTargetValue) =>
+    get().editWhiteChecks("edit_white_check", { key, difficulty, lastSkillValue, lastTargetValue }),
+  removeWhiteCheck: (key) => get().editWhiteChecks("remove_white_check", { key }),
+  editThoughtCabinet: async (command) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const cabinet = await invoke<ThoughtCabinetDisplay>(command, { thoughtCabinet: save.thought_cabinet });
+      get().updateField("thought_cabinet", cabinet);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  editInventory: async (command, args) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const ownedItems = await invoke<InventoryItemDisplay[]>(command, { ownedItems: save.owned_items, ...args });
+      get().updateField("owned_items", ownedItems);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  addInventoryItem: (name) => get().editInventory("add_inventory_item", { name }),
+  removeInventoryItem: (name) => get().editInventory("remove_inventory_item", { name }),
+  editInventoryItem: (name, isFresh, substanceUses, substanceTimeLeft) =>
+    get().editInventory("edit_inventory_item", { name, isFresh, substanceUses, substanceTimeLeft }),
+  equipInventoryItem: (name) => get().editInventory("equip_inventory_item", { name }),
+  unequipInventoryItem: (name) => get().editInventory("unequip_inventory_item", { name }),
+  editContainers: async (command, args) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const containers = await invoke<ContainerDisplay[]>(command, { containers: save.containers, ...args });
+      get().updateField("containers", containers);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  addContainerItem: (containerId, item) => get().editContainers("add_container_item", { containerId, item }),
+  removeContainerItem: (containerId, index) => get().editContainers("remove_container_item", { containerId, index }),
+  editContainerItem: (containerId, index, item) =>
+    get().editContainers("edit_container_item", { containerId, index, item }),
+  rerollContainers: (target, seed) => get().editContainers("reroll_containers", { target, seed }),
+  setDoorOpen: async (id, isOpen) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const doors = await invoke<DoorDisplay[]>("set_door_open", { doors: save.doors, id, isOpen });
+      get().updateField("doors", doors);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  advanceTime: async (minutes) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const advanced = await invoke<TimeAdvance>("advance_time", {
+        clock: save.clock,
+        thoughts: save.thoughts,
+        ownedItems: save.owned_items,
+        partyState: save.party_state,
+        minutes,
+      });
+      set((s) => s.currentSave ? { currentSave: { ...s.currentSave, ...advanced }, dirty: true } : s);
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
   },
 
   saving: false,
@@ -188,14 +448,13 @@ export const useStore = create<AppStore>((set, get) => ({
         money: s.currentSave.money,
         health: s.currentSave.health,
         morale: s.currentSave.morale,
-        day: s.currentSave.day,
-        hours: s.currentSave.hours,
-        minutes: s.currentSave.minutes,
+        clock: s.currentSave.clock,
         abilities: s.currentSave.abilities,
         skills: s.currentSave.skills,
         owned_items: s.currentSave.owned_items,
         bullets: s.currentSave.bullets,
         thoughts: s.currentSave.thoughts,
+        thought_cabinet: s.currentSave.thought_cabinet,
         area_id: s.currentSave.area_id,
         party_state: s.currentSave.party_state,
         hud_state: s.currentSave.hud_state,
@@ -204,16 +463,87 @@ export const useStore = create<AppStore>((set, get) => ({
         weather_preset: s.currentSave.weather_preset,
         reputation: s.currentSave.reputation,
         lua_edits: s.luaEdits,
-        reset_check_keys: s.resetCheckKeys,
-        reset_seen_check_keys: s.resetSeenCheckKeys,
-        door_states: s.currentSave.door_states,
+        doors: s.currentSave.doors,
         area_states: s.currentSave.area_states,
         shown_orbs: s.currentSave.shown_orbs,
+        fog: s.currentSave.fog,
+        journal: s.currentSave.journal,
+        white_checks: s.currentSave.white_checks,
+        containers: s.currentSave.containers,
       };
-      await invoke("save_changes", { payload });
-      set({ saving: false, dirty: false, luaEdits: {}, resetCheckKeys: [], resetSeenCheckKeys: [] });
+      try {
+        await invoke("save_changes", { payload });
+      } catch (e) {
+        if ((e as AppError)?.kind !== "salvage_unconfirmed" || !window.confirm(errorMessage(e))) throw e;
+        await invoke("save_changes", { payload: { ...payload, discard_salvaged: true } });
+      }
+      set((st) => ({
+        saving: false, dirty: false, luaEdits: {},
+        currentSave: st.currentSave && { ...st.currentSave, lua_salvage: null },
+      }));
+      const { luaSearch, loadLuaVariables, variableReport, loadVariableReport } = get();
+      if (luaSearch) await loadLuaVariables(luaSearch);
+      if (variableReport) await loadVariableReport();
+    } catch (e) {
+      set({ saving: false, error: errorMessage(e) });
+    }
+  },
+
+  snapshots: [],
+  snapshotsLoading: false,
+  snapshotDiff: null,
+  loadSnapshots: async () => {
+    const save = get().currentSave;
+    if (!save) return;
+    set({ snapshotsLoading: true });
+    try {
+      const snapshots = await invoke<SnapshotInfo[]>("list_snapshots", { savePath: save.folder_path });
+      set({ snapshots, snapshotsLoading: false });
+    } catch (e) {
+      set({ snapshotsLoading: false, error: errorMessage(e) });
+    }
+  },
+  createSnapshot: async (label: string | null) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      await invoke<SnapshotInfo>("create_snapshot", { savePath: save.folder_path, label });
+      await get().loadSnapshots();
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  diffSnapshot: async (from: string, to: string | null) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      const snapshotDiff = await invoke<SnapshotDiff>("diff_snapshots", { savePath: save.folder_path, from, to });
+      set({ snapshotDiff });
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  restoreSnapshot: async (id: string) => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      await invoke<SnapshotInfo>("restore_snapshot", { savePath: save.folder_path, id });
+      set({ snapshotDiff: null });
+      await get().loadSave(save.folder_path);
+      await get().loadSnapshots();
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+  pruneSnapshots: async () => {
+    const save = get().currentSave;
+    if (!save) return;
+    try {
+      await invoke<SnapshotInfo[]>("prune_snapshots", { savePath: save.folder_path });
+      set({ snapshotDiff: null });
+      await get().loadSnapshots();
     } catch (e) {
-      set({ saving: false, error: String(e) });
+      set({ error: errorMessage(e) });
     }
   },
 
@@ -1,9 +1,119 @@
 // Mirrors the Rust FullSaveState and related types
 
+export type FileRole =
+  | "first"
+  | "second"
+  | "lua_database"
+  | "states"
+  | "fow"
+  | "archive"
+  | "snapshot"
+  | "game_data"
+  | "settings";
+
+// Mirrors the Rust AppError as serialized over IPC
+export interface AppError {
+  kind:
+    | "invalid_path"
+    | "missing_file"
+    | "io"
+    | "zip"
+    | "json"
+    | "lua_parse"
+    | "unknown_snapshot"
+    | "unknown_variable"
+    | "salvage_unconfirmed"
+    | "invalid_value"
+    | "game_data_unavailable"
+    | "state_poisoned"
+    | "not_loaded"
+    | "dialog";
+  message: string;
+  role?: FileRole;
+  path?: string;
+  json_path?: string;
+  line?: number;
+  column?: number;
+  offset?: number;
+  expected?: string;
+  found?: number | null;
+  key?: string;
+  missing?: string;
+  id?: string;
+  skipped?: ByteRange[];
+}
+
+export interface ByteRange {
+  start: number;
+  end: number;
+}
+
+export interface LuaSalvageReport {
+  skipped: ByteRange[];
+  first_error: string;
+}
+
 export interface SaveSummary {
   name: string;
   path: string;
+  /** RFC 3339, UTC */
   last_modified: string;
+  is_zip: boolean;
+  snapshot_count: number;
+  details: SaveDetails | null;
+}
+
+export interface SaveDetails {
+  area_id: string;
+  day: number;
+  hours: number;
+  minutes: number;
+  level: number;
+  xp_amount: number;
+  money: number;
+}
+
+export interface SnapshotInfo {
+  id: string;
+  /** RFC 3339, UTC */
+  created_at: string;
+  hash: string;
+  label: string | null;
+  save_path: string;
+  size: number;
+  details: SaveDetails | null;
+}
+
+export type FileChangeKind = "added" | "removed" | "modified";
+
+export interface FileChange {
+  name: string;
+  change: FileChangeKind;
+}
+
+export interface SnapshotDiff {
+  from: SnapshotInfo;
+  /** null when comparing against the save as it is now */
+  to: SnapshotInfo | null;
+  to_details: SaveDetails | null;
+  files: FileChange[];
+}
+
+export interface ValueChange {
+  /** JSON pointer for the JSON files, dotted key for Lua and states */
+  key: string;
+  before: unknown | null;
+  after: unknown | null;
+  label: string | null;
+}
+
+export interface SaveDiff {
+  a: string;
+  b: string;
+  first: ValueChange[];
+  second: ValueChange[];
+  lua: ValueChange[];
+  states: ValueChange[];
 }
 
 export interface FullSaveState {
@@ -15,15 +125,14 @@ export interface FullSaveState {
   money: number;
   health: number;
   morale: number;
-  day: number;
-  hours: number;
-  minutes: number;
+  clock: GameClock;
   abilities: AbilityDisplay[];
   skills: SkillDisplay[];
   owned_items: InventoryItemDisplay[];
   bullets: number;
   thoughts: ThoughtDisplay[];
-  tasks: TaskDisplay[];
+  thought_cabinet: ThoughtCabinetDisplay;
+  journal: JournalState;
   area_id: string;
   party_state: PartyState;
   hud_state: HudStateDisplay;
@@ -32,12 +141,13 @@ export interface FullSaveState {
   weather_preset: number;
   reputation: ReputationDisplay;
   lua_variable_count: number;
-  failed_checks: WhiteCheckDisplay[];
-  seen_checks: WhiteCheckDisplay[];
+  white_checks: WhiteChecksState;
   containers: ContainerDisplay[];
-  door_states: Record<string, boolean>;
+  doors: DoorDisplay[];
   area_states: Record<string, number>;
   shown_orbs: Record<string, number>;
+  fog: FogState;
+  lua_salvage: LuaSalvageReport | null;
 }
 
 export interface AbilityDisplay {
@@ -64,6 +174,25 @@ export interface SkillDisplay {
   modifier_count: number;
 }
 
+export interface ModifierDisplay {
+  modifier_type: string;
+  amount: number;
+  explanation: string;
+  cause_key: string | null;
+  cause_type: string | null;
+  cause_name: string | null;
+}
+
+export interface SkillBreakdown {
+  skill_type: string;
+  display_name: string;
+  calculated_ability: number;
+  rank_value: number;
+  modifiers: ModifierDisplay[];
+  modifier_sum: number;
+  total: number;
+}
+
 export interface InventoryItemDisplay {
   name: string;
   display_name: string;
@@ -76,6 +205,9 @@ export interface InventoryItemDisplay {
   is_cursed: boolean;
   is_substance: boolean;
   substance_uses: number;
+  substance_time_left: number;
+  is_fresh: boolean;
+  stack_name: string;
 }
 
 export interface ThoughtDisplay {
@@ -91,15 +223,76 @@ export interface ThoughtDisplay {
   is_cursed: boolean;
   state: string;
   time_left: number;
+  is_fresh: boolean;
+}
+
+export type SlotStatus = "FILLED" | "EMPTY" | "BUYABLE" | "LOCKED";
+
+export interface CabinetSlot {
+  status: SlotStatus;
+  thought: string | null;
+}
+
+export interface ThoughtCabinetDisplay {
+  slots: CabinetSlot[];
+  selected_project_name: string;
+}
+
+export interface GameTimestamp {
+  day_counter: number;
+  real_day_counter: number;
+  day_minutes: number;
+  seconds: number;
+}
+
+export interface GameClock {
+  day: number;
+  real_day: number;
+  hours: number;
+  minutes: number;
+  seconds: number;
+  /** Time shown instead of the clock during scripted scenes */
+  time_override: GameTimestamp | null;
+}
+
+export interface TimeAdvance {
+  clock: GameClock;
+  thoughts: ThoughtDisplay[];
+  owned_items: InventoryItemDisplay[];
+  party_state: PartyState;
 }
 
 export interface TaskDisplay {
   task_name: string;
   description: string;
   acquired_time: string;
+  acquired: GameTimestamp;
+  /** null while the task is active */
+  resolved: GameTimestamp | null;
   is_resolved: boolean;
+  /** Not yet looked at in the journal */
   is_new: boolean;
-  subtasks: string[];
+  subtasks: SubtaskDisplay[];
+}
+
+export interface SubtaskDisplay {
+  name: string;
+  description: string;
+  acquired: GameTimestamp;
+}
+
+export interface JournalState {
+  tasks: TaskDisplay[];
+  last_active_task: string;
+  last_done_task: string;
+}
+
+export type FogStatus = "UNSEEN" | "ACTIVE" | "DONE";
+
+export interface FogState {
+  unrevealers: Record<string, FogStatus>;
+  /** `.FOW.json` contents, null when the save has none */
+  fow_file: Record<string, unknown> | null;
 }
 
 export interface PartyState {
@@ -161,12 +354,32 @@ export interface WhiteCheckDisplay {
   last_target_value: number;
   check_precondition: string;
   is_seen_only: boolean;
+  actor_name: string;
+}
+
+export interface WhiteChecksState {
+  failed: WhiteCheckDisplay[];
+  reopened: WhiteCheckDisplay[];
+  seen: WhiteCheckDisplay[];
+}
+
+/** A door from `DoorStates`, its `*.door_*_opens` variable, or both */
+export interface DoorDisplay {
+  id: string;
+  name: string;
+  area: string;
+  state_key: string | null;
+  is_open_state: boolean | null;
+  variable: string | null;
+  is_open_variable: boolean | null;
 }
 
 export interface ContainerDisplay {
   container_id: string;
+  area: string;
   item_count: number;
   total_value: number;
+  is_shared: boolean;
   items: ContainerItemDisplay[];
 }
 
@@ -184,6 +397,43 @@ export interface LuaVariableDisplay {
   value: string;
   var_type: string;
   description: string;
+  category: VariableCategory | null;
+  /** null for variables not in the game data */
+  initial_value: string | null;
+  differs_from_initial: boolean;
+}
+
+export type VariableCategory = "tasks" | "xp" | "reputation" | "character" | "locations" | "inventory";
+
+export type MatchMode = "substring" | "prefix" | "regex";
+
+export interface LuaSearchQuery {
+  query: string;
+  mode: MatchMode;
+  /** "String", "Number" or "Boolean" */
+  var_type: string | null;
+  category: VariableCategory | null;
+  changed_only: boolean;
+  offset: number;
+  limit: number;
+}
+
+export interface LuaSearchPage {
+  total: number;
+  offset: number;
+  variables: LuaVariableDisplay[];
+}
+
+export interface VariableGroup {
+  prefix: string;
+  variables: LuaVariableDisplay[];
+}
+
+export interface VariableReport {
+  changed_count: number;
+  groups: VariableGroup[];
+  /** Variables in the save that the game data doesn't define */
+  unknown: LuaVariableDisplay[];
 }
 
 export interface CatalogItem {
@@ -205,14 +455,13 @@ export interface SaveUpdatePayload {
   money: number;
   health: number;
   morale: number;
-  day: number;
-  hours: number;
-  minutes: number;
+  clock: GameClock;
   abilities: AbilityDisplay[];
   skills: SkillDisplay[];
   owned_items: InventoryItemDisplay[];
   bullets: number;
   thoughts: ThoughtDisplay[];
+  thought_cabinet: ThoughtCabinetDisplay;
   area_id: string;
   party_state: PartyState;
   hud_state: HudStateDisplay;
@@ -221,9 +470,13 @@ export interface SaveUpdatePayload {
   weather_preset: number;
   reputation: ReputationDisplay;
   lua_edits: Record<string, string>;
-  reset_check_keys: string[];
-  reset_seen_check_keys: string[];
-  door_states: Record<string, boolean>;
+  white_checks: WhiteChecksState;
+  containers: ContainerDisplay[];
+  doors: DoorDisplay[];
   area_states: Record<string, number>;
   shown_orbs: Record<string, number>;
+  fog: FogState;
+  journal: JournalState;
+  // Set once the user agreed to lose the bytes a salvage load skipped
+  discard_salvaged?: boolean;
 }